@@ -0,0 +1,75 @@
+//! Экспорт одобренных записей в файл fortune(6) с индексом strfile.
+//!
+//! cargo run --bin fortune -- <out> [--category <category>]... [--tag <tag>]...
+//!     [--author <username>] [--lang <language>]
+//!
+//! Рядом с файлом `<out>` создается индекс `<out>.dat`.
+
+use std::{env, fs, process};
+
+use jokehub::{
+    db::mongo::{self, shrimp::aggregation::Qilter},
+    model::{fortune::Fortune, shrimp::Category},
+};
+
+fn usage() -> ! {
+    eprintln!(
+        "Usage: fortune <out> [--category <category>]... [--tag <tag>]... \\
+         [--author <username>] [--lang <language>]"
+    );
+    process::exit(2)
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let (mut path, mut author, mut lang) = (None, None, None);
+    let (mut categories, mut tags): (Vec<Category>, Vec<String>) = (Vec::new(), Vec::new());
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--category" => {
+                let category = args.next().unwrap_or_else(|| usage());
+                match serde_json::from_value(serde_json::Value::String(category)) {
+                    Ok(category) => categories.push(category),
+                    Err(_) => usage(),
+                }
+            }
+            "--tag" => tags.push(args.next().unwrap_or_else(|| usage())),
+            "--author" => author = args.next(),
+            "--lang" => lang = args.next(),
+            _ if path.is_none() => path = Some(arg),
+            _ => usage(),
+        }
+    }
+
+    let path = path.unwrap_or_else(|| usage());
+    if categories.is_empty() {
+        categories = vec![Category::Anecdote, Category::Joke, Category::Punch];
+    }
+
+    let client = mongo::connect().unwrap_or_else(|| {
+        eprintln!("Faild to connect to MongoDB");
+        process::exit(1)
+    });
+
+    let tags: Option<Vec<&str>> = match tags.is_empty() {
+        true => None,
+        false => Some(tags.iter().map(String::as_str).collect()),
+    };
+
+    let fortune = Qilter::new(author.as_deref(), lang.as_deref(), None, tags)
+        .resolve(&client)
+        .and_then(|qilter| Fortune::select(&client, &categories, &qilter))
+        .unwrap_or_else(|err| {
+            eprintln!("{}", serde_json::to_string(&err).unwrap());
+            process::exit(1)
+        });
+
+    let written = fs::write(&path, &fortune.text)
+        .and_then(|_| fs::write(format!("{}.dat", path), &fortune.dat));
+
+    if let Err(err) = written {
+        eprintln!("Faild to write {}: {}", path, err);
+        process::exit(1);
+    }
+}
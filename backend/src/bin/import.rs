@@ -0,0 +1,108 @@
+//! Массовый импорт записей из файла в обход HTTP API.
+//!
+//! cargo run --bin import -- <file> --author <username> [--format jsonl|csv|fortune]
+//!     [--category joke|anecdote] [--tag <tag>]...
+//!
+//! Категория и теги задаются только для файлов fortune.
+//! Словари флагов читаются из каталога `LEXICON_DIR` (по умолчанию `lexicons`).
+
+use std::{env, fs, process};
+
+use jokehub::{
+    db::mongo,
+    model::{
+        account::User,
+        import::{self, ImportFormat, ImportReport},
+        lexicon::Lexicon,
+        shrimp::Category,
+    },
+    server::lingua,
+};
+
+fn usage() -> ! {
+    eprintln!(
+        "Usage: import <file> --author <username> [--format jsonl|csv|fortune] \
+         [--category joke|anecdote] [--tag <tag>]..."
+    );
+    process::exit(2)
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let (mut path, mut author, mut format) = (None, None, None);
+    let (mut category, mut tags) = (Category::Joke, Vec::new());
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--author" => author = args.next(),
+            "--format" => {
+                format = match args.next().as_deref() {
+                    Some("jsonl") => Some(ImportFormat::Jsonl),
+                    Some("csv") => Some(ImportFormat::Csv),
+                    Some("fortune") => Some(ImportFormat::Fortune),
+                    _ => usage(),
+                }
+            }
+            "--category" => {
+                category = match args.next().as_deref() {
+                    Some("joke") => Category::Joke,
+                    Some("anecdote") => Category::Anecdote,
+                    _ => usage(),
+                }
+            }
+            "--tag" => tags.push(args.next().unwrap_or_else(|| usage())),
+            _ if path.is_none() => path = Some(arg),
+            _ => usage(),
+        }
+    }
+
+    let (path, author) = match (path, author) {
+        (Some(path), Some(author)) => (path, author),
+        _ => usage(),
+    };
+
+    // Формат определяется по расширению файла, если не указан явно;
+    // по умолчанию, как и в HTTP API, файл читается как JSON Lines
+    let format = format.unwrap_or(match path.rsplit('.').next() {
+        Some("csv") => ImportFormat::Csv,
+        Some("fortune") => ImportFormat::Fortune,
+        _ => ImportFormat::default(),
+    });
+
+    let data = fs::read_to_string(&path).unwrap_or_else(|err| {
+        eprintln!("Faild to read {}: {}", path, err);
+        process::exit(1)
+    });
+
+    let rows = import::parse(&data, &format, &category, &tags).unwrap_or_else(|err| {
+        eprintln!("{}", serde_json::to_string(&err).unwrap());
+        process::exit(1)
+    });
+
+    let client = mongo::connect().unwrap_or_else(|| {
+        eprintln!("Faild to connect to MongoDB");
+        process::exit(1)
+    });
+
+    if let Err(err) = User::get_by_username(&client, author.clone()) {
+        eprintln!("{}", serde_json::to_string(&err).unwrap());
+        process::exit(1)
+    }
+
+    let lexicon = Lexicon::load(&Lexicon::dir()).unwrap_or_else(|err| {
+        eprintln!("{}", serde_json::to_string(&err).unwrap());
+        process::exit(1)
+    });
+
+    let report = ImportReport::run(&client, &lingua::detector(), &lexicon, &author, rows)
+        .unwrap_or_else(|err| {
+            eprintln!("{}", serde_json::to_string(&err).unwrap());
+            process::exit(1)
+        });
+
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+
+    if !report.failed.is_empty() {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,68 @@
+//! Разовая подготовка данных, созданных до появления новых возможностей.
+//!
+//! cargo run --bin migrate -- [--batch <size>]
+//!
+//! Запускается после обновления; повторный запуск ничего не меняет.
+
+use std::{env, process};
+
+use jokehub::{
+    db::mongo::{self, language, search, shrimp},
+    errors::HubError,
+    model::{account::favorites::Favorite, similarity::Fingerprint, trash::Trash},
+};
+
+/// Сколько записей без отпечатка обрабатывается за один запрос
+const DEFAULT_BATCH: i64 = 500;
+
+fn usage() -> ! {
+    eprintln!("Usage: migrate [--batch <size>]");
+    process::exit(2)
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let mut batch = DEFAULT_BATCH;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--batch" => {
+                batch = match args.next().and_then(|size| size.parse().ok()) {
+                    Some(size) if size > 0 => size,
+                    _ => usage(),
+                }
+            }
+            _ => usage(),
+        }
+    }
+
+    let client = mongo::connect().unwrap_or_else(|| {
+        eprintln!("Faild to connect to MongoDB");
+        process::exit(1)
+    });
+
+    // Языки записей хранятся кодами ISO 639-1
+    language::migrate(&client).unwrap_or_else(fail);
+
+    // Текстовые индексы для поиска и язык индекса у старых записей
+    search::ensure_indexes(&client).unwrap_or_else(fail);
+
+    // Лента подписок упорядочена по времени одобрения записей
+    let count = shrimp::backfill_published(&client).unwrap_or_else(fail);
+    println!("Publication time restored: {}", count);
+
+    // Запись в корзине не мешает добавить такую же заново
+    Trash::migrate_indexes(&client).unwrap_or_else(fail);
+
+    // Одну запись в избранное добавляют разные пользователи
+    Favorite::migrate_indexes(&client).unwrap_or_else(fail);
+
+    // Отпечатки для поиска почти-дубликатов среди уже существующих записей
+    let count = Fingerprint::backfill(&client, batch).unwrap_or_else(fail);
+    println!("Fingerprints created: {}", count);
+}
+
+fn fail<T>(err: HubError) -> T {
+    eprintln!("{}", serde_json::to_string(&err).unwrap());
+    process::exit(1)
+}
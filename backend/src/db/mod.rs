@@ -9,6 +9,15 @@ pub trait DbManage {
 impl DbManage for Rocket<Build> {
     fn manage_mongodb(self) -> Self {
         let client = mongo::connect().unwrap();
+
+        // Текстовые индексы для поиска создаются приложением по весам категорий
+        if let Err(err) = mongo::search::ensure_indexes(&client) {
+            error!("Faild to create text search indexes: {:?}", err);
+        }
+
+        // Записи с истекшим сроком хранения в корзине удаляются окончательно
+        crate::model::trash::Trash::spawn_purge(client.clone());
+
         let mbox = Box::new(client);
         self.manage(mbox)
     }
@@ -1,15 +1,16 @@
 use bson::Document;
 use mongodb::bson::DateTime as MongoDateTime;
+use mongodb::options::{FindOptions, IndexOptions};
 use mongodb::results::InsertOneResult;
 use mongodb::sync::Client;
-use mongodb::{bson::doc, sync::Collection};
+use mongodb::{bson::doc, sync::Collection, IndexModel};
 
 use crate::model::account::favorites::Favorite;
 use crate::model::account::notification::Notification;
 use crate::model::account::{security::api_key::ApiKey, security::Session, User};
-use crate::model::account::{Tariff, Theme};
+use crate::model::account::{EditProfile, Tariff, Theme};
 use crate::{
-    db::mongo::{varys::Varys, Crud},
+    db::mongo::{is_duplicate, varys::Varys, Crud},
     err_internal, err_not_found, err_unauthorized,
     errors::HubError,
     macro_crud,
@@ -82,6 +83,27 @@ impl<'a> User {
             Err(err) => Err(err_internal!("Faild to change theme", err.to_string())),
         }
     }
+
+    pub fn change_profile(
+        client: &Client,
+        profile: &EditProfile,
+        username: &str,
+    ) -> Result<(), HubError> {
+        let collection: Collection<User> = Varys::get(client, Varys::Users);
+
+        let filter = doc! {"username": username};
+        let update = doc! {"$set": {
+            "display_name": profile.display_name.as_deref(),
+            "bio": profile.bio.as_deref(),
+            "updated_at": MongoDateTime::now()
+        }};
+
+        match collection.update_one(filter, update, None) {
+            Ok(ur) if ur.matched_count > 0 => Ok(()),
+            Ok(_) => Err(err_unauthorized!("Faild to find such user")),
+            Err(err) => Err(err_internal!("Faild to change profile", err.to_string())),
+        }
+    }
 }
 
 macro_crud!(ApiKey);
@@ -201,13 +223,94 @@ impl Notification {}
 
 macro_crud!(Favorite);
 impl Favorite {
-    pub fn del_by_record_id(client: &Client, record_id: &str) -> Result<(), HubError> {
+    /// Добавление в избранное; каждую запись пользователь добавляет только один раз
+    pub fn add(&self, client: &Client) -> Result<(), HubError> {
+        let collection: Collection<Favorite> = Varys::get(client, Varys::Favorite);
+
+        match collection.insert_one(self, None) {
+            Ok(_) => Ok(()),
+            Err(err) if is_duplicate(&err) => Err(HubError::new_unprocessable(
+                "Record is already in favorite",
+                None,
+            )),
+            Err(err) => Err(err_internal!("Falid to add to favorite", err)),
+        }
+    }
+
+    /// Страница избранного пользователя, от новых к старым
+    pub fn roll(
+        client: &Client,
+        master: &str,
+        page: u64,
+        limit: i64,
+    ) -> Result<Vec<Favorite>, HubError> {
+        let collection: Collection<Favorite> = Varys::get(client, Varys::Favorite);
+        let options = FindOptions::builder()
+            .sort(doc! {"added_at": -1, "_id": -1})
+            .skip((page.max(1) - 1) * limit as u64)
+            .limit(limit)
+            .build();
+
+        let mut result: Vec<Favorite> = Vec::new();
+        for favorite in collection.find(doc! {"master": master}, options)? {
+            result.push(favorite?);
+        }
+
+        Ok(result)
+    }
+
+    pub fn del_by_record_id(
+        client: &Client,
+        master: &str,
+        record_id: &str,
+    ) -> Result<(), HubError> {
         let collection: Collection<Favorite> = Varys::get(client, Varys::Favorite);
 
-        match collection.delete_one(doc! {"content_id" : record_id}, None) {
+        match collection.delete_one(doc! {"master": master, "content_id": record_id}, None) {
             Ok(dr) if dr.deleted_count > 0 => Ok(()),
             Ok(_) => Err(err_not_found!("favorite")),
             Err(err) => Err(err_internal!("Falid to remove from favorite", err)),
         }
     }
+
+    /// Индексы избранного для баз, созданных до того, как одну запись смогли добавлять
+    /// разные пользователи: старый уникальный индекс по `content_id` удаляется,
+    /// уникальность переходит на пару пользователь-запись.
+    pub fn migrate_indexes(client: &Client) -> Result<(), HubError> {
+        let collection: Collection<Favorite> = Varys::get(client, Varys::Favorite);
+
+        for index in collection.list_indexes(None)? {
+            let index = index?;
+            let unique = index
+                .options
+                .as_ref()
+                .and_then(|options| options.unique)
+                .unwrap_or(false);
+
+            if unique && index.keys == doc! {"content_id": 1} {
+                let name = index
+                    .options
+                    .and_then(|options| options.name)
+                    .unwrap_or_else(|| "content_id_1".to_string());
+
+                collection
+                    .drop_index(name, None)
+                    .map_err(|err| err_internal!("Faild to drop favorite index", err))?;
+            }
+        }
+
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! {"master": 1, "content_id": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder().keys(doc! {"content_id": 1}).build(),
+        ];
+
+        collection
+            .create_indexes(indexes, None)
+            .map_err(|err| err_internal!("Faild to create favorite indexes", err))?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,75 @@
+use bson::{doc, Bson, Document};
+use mongodb::sync::{Client, Collection};
+use std::collections::HashMap;
+
+use crate::{
+    db::mongo::{shrimp::aggregation::Qilter, varys::Varys},
+    err_internal,
+    errors::HubError,
+    model::{
+        author::AuthorStats,
+        shrimp::{Category, ReactionKind, Status},
+    },
+};
+
+impl AuthorStats {
+    /// Статистика по одобренным записям автора во всех категориях
+    pub fn collect(client: &Client, username: &str) -> Result<AuthorStats, HubError> {
+        let mut group = doc! {
+            "_id": Bson::Null,
+            "records": {"$sum": 1},
+            "views": {"$sum": "$_header.counter"}
+        };
+
+        for kind in ReactionKind::all() {
+            let name = kind.to_string().to_lowercase();
+            group.insert(
+                name.clone(),
+                doc! {"$sum": {"$ifNull": [format!("$_meta-data.reactions.{}", name), 0]}},
+            );
+        }
+
+        let mut pipeline = Qilter::new(Some(username), None, None, None).stages();
+        pipeline.push(Qilter::status(&Status::Approved));
+        pipeline.push(doc! {"$group": group});
+
+        let mut stats = AuthorStats::default();
+        for category in Category::all() {
+            let collection: Collection<Document> = Varys::get(client, category.clone().into());
+
+            let mut cursor = collection
+                .aggregate(pipeline.clone(), None)
+                .map_err(|err| err_internal!("Faild to collect author stats", err))?;
+
+            if let Some(total) = cursor.next() {
+                let total = total?;
+
+                let reactions: HashMap<ReactionKind, usize> = ReactionKind::all()
+                    .into_iter()
+                    .map(|kind| {
+                        let count = Self::number(&total, &kind.to_string().to_lowercase());
+                        (kind, count)
+                    })
+                    .collect();
+
+                stats.add(
+                    category,
+                    Self::number(&total, "records"),
+                    Self::number(&total, "views"),
+                    reactions,
+                );
+            }
+        }
+
+        Ok(stats)
+    }
+
+    fn number(doc: &Document, key: &str) -> usize {
+        match doc.get(key) {
+            Some(Bson::Int32(v)) => *v as usize,
+            Some(Bson::Int64(v)) => *v as usize,
+            Some(Bson::Double(v)) => *v as usize,
+            _ => 0,
+        }
+    }
+}
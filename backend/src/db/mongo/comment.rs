@@ -0,0 +1,265 @@
+use bson::{doc, Bson, Document};
+use mongodb::{
+    bson::DateTime as MongoDateTime,
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    sync::{Client, Collection},
+};
+
+use crate::{
+    db::mongo::{is_duplicate, shrimp::MODIFIED_FIELD, varys::Varys, Crud},
+    err_internal, err_not_found,
+    errors::HubError,
+    macro_crud,
+    model::{
+        comment::{Comment, CommentReaction, NewComment},
+        shrimp::{Category, ReactionKind},
+        validation::uuid_validation,
+    },
+};
+
+/// Счетчик комментариев в документе записи
+const COUNTER_FIELD: &str = "_meta-data.comments_count";
+
+macro_crud!(Comment);
+impl Comment {
+    /// Публикация комментария к записи или ответа на другой комментарий.
+    /// Вместе с комментарием растут счетчик записи и счетчик ответов родителя.
+    pub fn publish(
+        client: &Client,
+        category: Category,
+        record_id: &str,
+        author: &str,
+        new: &NewComment,
+    ) -> Result<Comment, HubError> {
+        let collection: Collection<Comment> = Varys::get(client, Varys::Comments);
+        let mut comment = Comment::new(record_id, category.clone(), author, &new.text);
+
+        if let Some(parent_id) = new.parent_id.as_deref() {
+            let parent = Comment::find(client, uuid_validation(parent_id)?)?;
+
+            if parent.record_id != record_id || parent.deleted {
+                return Err(err_not_found!("comment"));
+            }
+
+            comment = comment.reply_to(&parent);
+        }
+
+        // Счетчики растут только после сохранения, чтобы неудачная вставка их не сбивала
+        Comment::create(Varys::get(client, Varys::Comments), &comment)?;
+
+        if let Some(parent_id) = comment.parent_id.as_ref() {
+            collection.update_one(doc! {"_id": parent_id}, doc! {"$inc": {"replies": 1}}, None)?;
+        }
+        Comment::count(client, &category, record_id, 1)?;
+
+        Ok(comment)
+    }
+
+    pub fn find(client: &Client, id: &str) -> Result<Comment, HubError> {
+        let collection: Collection<Comment> = Varys::get(client, Varys::Comments);
+
+        match collection.find_one(doc! {"_id": id}, None)? {
+            Some(comment) => Ok(comment),
+            None => Err(err_not_found!("comment")),
+        }
+    }
+
+    /// Страница комментариев записи от старых к новым: верхнего уровня или ответов на `parent_id`.
+    /// Возвращает курсор следующей страницы, если она есть.
+    pub fn page(
+        client: &Client,
+        record_id: &str,
+        parent_id: Option<&str>,
+        cursor: Option<&str>,
+        limit: i64,
+    ) -> Result<(Vec<Comment>, Option<String>), HubError> {
+        let collection: Collection<Comment> = Varys::get(client, Varys::Comments);
+
+        let mut filter = doc! {
+            "record_id": record_id,
+            "parent_id": parent_id.map_or(Bson::Null, |id| Bson::String(id.to_string()))
+        };
+
+        if let Some(cursor) = cursor {
+            let (at, id) = Comment::parse_cursor(cursor)?;
+            let at = MongoDateTime::from_millis(at);
+
+            filter.insert(
+                "$or",
+                vec![
+                    doc! {"created_at": {"$gt": at}},
+                    doc! {"created_at": at, "_id": {"$gt": id}},
+                ],
+            );
+        }
+
+        // Запрашивается на один комментарий больше, чтобы понять, есть ли следующая страница
+        let options = FindOptions::builder()
+            .sort(doc! {"created_at": 1, "_id": 1})
+            .limit(limit + 1)
+            .build();
+
+        let mut result: Vec<Comment> = Vec::new();
+        for comment in collection.find(filter, options)? {
+            result.push(comment?);
+        }
+
+        let has_next = result.len() as i64 > limit;
+        result.truncate(limit as usize);
+
+        let next = match result.last() {
+            Some(last) if has_next => Some(last.cursor()),
+            _ => None,
+        };
+
+        Ok((result, next))
+    }
+
+    pub fn edit(&self, client: &Client, text: &str) -> Result<(), HubError> {
+        let collection: Collection<Comment> = Varys::get(client, Varys::Comments);
+        let update = doc! {"$set": {"text": text, "edited_at": MongoDateTime::now()}};
+
+        match collection.update_one(doc! {"_id": &self.id, "deleted": false}, update, None) {
+            Ok(ur) if ur.matched_count > 0 => Ok(()),
+            Ok(_) => Err(err_not_found!("comment")),
+            Err(err) => Err(err_internal!("Faild to update comment", err)),
+        }
+    }
+
+    /// Удаление комментария.
+    /// Комментарий с ответами остается в ветке без текста, чтобы ответы не потеряли контекст.
+    pub fn remove(&self, client: &Client) -> Result<(), HubError> {
+        if self.deleted {
+            return Err(err_not_found!("comment"));
+        }
+
+        let collection: Collection<Comment> = Varys::get(client, Varys::Comments);
+
+        if self.replies > 0 {
+            collection.update_one(
+                doc! {"_id": &self.id},
+                doc! {"$set": {"deleted": true, "text": "", "reactions": {}}},
+                None,
+            )?;
+        } else {
+            collection.delete_one(doc! {"_id": &self.id}, None)?;
+
+            if let Some(parent_id) = self.parent_id.as_ref() {
+                collection.update_one(
+                    doc! {"_id": parent_id},
+                    doc! {"$inc": {"replies": -1}},
+                    None,
+                )?;
+            }
+        }
+
+        let reactions: Collection<CommentReaction> = Varys::get(client, Varys::CommentReactions);
+        reactions.delete_many(doc! {"comment_id": &self.id}, None)?;
+
+        Comment::count(client, &self.category, &self.record_id, -1)
+    }
+
+    /// Установка или смена реакции потребителя на комментарий
+    pub fn react(
+        &self,
+        client: &Client,
+        identity: &str,
+        kind: ReactionKind,
+    ) -> Result<(), HubError> {
+        if self.deleted {
+            return Err(err_not_found!("comment"));
+        }
+
+        let collection: Collection<CommentReaction> = Varys::get(client, Varys::CommentReactions);
+        let options = FindOneAndUpdateOptions::builder()
+            .upsert(true)
+            .return_document(ReturnDocument::Before)
+            .build();
+
+        let filter = doc! {"comment_id": &self.id, "identity": identity};
+        let update = doc! {"$set": {
+            "kind": bson::to_bson(&kind)?,
+            "record_id": &self.record_id,
+            "created_at": MongoDateTime::now()
+        }};
+        let upsert =
+            || collection.find_one_and_update(filter.clone(), update.clone(), options.clone());
+
+        // Одновременные первые реакции одного потребителя сталкиваются на уникальном индексе:
+        // проигравший запрос повторяет обновление уже вставленной реакции
+        let previous = match upsert() {
+            Err(err) if is_duplicate(&err) => upsert()?,
+            result => result?,
+        };
+
+        let mut inc = Document::new();
+        match previous {
+            Some(prev) if prev.kind == kind => return Ok(()),
+            Some(prev) => {
+                inc.insert(Self::reaction_counter(&prev.kind), -1);
+            }
+            None => {}
+        }
+        inc.insert(Self::reaction_counter(&kind), 1);
+
+        let comments: Collection<Comment> = Varys::get(client, Varys::Comments);
+        match comments.update_one(doc! {"_id": &self.id}, doc! {"$inc": inc}, None) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err_internal!("Faild to add reaction", err)),
+        }
+    }
+
+    /// Отмена реакции потребителя на комментарий
+    pub fn unreact(client: &Client, comment_id: &str, identity: &str) -> Result<(), HubError> {
+        let collection: Collection<CommentReaction> = Varys::get(client, Varys::CommentReactions);
+        let filter = doc! {"comment_id": comment_id, "identity": identity};
+
+        let previous = match collection.find_one_and_delete(filter, None)? {
+            Some(reaction) => reaction,
+            None => return Err(err_not_found!("reaction")),
+        };
+
+        let comments: Collection<Comment> = Varys::get(client, Varys::Comments);
+        let update = doc! {"$inc": {Self::reaction_counter(&previous.kind): -1}};
+
+        match comments.update_one(doc! {"_id": comment_id}, update, None) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err_internal!("Faild to remove reaction", err)),
+        }
+    }
+
+    /// Все комментарии записи и реакции на них
+    pub fn forget(client: &Client, record_id: &str) -> Result<(), HubError> {
+        let comments: Collection<Comment> = Varys::get(client, Varys::Comments);
+        comments.delete_many(doc! {"record_id": record_id}, None)?;
+
+        let reactions: Collection<CommentReaction> = Varys::get(client, Varys::CommentReactions);
+        reactions.delete_many(doc! {"record_id": record_id}, None)?;
+
+        Ok(())
+    }
+
+    fn count(
+        client: &Client,
+        category: &Category,
+        record_id: &str,
+        inc: i32,
+    ) -> Result<(), HubError> {
+        let records: Collection<Document> = Varys::get(client, category.clone().into());
+
+        // Изменение счетчика попадает в инкрементальный снимок
+        let update = doc! {
+            "$inc": {COUNTER_FIELD: inc},
+            "$set": {MODIFIED_FIELD: MongoDateTime::now().timestamp_millis()}
+        };
+
+        match records.update_one(doc! {"_id": record_id}, update, None) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err_internal!("Faild to count comments", err)),
+        }
+    }
+
+    fn reaction_counter(kind: &ReactionKind) -> String {
+        format!("reactions.{}", kind.to_string().to_lowercase())
+    }
+}
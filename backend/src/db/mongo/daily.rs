@@ -0,0 +1,142 @@
+use bson::{doc, Document};
+use chrono::{Duration, NaiveDate};
+use mongodb::sync::{Client, Collection};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    db::mongo::{
+        is_duplicate,
+        shrimp::{
+            aggregation::{self, Qilter},
+            published,
+        },
+        varys::Varys,
+    },
+    err_internal, err_not_found,
+    errors::HubError,
+    model::{
+        daily::Daily,
+        shrimp::{Category, Status},
+    },
+};
+
+impl Daily {
+    /// Запись дня для даты `date` и набора фильтров.
+    /// Если запись на эту дату уже выбрана, возвращается она же.
+    /// Иначе запись выбирается детерминированно из одобренных записей,
+    /// не попадавших в записи дня с тем же фильтром за последние `period` дней.
+    pub fn pick(
+        client: &Client,
+        categories: &[Category],
+        qilter: &Qilter,
+        date: &NaiveDate,
+        period: u32,
+    ) -> Result<Document, HubError> {
+        let collection: Collection<Daily> = Varys::get(client, Varys::Daily);
+
+        let mut names: Vec<String> = categories.iter().map(|c| c.to_string()).collect();
+        names.sort();
+        names.dedup();
+
+        let key = format!(
+            "category={};{};period={}",
+            names.join(","),
+            qilter.key(),
+            period
+        );
+
+        let id = Daily::id(date, &key);
+
+        if let Some(daily) = collection.find_one(doc! {"_id": &id}, None)? {
+            match daily.record(client)? {
+                Some(record) => return Ok(record),
+                // Запись успели удалить или снять с публикации — выбираем новую
+                None => {
+                    collection.delete_one(doc! {"_id": &id}, None)?;
+                }
+            }
+        }
+
+        let since = (*date - Duration::days(i64::from(period))).to_string();
+        let filter = doc! {"key": &key, "date": {"$gte": since, "$lt": date.to_string()}};
+
+        let mut recent: Vec<String> = Vec::new();
+        for daily in collection.find(filter, None)? {
+            recent.push(daily?.record_id);
+        }
+
+        // Если все записи уже были записями дня, повторы неизбежны
+        let mut candidates = Daily::candidates(
+            client,
+            categories,
+            &qilter.without_exclude().exclude(recent),
+        )?;
+        if candidates.is_empty() {
+            candidates = Daily::candidates(client, categories, &qilter.without_exclude())?;
+        }
+
+        if candidates.is_empty() {
+            return Err(err_not_found!("record"));
+        }
+
+        let (record_id, category) = candidates.swap_remove(Daily::index(&id, candidates.len()));
+        let daily = Daily::new(date, &key, &record_id, category);
+
+        let daily = match collection.insert_one(&daily, None) {
+            Ok(_) => daily,
+
+            // Параллельный запрос успел выбрать запись дня раньше
+            Err(err) if is_duplicate(&err) => match collection.find_one(doc! {"_id": &id}, None)? {
+                Some(daily) => daily,
+                None => return Err(err_internal!("Faild to pick daily record", err)),
+            },
+
+            Err(err) => return Err(err_internal!("Faild to pick daily record", err)),
+        };
+
+        daily
+            .record(client)?
+            .ok_or_else(|| err_not_found!("record"))
+    }
+
+    /// Запись, выбранная записью дня, пока она одобрена и не лежит в корзине
+    fn record(&self, client: &Client) -> Result<Option<Document>, HubError> {
+        let collection: Collection<Document> = Varys::get(client, self.category.clone().into());
+        Ok(collection.find_one(published(&self.record_id), None)?)
+    }
+
+    /// Идентификаторы и категории всех подходящих записей в неизменном порядке
+    fn candidates(
+        client: &Client,
+        categories: &[Category],
+        qilter: &Qilter,
+    ) -> Result<Vec<(String, Category)>, HubError> {
+        let mut stages = qilter.stages();
+        stages.push(Qilter::status(&Status::Approved));
+        stages.push(doc! {"$project": {"_id": 1, "category": 1}});
+
+        let tail = vec![doc! {"$sort": {"_id": 1}}];
+
+        let mut result: Vec<(String, Category)> = Vec::new();
+        for record in aggregation::union(client, categories, stages, tail)? {
+            let id = record.get_str("_id").unwrap_or_default().to_string();
+            let category = bson::from_bson(record.get("category").cloned().unwrap_or_default());
+
+            if let Ok(category) = category {
+                result.push((id, category));
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Номер записи дня среди `len` кандидатов.
+    /// Хеш идентификатора дня не зависит от версии компилятора и процесса.
+    fn index(id: &str, len: usize) -> usize {
+        let hash = Sha256::digest(id.as_bytes());
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&hash[..8]);
+
+        (u64::from_be_bytes(bytes) % len as u64) as usize
+    }
+}
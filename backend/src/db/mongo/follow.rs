@@ -0,0 +1,126 @@
+use bson::doc;
+use mongodb::{
+    options::FindOptions,
+    sync::{Client, Collection},
+};
+
+use crate::{
+    db::mongo::{is_duplicate, varys::Varys, Crud},
+    err_internal, err_not_found,
+    errors::HubError,
+    macro_crud,
+    model::{
+        account::{
+            notification::{self, Notification, NotifyKind},
+            User,
+        },
+        follow::Follow,
+        shrimp::Category,
+    },
+};
+
+macro_crud!(Follow);
+impl Follow {
+    /// Подписка на существующего автора. Повторная подписка ничего не меняет.
+    pub fn subscribe(&self, client: &Client) -> Result<(), HubError> {
+        User::get_by_username(client, self.author.clone())?;
+
+        let collection: Collection<Follow> = Varys::get(client, Varys::Follows);
+
+        match collection.insert_one(self, None) {
+            Ok(_) => Ok(()),
+            Err(err) if is_duplicate(&err) => Ok(()),
+            Err(err) => Err(err_internal!("Faild to follow author", err)),
+        }
+    }
+
+    pub fn unsubscribe(client: &Client, follower: &str, author: &str) -> Result<(), HubError> {
+        let collection: Collection<Follow> = Varys::get(client, Varys::Follows);
+
+        match collection.delete_one(doc! {"follower": follower, "author": author}, None) {
+            Ok(dr) if dr.deleted_count > 0 => Ok(()),
+            Ok(_) => Err(err_not_found!("follow")),
+            Err(err) => Err(err_internal!("Faild to unfollow author", err)),
+        }
+    }
+
+    /// Имена всех авторов, на которых подписан пользователь
+    pub fn following(client: &Client, follower: &str) -> Result<Vec<String>, HubError> {
+        let result = Self::roll(client, "follower", follower, None)?;
+
+        Ok(result.into_iter().map(|f| f.author).collect())
+    }
+
+    /// Страница подписок (`field` = `follower`) или подписчиков (`field` = `author`), от новых к старым
+    pub fn page(
+        client: &Client,
+        field: &str,
+        username: &str,
+        page: u64,
+        limit: i64,
+    ) -> Result<Vec<Follow>, HubError> {
+        let options = FindOptions::builder()
+            .sort(doc! {"created_at": -1, "_id": -1})
+            .skip((page.max(1) - 1) * limit as u64)
+            .limit(limit)
+            .build();
+
+        Self::roll(client, field, username, Some(options))
+    }
+
+    /// Уведомление подписчиков автора об одобренной записи
+    pub fn notify(
+        client: &Client,
+        author: &str,
+        category: &Category,
+        record_id: &str,
+    ) -> Result<(), HubError> {
+        let name = category.to_string().to_lowercase();
+
+        let notifications: Vec<Notification> = Self::roll(client, "author", author, None)?
+            .into_iter()
+            .map(|f| {
+                Notification::new(
+                    author,
+                    &f.follower,
+                    NotifyKind::General,
+                    notification::Body::new(
+                        format!("{} published a new {}", author, name).as_str(),
+                        None,
+                        Some(vec![notification::Action {
+                            kind: notification::ActionKind::Major,
+                            text: String::from("Open"),
+                            href: format!("/v1/{}/{}", name, record_id),
+                        }]),
+                    ),
+                )
+            })
+            .collect();
+
+        if notifications.is_empty() {
+            return Ok(());
+        }
+
+        let collection: Collection<Notification> = Varys::get(client, Varys::Notification);
+        match collection.insert_many(notifications, None) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err_internal!("Faild to notify followers", err)),
+        }
+    }
+
+    fn roll(
+        client: &Client,
+        field: &str,
+        username: &str,
+        options: Option<FindOptions>,
+    ) -> Result<Vec<Follow>, HubError> {
+        let collection: Collection<Follow> = Varys::get(client, Varys::Follows);
+
+        let mut result: Vec<Follow> = Vec::new();
+        for follow in collection.find(doc! {field: username}, options)? {
+            result.push(follow?);
+        }
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,48 @@
+use bson::doc;
+use mongodb::sync::Client;
+
+use crate::{
+    db::mongo::{
+        search,
+        shrimp::aggregation::{self, Qilter},
+    },
+    errors::HubError,
+    model::{
+        fortune::Fortune,
+        shrimp::{Category, Status},
+    },
+};
+
+impl Fortune {
+    /// Файл fortune из одобренных записей, подходящих под фильтр.
+    /// Записи идут в неизменном порядке, поэтому текст и индекс совпадают между запросами.
+    pub fn select(
+        client: &Client,
+        categories: &[Category],
+        qilter: &Qilter,
+    ) -> Result<Fortune, HubError> {
+        let mut stages = qilter.stages();
+        stages.push(Qilter::status(&Status::Approved));
+
+        let tail = vec![doc! {"$sort": {"_id": 1}}];
+
+        let mut cookies: Vec<String> = Vec::new();
+        for record in aggregation::union(client, categories, stages, tail)? {
+            let category: Category = match record.get("category").cloned() {
+                Some(category) => bson::from_bson(category)?,
+                None => continue,
+            };
+
+            // Все текстовые поля записи по порядку: заголовок, текст, части
+            let texts: Vec<String> = category
+                .text_fields()
+                .iter()
+                .flat_map(|(field, _)| search::texts(&record, field))
+                .collect();
+
+            cookies.push(texts.join("\n"));
+        }
+
+        Ok(Fortune::new(&cookies))
+    }
+}
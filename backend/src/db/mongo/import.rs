@@ -0,0 +1,126 @@
+use ::lingua::LanguageDetector;
+use mongodb::sync::{Client, Collection};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{
+    db::mongo::{is_duplicate, varys::Varys},
+    errors::HubError,
+    model::{
+        anecdote::Anecdote,
+        import::{ImportReport, ImportRow, NewShrimp},
+        joke::Joke,
+        lexicon::Lexicon,
+        punch::Punch,
+        shrimp::{Flags, Paws, Shrimp, Status, Tail},
+        similarity::{Fingerprint, REJECT_THRESHOLD},
+        tag::{canonize, normalize, Tag},
+    },
+    server::lingua,
+};
+
+impl ImportReport {
+    /// Импорт разобранных строк от имени `author`.
+    /// Импортированные записи сразу считаются одобренными,
+    /// флаги по словарям `lexicon` выставляются так же, как при создании записи.
+    /// Теги всех строк разрешаются одним запросом и регистрируются после вставки.
+    pub fn run(
+        client: &Client,
+        detector: &LanguageDetector,
+        lexicon: &Lexicon,
+        author: &str,
+        rows: Vec<ImportRow>,
+    ) -> Result<ImportReport, HubError> {
+        let mut report = ImportReport {
+            total: rows.len(),
+            ..Default::default()
+        };
+
+        let names: BTreeSet<String> = rows
+            .iter()
+            .filter_map(|(_, shrimp)| shrimp.as_ref().ok())
+            .flat_map(|shrimp| shrimp.tags().iter().map(|tag| normalize(tag)))
+            .collect();
+        let known = Tag::known(client, &names.into_iter().collect::<Vec<String>>())?;
+
+        let mut inserted: BTreeSet<String> = BTreeSet::new();
+        for (row, shrimp) in rows {
+            match shrimp
+                .and_then(|shrimp| insert(client, detector, lexicon, &known, author, shrimp))
+            {
+                Ok(Some(tags)) => {
+                    inserted.extend(tags);
+                    report.inserted += 1
+                }
+                Ok(None) => report.duplicates += 1,
+                Err(err) => report.fail(row, err),
+            }
+        }
+
+        Tag::register(client, &inserted.into_iter().collect::<Vec<String>>())?;
+
+        Ok(report)
+    }
+}
+
+/// Вставка одной записи с тегами, приведенными по `known`.
+/// Возвращает теги вставленной записи или `None`, если такая запись уже есть.
+fn insert(
+    client: &Client,
+    detector: &LanguageDetector,
+    lexicon: &Lexicon,
+    known: &HashMap<String, String>,
+    author: &str,
+    shrimp: NewShrimp,
+) -> Result<Option<Vec<String>>, HubError> {
+    shrimp.validate()?;
+
+    let (lang, detection) = lingua::detect(detector, shrimp.text(), shrimp.lang())?;
+    let triggers = lexicon.suggest(&shrimp.text());
+    let collection: Collection<bson::Document> = Varys::get(client, shrimp.category().into());
+
+    let tags = canonize(shrimp.tags(), known)?;
+    let document = match shrimp {
+        NewShrimp::Anecdote(na) => {
+            let tail = Tail::new(Flags::from(&na.flags), lang, author.to_string(), &tags)
+                .detection(detection)
+                .suggest(triggers);
+            approved(Anecdote::from(na), tail)?
+        }
+
+        NewShrimp::Joke(nj) => {
+            let tail = Tail::new(Flags::from(&nj.flags), lang, author.to_string(), &tags)
+                .detection(detection)
+                .suggest(triggers);
+            approved(Joke::from(nj), tail)?
+        }
+
+        NewShrimp::Punch(np) => {
+            let tail = Tail::new(Flags::from(&np.flags), lang, author.to_string(), &tags)
+                .detection(detection)
+                .suggest(triggers);
+            approved(Punch::from(np), tail)?
+        }
+    };
+
+    // Почти-дубликаты существующих записей тоже пропускаются
+    let fingerprint = Fingerprint::of(&document)?;
+    if fingerprint.nearest(client, REJECT_THRESHOLD)?.is_some() {
+        return Ok(None);
+    }
+
+    // Повторная загрузка упирается в уникальные индексы по тексту записи
+    match collection.insert_one(document, None) {
+        Ok(_) => fingerprint.save(client).map(|_| Some(tags)),
+        Err(err) if is_duplicate(&err) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn approved<T>(body: T, mut tail: Tail) -> Result<bson::Document, HubError>
+where
+    T: Serialize + serde::de::DeserializeOwned + Unpin + Send + Sync + Paws,
+{
+    tail.status = Status::Approved;
+    Shrimp::new(body, tail).document()
+}
@@ -0,0 +1,36 @@
+use bson::{doc, Document};
+use lingua::Language;
+use mongodb::sync::{Client, Collection};
+
+use crate::{
+    db::mongo::varys::Varys,
+    errors::HubError,
+    model::{language, shrimp::Category},
+};
+
+/// Замена названий языков (`russian`, `english`) кодами ISO 639-1
+/// в записях и в сохраненных версиях записей
+pub fn migrate(client: &Client) -> Result<(), HubError> {
+    let revisions: Collection<Document> = Varys::get(client, Varys::Revisions);
+
+    for lang in Language::all() {
+        let (name, code) = (lang.to_string(), language::code(&lang));
+
+        for category in Category::all() {
+            let collection: Collection<Document> = Varys::get(client, category.into());
+            collection.update_many(
+                doc! {"_meta-data.language": &name},
+                doc! {"$set": {"_meta-data.language": &code}},
+                None,
+            )?;
+        }
+
+        revisions.update_many(
+            doc! {"snapshot._meta-data.language": &name},
+            doc! {"$set": {"snapshot._meta-data.language": &code}},
+            None,
+        )?;
+    }
+
+    Ok(())
+}
@@ -1,9 +1,23 @@
 pub mod account;
+pub mod author;
+pub mod comment;
+pub mod daily;
+pub mod follow;
+pub mod fortune;
+pub mod import;
+pub mod language;
+pub mod search;
 pub mod shrimp;
+pub mod similarity;
+pub mod snapshot;
+pub mod tag;
+pub mod translation;
+pub mod trash;
 pub mod varys;
 
 use mongodb::{
     bson::{doc, Document},
+    error::{ErrorKind, WriteFailure},
     options::ClientOptions,
     results::{DeleteResult, InsertOneResult},
     sync::Client,
@@ -64,6 +78,17 @@ impl<'r> FromRequest<'r> for MongoConn<'r> {
     }
 }
 
+/// Код ошибки MongoDB при нарушении уникальности
+const DUPLICATE_KEY: i32 = 11000;
+
+/// Ошибка вставки документа, который уже есть в коллекции
+pub(crate) fn is_duplicate(err: &mongodb::error::Error) -> bool {
+    matches!(
+        err.kind.as_ref(),
+        ErrorKind::Write(WriteFailure::WriteError(e)) if e.code == DUPLICATE_KEY
+    )
+}
+
 #[macro_export]
 macro_rules! macro_crud {
     ($t:ident) => {
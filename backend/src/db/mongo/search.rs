@@ -0,0 +1,181 @@
+use bson::{doc, Bson, Document};
+use mongodb::{
+    options::IndexOptions,
+    sync::{Client, Collection},
+    IndexModel,
+};
+use std::collections::HashMap;
+
+use crate::{
+    db::mongo::varys::Varys,
+    err_internal,
+    errors::HubError,
+    model::{language, shrimp::Category},
+};
+
+/// Поле верхнего уровня с языком записи для текстового индекса.
+/// MongoDB не умеет брать язык из вложенного `_meta-data.language`, поэтому он дублируется.
+pub const LANGUAGE_FIELD: &str = "_language";
+
+/// Имя текстового индекса в коллекциях категорий
+const INDEX_NAME: &str = "text_search";
+
+/// Поле с релевантностью записи в результатах поиска
+pub const SCORE_FIELD: &str = "_score";
+
+/// Язык текстового индекса для языка записи.
+/// Для языков, которые MongoDB не умеет стеммировать (например украинского), стемминг отключается.
+pub fn text_language(lang: &str) -> &'static str {
+    match lang.to_lowercase().as_str() {
+        "russian" | "ru" => "russian",
+        "english" | "en" => "english",
+        "german" | "de" => "german",
+        "spanish" | "es" => "spanish",
+        "french" | "fr" => "french",
+        _ => "none",
+    }
+}
+
+/// Языки текстового индекса для всех поддерживаемых языков записей
+fn text_languages() -> Vec<&'static str> {
+    let mut result: Vec<&'static str> = Vec::new();
+    for code in language::codes() {
+        let lang = text_language(&code);
+        if !result.contains(&lang) {
+            result.push(lang);
+        }
+    }
+
+    result
+}
+
+/// Создание текстовых индексов во всех коллекциях категорий, вызывается при запуске сервера.
+/// Записи, созданные до появления поиска, получают поле языка индекса.
+pub fn ensure_indexes(client: &Client) -> Result<(), HubError> {
+    for category in Category::all() {
+        let collection: Collection<Document> = Varys::get(client, category.clone().into());
+
+        let mut keys = Document::new();
+        let mut weights = Document::new();
+        for (field, weight) in category.text_fields() {
+            keys.insert(field, "text");
+            weights.insert(field, weight);
+        }
+
+        let options = IndexOptions::builder()
+            .name(INDEX_NAME.to_string())
+            .default_language("none".to_string())
+            .language_override(LANGUAGE_FIELD.to_string())
+            .weights(weights)
+            .build();
+
+        collection
+            .create_index(
+                IndexModel::builder().keys(keys).options(options).build(),
+                None,
+            )
+            .map_err(|err| err_internal!("Faild to create text index", err))?;
+
+        for lang in language::codes() {
+            collection.update_many(
+                doc! {LANGUAGE_FIELD: {"$exists": false}, "_meta-data.language": &lang},
+                doc! {"$set": {LANGUAGE_FIELD: text_language(&lang)}},
+                None,
+            )?;
+        }
+
+        collection.update_many(
+            doc! {LANGUAGE_FIELD: {"$exists": false}},
+            doc! {"$set": {LANGUAGE_FIELD: "none"}},
+            None,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Полнотекстовый поиск по коллекциям нескольких категорий.
+/// Если язык не указан, запрос выполняется для каждого поддерживаемого языка,
+/// чтобы слова запроса прошли стемминг для записей на любом из них.
+/// Записи возвращаются по убыванию релевантности, она хранится в поле `_score`.
+pub fn find(
+    client: &Client,
+    categories: &[Category],
+    query: &str,
+    language: Option<&str>,
+    stages: Vec<Document>,
+    limit: i64,
+) -> Result<Vec<Document>, HubError> {
+    let languages: Vec<&str> = match language {
+        Some(lang) => vec![text_language(lang)],
+        None => text_languages(),
+    };
+
+    let mut found: HashMap<String, Document> = HashMap::new();
+
+    for category in categories {
+        let collection: Collection<Document> = Varys::get(client, category.clone().into());
+
+        for lang in languages.iter() {
+            let mut pipeline = vec![doc! {
+                "$match": {"$text": {"$search": query, "$language": lang}}
+            }];
+            pipeline.extend(stages.clone());
+            pipeline.push(doc! {"$addFields": {SCORE_FIELD: {"$meta": "textScore"}}});
+            pipeline.push(doc! {"$sort": {SCORE_FIELD: -1}});
+            pipeline.push(doc! {"$limit": limit});
+
+            let cursor = collection
+                .aggregate(pipeline, None)
+                .map_err(|err| err_internal!("Faild to search records", err))?;
+
+            for record in cursor {
+                let record = record?;
+                let id = record.get_str("_id").unwrap_or_default().to_string();
+
+                match found.get(&id) {
+                    Some(prev) if score(prev) >= score(&record) => {}
+                    _ => {
+                        found.insert(id, record);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut result: Vec<Document> = found.into_values().collect();
+    result.sort_by(|a, b| score(b).total_cmp(&score(a)));
+    result.truncate(limit as usize);
+
+    Ok(result)
+}
+
+/// Релевантность найденной записи
+pub fn score(record: &Document) -> f64 {
+    record.get_f64(SCORE_FIELD).unwrap_or_default()
+}
+
+/// Все строки записи, лежащие по пути `path` (с учетом вложенных массивов)
+pub fn texts(record: &Document, path: &str) -> Vec<String> {
+    fn collect(value: &Bson, path: &[&str], out: &mut Vec<String>) {
+        match value {
+            Bson::String(s) if path.is_empty() => out.push(s.clone()),
+            Bson::Array(items) => items.iter().for_each(|item| collect(item, path, out)),
+            Bson::Document(d) if !path.is_empty() => {
+                if let Some(next) = d.get(path[0]) {
+                    collect(next, &path[1..], out)
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let path: Vec<&str> = path.split('.').collect();
+    let mut out: Vec<String> = Vec::new();
+
+    if let Some(value) = record.get(path[0]) {
+        collect(value, &path[1..], &mut out);
+    }
+
+    out
+}
@@ -1,21 +1,92 @@
-use mongodb::{bson::doc, sync::Collection};
+use bson::{oid::ObjectId, Bson, Document};
+use mongodb::{
+    bson::{doc, DateTime as MongoDateTime},
+    options::{FindOneAndUpdateOptions, FindOneOptions, FindOptions, ReturnDocument},
+    results::InsertOneResult,
+    sync::{Client, Collection},
+};
 use rocket::serde::DeserializeOwned;
 use serde::Serialize;
+use serde_json::Value;
 
 use crate::{
-    db::mongo::{shrimp::aggregation::Qilter, Crud},
+    db::mongo::{search, shrimp::aggregation::Qilter, varys::Varys, Crud},
     err_internal, err_not_found,
     errors::HubError,
-    model::shrimp::{Paws, ReactionKind, Shrimp},
+    macro_crud,
+    model::{
+        account::Tariff,
+        anecdote::Anecdote,
+        joke::Joke,
+        language::Detection,
+        lexicon::Lexicon,
+        punch::Punch,
+        shrimp::{
+            history::Served, moderation::FlagAudit, reaction::Reaction, revision::Revision,
+            Category, Flag, Paws, ReactionKind, Shrimp, Status, StatusChange,
+        },
+        similarity::{Fingerprint, Similar, FLAG_THRESHOLD},
+        story::Story,
+        tag::Tag,
+        trash::Trash,
+    },
 };
 
+/// Поле времени последнего изменения записи
+pub const MODIFIED_FIELD: &str = "_header.modified";
+
+/// Поле времени первого одобрения записи
+pub const PUBLISHED_FIELD: &str = "_header.published";
+
+/// Фильтр одобренной записи, которая не лежит в корзине.
+/// Записи, созданные до появления модерации, считаются одобренными.
+pub fn published(record_id: &str) -> Document {
+    let mut filter = Trash::alive(record_id);
+    filter.insert(
+        "_meta-data.status.kind",
+        doc! {"$in": [Status::Approved.to_string(), Bson::Null]},
+    );
+
+    filter
+}
+
+/// Время первого одобрения для записей, одобренных до его появления: им считается время создания.
+/// Возвращает количество обновленных записей.
+pub fn backfill_published(client: &Client) -> Result<u64, HubError> {
+    let mut count = 0;
+
+    for category in Category::all() {
+        let collection: Collection<Document> = Varys::get(client, category.into());
+        let filter = doc! {
+            PUBLISHED_FIELD: {"$exists": false},
+            "_meta-data.status.kind": {"$in": [Status::Approved.to_string(), Bson::Null]}
+        };
+        let update = vec![doc! {"$set": {PUBLISHED_FIELD: "$_header.timestamp"}}];
+
+        count += collection
+            .update_many(filter, update, None)
+            .map_err(|err| err_internal!("Faild to backfill publication time", err))?
+            .modified_count;
+    }
+
+    Ok(count)
+}
+
 impl<'a, T> Crud<'a, Shrimp<T>> for Shrimp<T>
 where
     T: Serialize + DeserializeOwned + Unpin + std::marker::Send + Sync,
     T: Paws,
 {
+    fn create(
+        collection: Collection<Document>,
+        data: &Shrimp<T>,
+    ) -> Result<InsertOneResult, HubError> {
+        Ok(collection.insert_one(data.document()?, None)?)
+    }
+
+    /// Находятся только одобренные записи вне корзины, как и в списках
     fn get_by_id(collection: Collection<Shrimp<T>>, id: &str) -> Result<Shrimp<T>, HubError> {
-        let filter = doc! {"_id": id};
+        let filter = published(id);
         let update = doc! {"$inc": {"_header.counter": 1}};
 
         match collection.find_one_and_update(filter, update, None) {
@@ -31,6 +102,40 @@ where
     T: Serialize + DeserializeOwned + Unpin + std::marker::Send + Sync,
     T: Paws,
 {
+    /// Документ записи вместе с полем языка для текстового индекса
+    pub fn document(&self) -> Result<Document, HubError> {
+        let mut doc = bson::to_document(self)?;
+        doc.insert(
+            search::LANGUAGE_FIELD,
+            search::text_language(&self.tail.lang),
+        );
+
+        Ok(doc)
+    }
+
+    /// Создание записи с проверкой на почти-дубликаты.
+    /// Слишком похожая запись отклоняется, просто похожая помечается для модераторов.
+    pub fn insert(
+        mut self,
+        client: &Client,
+    ) -> Result<(InsertOneResult, Option<Similar>), HubError> {
+        let fingerprint = Fingerprint::of(&self.document()?)?;
+        let similar = fingerprint.nearest(client, FLAG_THRESHOLD)?;
+
+        if let Some(similar) = similar.as_ref().filter(|s| s.is_duplicate()) {
+            return Err(Similar::rejection(similar));
+        }
+
+        self.tail.similar = similar.clone();
+
+        let collection = Varys::get(client, self.body.get_category().into());
+        let result = Shrimp::create(collection, &self)?;
+        fingerprint.save(client)?;
+        Tag::register(client, &self.tail.tags)?;
+
+        Ok((result, similar))
+    }
+
     pub fn inc_counter(&self, collection: &Collection<Shrimp<T>>) -> Result<&Self, HubError> {
         let query = doc! {"_id": self.id.clone()};
         let update = doc! {"$inc": {"_header.counter": 1}};
@@ -59,36 +164,578 @@ where
             None => Ok(None),
         }
     }
+}
+
+impl Category {
+    /// Автор записи данной категории
+    pub fn record_author(&self, client: &Client, record_id: &str) -> Result<String, HubError> {
+        let collection: Collection<Document> = Varys::get(client, self.clone().into());
+
+        match collection.find_one(Trash::alive(record_id), None)? {
+            Some(record) => Self::author_of(&record),
+            None => Err(err_not_found!(collection.name())),
+        }
+    }
 
-    pub fn add_reaction(
-        collection: &Collection<Shrimp<T>>,
+    /// Автор одобренной записи данной категории, которая не лежит в корзине
+    pub fn published_author(&self, client: &Client, record_id: &str) -> Result<String, HubError> {
+        let collection: Collection<Document> = Varys::get(client, self.clone().into());
+
+        match collection.find_one(published(record_id), None)? {
+            Some(record) => Self::author_of(&record),
+            None => Err(err_not_found!(collection.name())),
+        }
+    }
+
+    /// Категория, в которой лежит одобренная запись с данным id
+    pub fn locate(client: &Client, record_id: &str) -> Result<Category, HubError> {
+        for category in Category::all() {
+            let collection: Collection<Document> = Varys::get(client, category.clone().into());
+            let options = FindOneOptions::builder()
+                .projection(doc! {"_id": 1})
+                .build();
+
+            if collection
+                .find_one(published(record_id), options)?
+                .is_some()
+            {
+                return Ok(category);
+            }
+        }
+
+        Err(err_not_found!("record"))
+    }
+
+    /// Смена этапа модерации записи, возвращает автора и статус записи до изменения.
+    /// При первом одобрении запоминается время, с которого запись видна всем.
+    pub fn set_status(
+        &self,
+        client: &Client,
         record_id: &str,
-        reaction: ReactionKind,
-    ) -> Result<(), HubError> {
-        let query = doc! {"_id": record_id};
-        let update = doc! {"$inc": {
-                format!("_meta-data.reactions.{}", reaction.to_string().to_lowercase()): 1
+        status: &Status,
+    ) -> Result<StatusChange, HubError> {
+        let collection: Collection<Document> = Varys::get(client, self.clone().into());
+        let now = MongoDateTime::now().timestamp_millis();
+
+        // Обновление конвейером, чтобы не перезаписывать время первого одобрения
+        let mut fields = doc! {
+            "_meta-data.status": {"$literal": bson::to_bson(status)?},
+            MODIFIED_FIELD: now
+        };
+        if *status == Status::Approved {
+            fields.insert(
+                PUBLISHED_FIELD,
+                doc! {"$ifNull": [format!("${}", PUBLISHED_FIELD), now]},
+            );
+        }
+        let update = vec![doc! {"$set": fields}];
+
+        match collection.find_one_and_update(Trash::alive(record_id), update, None) {
+            Ok(Some(record)) => Self::status_change(&record),
+            Ok(None) => Err(err_not_found!(collection.name())),
+            Err(err) => Err(err_internal!("Faild to update record status", err)),
+        }
+    }
+
+    /// Сериализация записи любой категории согласно тарифу.
+    /// Категория определяется по полю `category` самой записи.
+    pub fn tariffing(record: Document, tariff: &Tariff) -> Result<Value, HubError> {
+        let category: Category =
+            bson::from_bson(record.get("category").cloned().unwrap_or(Bson::Null))?;
+
+        let value = match category {
+            Category::Anecdote => {
+                bson::from_document::<Shrimp<Anecdote>>(record)?.tariffing(tariff, &None)
+            }
+            Category::Joke => bson::from_document::<Shrimp<Joke>>(record)?.tariffing(tariff, &None),
+            Category::Punch => {
+                bson::from_document::<Shrimp<Punch>>(record)?.tariffing(tariff, &None)
+            }
+            Category::Story => {
+                bson::from_document::<Shrimp<Story>>(record)?.tariffing(tariff, &None)
             }
         };
 
-        match collection.update_one(query, update, None) {
-            Ok(ur) if ur.modified_count > 0 => Ok(()),
+        Ok(value)
+    }
+
+    /// Полная запись любой категории для модераторов
+    pub fn moderation(record: Document) -> Result<Value, HubError> {
+        let category: Category =
+            bson::from_bson(record.get("category").cloned().unwrap_or(Bson::Null))?;
+
+        let value = match category {
+            Category::Anecdote => bson::from_document::<Shrimp<Anecdote>>(record)?.moderation(),
+            Category::Joke => bson::from_document::<Shrimp<Joke>>(record)?.moderation(),
+            Category::Punch => bson::from_document::<Shrimp<Punch>>(record)?.moderation(),
+            Category::Story => bson::from_document::<Shrimp<Story>>(record)?.moderation(),
+        };
+
+        Ok(value)
+    }
+
+    /// Статус и время первого одобрения из записи до изменения
+    fn status_change(record: &Document) -> Result<StatusChange, HubError> {
+        let previous = match record
+            .get_document("_meta-data")
+            .and_then(|tail| tail.get_document("status"))
+        {
+            Ok(status) => bson::from_document(status.clone())?,
+            Err(_) => Status::default(),
+        };
+
+        let published = match record
+            .get_document("_header")
+            .ok()
+            .and_then(|head| head.get("published"))
+        {
+            Some(Bson::Int64(value)) => Some(*value),
+            Some(Bson::Int32(value)) => Some(i64::from(*value)),
+            _ => None,
+        };
+
+        Ok(StatusChange {
+            author: Self::author_of(record)?,
+            previous,
+            published,
+        })
+    }
+
+    fn author_of(record: &Document) -> Result<String, HubError> {
+        record
+            .get_document("_meta-data")
+            .and_then(|tail| tail.get_str("author"))
+            .map(|author| author.to_string())
+            .map_err(|err| err_internal!("Faild to read record author", err))
+    }
+}
+
+macro_crud!(Revision);
+impl Revision {
+    /// Все версии записи, начиная с самой свежей
+    pub fn roll(
+        client: &Client,
+        category: &Category,
+        record_id: &str,
+    ) -> Result<Vec<Revision>, HubError> {
+        let collection: Collection<Revision> = Varys::get(client, Varys::Revisions);
+        let filter = doc! {"record_id": record_id, "category": bson::to_bson(category)?};
+        let options = FindOptions::builder().sort(doc! {"created_at": -1}).build();
+
+        let mut cursor = collection.find(filter, options)?;
+        let mut result: Vec<Revision> = Vec::new();
+
+        while let Some(doc) = cursor.next() {
+            result.push(doc?);
+        }
+
+        Ok(result)
+    }
+
+    pub fn get_by_record(
+        client: &Client,
+        record_id: &str,
+        revision_id: &str,
+    ) -> Result<Revision, HubError> {
+        let collection: Collection<Revision> = Varys::get(client, Varys::Revisions);
+        let id = ObjectId::parse_str(revision_id)
+            .map_err(|_| HubError::new_unprocessable("Invalid format of revision id", None))?;
+
+        match collection.find_one(doc! {"_id": id, "record_id": record_id}, None)? {
+            Some(revision) => Ok(revision),
+            None => Err(err_not_found!("revision")),
+        }
+    }
+
+    /// Сохраняет текущую версию записи в истории и заменяет тело записи новым.
+    /// Счетчик просмотров, реакции и прочие мета-данные записи остаются нетронутыми.
+    /// Измененная запись заново проходит проверку словарями и поиск дубликатов
+    /// и возвращается на модерацию.
+    #[allow(clippy::too_many_arguments)]
+    pub fn revise(
+        client: &Client,
+        lexicon: &Lexicon,
+        category: Category,
+        record_id: &str,
+        editor: &str,
+        body: Document,
+        lang: &str,
+        detection: Option<&Detection>,
+        tags: &[String],
+    ) -> Result<(), HubError> {
+        let collection: Collection<Document> = Varys::get(client, category.clone().into());
+        let current = match collection.find_one(doc! {"_id": record_id}, None)? {
+            Some(record) => record,
+            None => return Err(err_not_found!(collection.name())),
+        };
+        let tags = Tag::resolve(client, tags)?;
+
+        let mut probe = body.clone();
+        probe.insert("_id", record_id);
+        probe.insert("category", bson::to_bson(&category)?);
+
+        let fingerprint = Fingerprint::of(&probe)?;
+        let similar = fingerprint.nearest(client, FLAG_THRESHOLD)?;
+
+        if let Some(similar) = similar.as_ref().filter(|s| s.is_duplicate()) {
+            return Err(Similar::rejection(similar));
+        }
+
+        let text: Vec<String> = category
+            .text_fields()
+            .iter()
+            .flat_map(|(field, _)| search::texts(&probe, field))
+            .collect();
+        let triggers = lexicon.suggest(&text.join(" "));
+
+        Revision::create(
+            Varys::get(client, Varys::Revisions),
+            &Revision::new(record_id, category.clone(), editor, current.clone()),
+        )?;
+
+        let mut set = body;
+        set.insert("_meta-data.language", lang);
+        set.insert(search::LANGUAGE_FIELD, search::text_language(lang));
+        set.insert("_meta-data.tags", &tags);
+        set.insert("_meta-data.status", bson::to_bson(&Status::Pending)?);
+        set.insert(MODIFIED_FIELD, MongoDateTime::now().timestamp_millis());
+
+        // Флаги, предложенные словарями, добавляются к уже выставленным
+        for flag in triggers.keys() {
+            set.insert(
+                format!("_meta-data.flags.{}", flag.to_string().to_ascii_lowercase()),
+                true,
+            );
+        }
+        set.insert("_meta-data.triggers", bson::to_bson(&triggers)?);
+
+        // Поля тела, которых нет в новой версии (например необязательные), удаляются
+        let mut unset = Document::new();
+        match similar.as_ref() {
+            Some(similar) => {
+                set.insert("_meta-data.similar", bson::to_bson(similar)?);
+            }
+            None => {
+                unset.insert("_meta-data.similar", "");
+            }
+        }
+
+        match detection {
+            Some(detection) => {
+                set.insert("_meta-data.language_detection", bson::to_bson(detection)?);
+            }
+            None => {
+                unset.insert("_meta-data.language_detection", "");
+            }
+        }
+
+        for key in current.keys() {
+            if !key.starts_with('_') && !set.contains_key(key) {
+                unset.insert(key, "");
+            }
+        }
+
+        let mut update = doc! {"$set": set};
+        if !unset.is_empty() {
+            update.insert("$unset", unset);
+        }
+
+        match collection.update_one(doc! {"_id": record_id}, update, None) {
+            Ok(ur) if ur.matched_count > 0 => {
+                fingerprint.save(client)?;
+                Tag::register(client, &tags)
+            }
             Ok(_) => Err(err_not_found!(collection.name())),
+            Err(err) => Err(err_internal!("Faild to update record", err)),
+        }
+    }
+
+    /// Откат записи к этой версии, текущая версия также попадает в историю
+    pub fn rollback(
+        &self,
+        client: &Client,
+        lexicon: &Lexicon,
+        editor: &str,
+    ) -> Result<(), HubError> {
+        let tail = self
+            .snapshot
+            .get_document("_meta-data")
+            .map_err(|err| err_internal!("Faild to read revision", err))?;
+
+        let lang = tail
+            .get_str("language")
+            .map_err(|err| err_internal!("Faild to read revision", err))?;
+
+        let tags: Vec<String> = bson::from_bson(
+            tail.get("tags")
+                .cloned()
+                .unwrap_or(bson::Bson::Array(Vec::new())),
+        )?;
+
+        let detection: Option<Detection> = bson::from_bson(
+            tail.get("language_detection")
+                .cloned()
+                .unwrap_or(bson::Bson::Null),
+        )?;
+
+        Revision::revise(
+            client,
+            lexicon,
+            self.category.clone(),
+            &self.record_id,
+            editor,
+            self.body(),
+            lang,
+            detection.as_ref(),
+            &tags,
+        )
+    }
+}
+
+macro_crud!(Served);
+impl Served {
+    /// Запоминание показанной записи
+    pub fn remember(
+        client: &Client,
+        identity: &str,
+        category: Category,
+        record_id: &str,
+    ) -> Result<(), HubError> {
+        Served::create(
+            Varys::get(client, Varys::Served),
+            &Served::new(identity, record_id, category),
+        )?;
+
+        Ok(())
+    }
+
+    /// Записи, показанные потребителю за последние `window` часов
+    pub fn recent(client: &Client, identity: &str, window: u32) -> Result<Vec<String>, HubError> {
+        let collection: Collection<Served> = Varys::get(client, Varys::Served);
+        let since = MongoDateTime::now().timestamp_millis() - i64::from(window) * 3_600_000;
+        let filter = doc! {
+            "identity": identity,
+            "served_at": {"$gte": MongoDateTime::from_millis(since)}
+        };
+
+        let mut result: Vec<String> = Vec::new();
+        for served in collection.find(filter, None)? {
+            result.push(served?.record_id);
+        }
+
+        Ok(result)
+    }
+
+    /// Очистка истории показов потребителя
+    pub fn reset(client: &Client, identity: &str) -> Result<u64, HubError> {
+        let collection: Collection<Served> = Varys::get(client, Varys::Served);
+        let result = collection.delete_many(doc! {"identity": identity}, None)?;
+
+        Ok(result.deleted_count)
+    }
+}
+
+macro_crud!(Reaction);
+impl Reaction {
+    /// Установка или смена реакции потребителя на запись.
+    /// Общий счетчик реакций записи меняется только на разницу со старой реакцией.
+    pub fn set(
+        client: &Client,
+        category: Category,
+        record_id: &str,
+        identity: &str,
+        kind: ReactionKind,
+    ) -> Result<(), HubError> {
+        let records: Collection<Document> = Varys::get(client, category.clone().into());
+        if records.count_documents(Trash::alive(record_id), None)? == 0 {
+            return Err(err_not_found!(records.name()));
+        }
+
+        let collection: Collection<Reaction> = Varys::get(client, Varys::Reactions);
+        let options = FindOneAndUpdateOptions::builder()
+            .upsert(true)
+            .return_document(ReturnDocument::Before)
+            .build();
+
+        let previous = collection.find_one_and_update(
+            doc! {"record_id": record_id, "identity": identity},
+            doc! {"$set": {
+                "kind": bson::to_bson(&kind)?,
+                "category": bson::to_bson(&category)?,
+                "created_at": MongoDateTime::now()
+            }},
+            options,
+        )?;
+
+        let mut inc = Document::new();
+        match previous {
+            Some(prev) if prev.kind == kind => return Ok(()),
+            Some(prev) => {
+                inc.insert(Self::counter(&prev.kind), -1);
+            }
+            None => {}
+        }
+        inc.insert(Self::counter(&kind), 1);
+
+        // Изменение счетчиков попадает в инкрементальный снимок
+        let update = doc! {
+            "$inc": inc,
+            "$set": {MODIFIED_FIELD: MongoDateTime::now().timestamp_millis()}
+        };
+
+        match records.update_one(doc! {"_id": record_id}, update, None) {
+            Ok(_) => Ok(()),
             Err(err) => Err(err_internal!("Faild to add reaction", err)),
         }
     }
+
+    /// Отмена реакции потребителя на запись
+    pub fn remove(
+        client: &Client,
+        category: Category,
+        record_id: &str,
+        identity: &str,
+    ) -> Result<(), HubError> {
+        let collection: Collection<Reaction> = Varys::get(client, Varys::Reactions);
+        let filter = doc! {
+            "record_id": record_id,
+            "identity": identity,
+            "category": bson::to_bson(&category)?
+        };
+
+        let previous = match collection.find_one_and_delete(filter, None)? {
+            Some(reaction) => reaction,
+            None => return Err(err_not_found!("reaction")),
+        };
+
+        let records: Collection<Document> = Varys::get(client, category.into());
+        let update = doc! {
+            "$inc": {Self::counter(&previous.kind): -1},
+            "$set": {MODIFIED_FIELD: MongoDateTime::now().timestamp_millis()}
+        };
+
+        match records.update_one(doc! {"_id": record_id}, update, None) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err_internal!("Faild to remove reaction", err)),
+        }
+    }
+
+    /// Реакция потребителя на запись
+    pub fn get(
+        client: &Client,
+        category: &Category,
+        record_id: &str,
+        identity: &str,
+    ) -> Result<Reaction, HubError> {
+        let collection: Collection<Reaction> = Varys::get(client, Varys::Reactions);
+        let filter = doc! {
+            "record_id": record_id,
+            "identity": identity,
+            "category": bson::to_bson(category)?
+        };
+
+        match collection.find_one(filter, None)? {
+            Some(reaction) => Ok(reaction),
+            None => Err(err_not_found!("reaction")),
+        }
+    }
+
+    fn counter(kind: &ReactionKind) -> String {
+        format!("_meta-data.reactions.{}", kind.to_string().to_lowercase())
+    }
+}
+
+macro_crud!(FlagAudit);
+impl FlagAudit {
+    /// Установка флага деликатности записи.
+    /// Каждое фактическое изменение флага сохраняется в журнал вместе с модератором.
+    pub fn apply(
+        client: &Client,
+        category: Category,
+        record_id: &str,
+        flag: Flag,
+        value: bool,
+        moderator: &str,
+    ) -> Result<(), HubError> {
+        let collection: Collection<Document> = Varys::get(client, category.clone().into());
+        let field = format!("_meta-data.flags.{}", flag.to_string().to_ascii_lowercase());
+
+        // Меняется только один флаг и только если у него другое значение,
+        // поэтому параллельные изменения разных флагов не затирают друг друга
+        let mut filter = Trash::alive(record_id);
+        filter.insert(&field, doc! {"$ne": value});
+
+        let update = doc! {"$set": {
+            &field: value,
+            MODIFIED_FIELD: MongoDateTime::now().timestamp_millis()
+        }};
+
+        if collection.update_one(filter, update, None)?.modified_count != 1 {
+            return match collection.find_one(Trash::alive(record_id), None)? {
+                Some(_) => Ok(()),
+                None => Err(err_not_found!(collection.name())),
+            };
+        }
+
+        FlagAudit::create(
+            Varys::get(client, Varys::FlagAudit),
+            &FlagAudit::new(record_id, category, flag, value, moderator),
+        )?;
+
+        Ok(())
+    }
+
+    /// Журнал изменения флагов записи, начиная с самых свежих
+    pub fn roll(
+        client: &Client,
+        category: &Category,
+        record_id: &str,
+    ) -> Result<Vec<FlagAudit>, HubError> {
+        let collection: Collection<FlagAudit> = Varys::get(client, Varys::FlagAudit);
+        let filter = doc! {"record_id": record_id, "category": bson::to_bson(category)?};
+        let options = FindOptions::builder().sort(doc! {"created_at": -1}).build();
+
+        let cursor = collection.find(filter, options)?;
+        let mut result: Vec<FlagAudit> = Vec::new();
+
+        for doc in cursor {
+            result.push(doc?);
+        }
+
+        Ok(result)
+    }
 }
 
 pub mod aggregation {
-    use bson::{doc, Document};
+    use bson::{doc, Bson, Document};
+    use mongodb::sync::{Client, Collection};
 
-    use crate::model::shrimp::Flag;
+    use crate::{
+        db::mongo::{shrimp::PUBLISHED_FIELD, trash::TRASH_FIELD, varys::Varys},
+        err_internal,
+        errors::HubError,
+        model::{
+            language,
+            shrimp::{Category, Flag, Period, ReactionKind, SortKey, SortOrder, Status},
+            tag::{normalize, Tag},
+        },
+    };
 
     pub struct Qilter<'a> {
         author: Option<&'a str>,
-        language: Option<&'a str>,
+
+        /// Записи любого из перечисленных авторов, например подписок пользователя
+        authors: Option<Vec<String>>,
+
+        /// Код языка ISO 639-1 после проверки в `resolve`
+        language: Option<String>,
         flags: Option<Vec<Flag>>,
-        tags: Option<Vec<&'a str>>,
+
+        /// Для каждого запрошенного тега — все его имена (каноническое и синонимы)
+        tags: Option<Vec<Vec<String>>>,
+
+        /// Записи, которые нужно исключить из выборки
+        exclude: Option<Vec<String>>,
     }
 
     impl<'a> Qilter<'a> {
@@ -100,20 +747,98 @@ pub mod aggregation {
         ) -> Self {
             Qilter {
                 author,
-                language,
+                authors: None,
+                language: language.map(String::from),
                 flags,
-                tags,
+                tags: tags.map(|v| v.iter().map(|t| vec![t.to_string()]).collect()),
+                exclude: None,
             }
         }
-        pub fn pipeline(&self) -> Vec<Document> {
-            let mut pipeline: Vec<Document> = Vec::new();
+
+        /// Исключение уже показанных записей
+        pub fn exclude(mut self, ids: Vec<String>) -> Self {
+            self.exclude = if ids.is_empty() { None } else { Some(ids) };
+            self
+        }
+
+        /// Отбор записей только указанных авторов
+        pub fn authors(mut self, names: Vec<String>) -> Self {
+            self.authors = Some(names);
+            self
+        }
+
+        /// Тот же фильтр, но без исключенных записей
+        pub fn without_exclude(&self) -> Self {
+            Qilter {
+                author: self.author,
+                authors: self.authors.clone(),
+                language: self.language.clone(),
+                flags: self.flags.clone(),
+                tags: self.tags.clone(),
+                exclude: None,
+            }
+        }
+
+        /// Проверка языка и замена запрошенных тегов на все их имена из реестра
+        pub fn resolve(mut self, client: &Client) -> Result<Self, HubError> {
+            if let Some(lang) = self.language.as_ref() {
+                self.language = Some(language::validate(lang)?);
+            }
+
+            if let Some(tags) = self.tags.as_mut() {
+                for names in tags.iter_mut() {
+                    *names = Tag::names_of(client, &names[0])?;
+                }
+            }
+
+            Ok(self)
+        }
+
+        /// Код запрошенного языка
+        pub fn language(&self) -> Option<&str> {
+            self.language.as_deref()
+        }
+
+        /// Канонический ключ фильтра: одинаковые фильтры, записанные
+        /// в разном порядке, дают одинаковый ключ
+        pub fn key(&self) -> String {
+            let mut flags: Vec<String> = self
+                .flags
+                .iter()
+                .flatten()
+                .map(|f| f.to_string().to_ascii_lowercase())
+                .collect();
+            flags.sort();
+            flags.dedup();
+
+            let mut tags: Vec<String> = self
+                .tags
+                .iter()
+                .flatten()
+                .filter_map(|names| names.iter().map(|name| normalize(name)).min())
+                .collect();
+            tags.sort();
+            tags.dedup();
+
+            format!(
+                "author={};lang={};flag={};tag={}",
+                self.author.unwrap_or_default(),
+                self.language.as_deref().unwrap_or_default(),
+                flags.join(","),
+                tags.join(",")
+            )
+        }
+
+        /// Стадии фильтрации записей без выборки
+        pub fn stages(&self) -> Vec<Document> {
+            let mut pipeline: Vec<Document> = vec![Self::alive()];
 
             self.tags.as_ref().map(|vector| {
-                for tag in vector.to_owned() {
+                for names in vector {
                     pipeline.push(doc! {
                         "$match": {
-                            "$expr": {
-                              "$in": [tag, "$_meta-data.tags"],
+                            "_meta-data.tags": {
+                              "$in": names,
                             },
                           }
                     })
@@ -128,13 +853,21 @@ pub mod aggregation {
                 })
             });
 
-            self.language.map(|l| {
+            if let Some(names) = self.authors.as_ref() {
+                pipeline.push(doc! {
+                    "$match": {
+                        "_meta-data.author": { "$in": names }
+                    }
+                })
+            }
+
+            if let Some(l) = self.language.as_ref() {
                 pipeline.push(doc! {
                     "$match": {
                         "_meta-data.language": l
                     }
                 })
-            });
+            }
 
             self.flags.as_ref().map(|vector| {
                 let mut d = Document::new();
@@ -151,6 +884,22 @@ pub mod aggregation {
                 })
             });
 
+            if let Some(ids) = self.exclude.as_ref() {
+                pipeline.push(doc! {
+                    "$match": {
+                        "_id": { "$nin": ids }
+                    }
+                })
+            }
+
+            pipeline
+        }
+
+        /// Случайная запись из числа одобренных модераторами
+        pub fn pipeline(&self) -> Vec<Document> {
+            let mut pipeline = self.stages();
+
+            pipeline.push(Self::status(&Status::Approved));
             pipeline.push(doc! {
               "$sample": {
                 "size": 1
@@ -159,5 +908,316 @@ pub mod aggregation {
 
             pipeline
         }
+
+        /// Отбор записей, которые не лежат в корзине
+        pub fn alive() -> Document {
+            doc! {
+                "$match": {
+                    TRASH_FIELD: { "$exists": false }
+                }
+            }
+        }
+
+        /// Отбор записей по этапу модерации.
+        /// У записей, созданных до появления модерации, статуса нет — они считаются одобренными.
+        pub fn status(status: &Status) -> Document {
+            match status {
+                Status::Approved => doc! {
+                    "$match": {
+                        "_meta-data.status.kind": { "$in": [status.to_string(), Bson::Null] }
+                    }
+                },
+
+                _ => doc! {
+                    "$match": {
+                        "_meta-data.status.kind": status.to_string()
+                    }
+                },
+            }
+        }
+    }
+
+    /// Постраничная выборка записей.
+    /// Страница задается либо номером `page`, либо курсором `cursor` из предыдущего ответа.
+    /// Курсор имеет вид `<значение поля сортировки>_<id последней записи>`.
+    pub struct Pagination {
+        sort: SortKey,
+        order: SortOrder,
+        limit: i64,
+        page: u64,
+        cursor: Option<(i64, String)>,
+    }
+
+    impl Pagination {
+        /// Имя временного поля, по которому идет сортировка
+        const SORT_FIELD: &'static str = "_sort";
+
+        pub fn new(
+            sort: SortKey,
+            order: SortOrder,
+            limit: i64,
+            page: Option<u64>,
+            cursor: Option<&str>,
+        ) -> Result<Self, HubError> {
+            let cursor = match cursor {
+                Some(c) => {
+                    let (value, id) = c
+                        .split_once('_')
+                        .and_then(|(v, id)| Some((v.parse::<i64>().ok()?, id.to_string())))
+                        .ok_or_else(|| HubError::new_unprocessable("Cursor is invalid", None))?;
+
+                    Some((value, id))
+                }
+
+                None => None,
+            };
+
+            Ok(Pagination {
+                sort,
+                order,
+                limit,
+                page: page.unwrap_or(1).max(1),
+                cursor,
+            })
+        }
+
+        /// Стадии сортировки и выборки страницы.
+        /// Запрашивается на одну запись больше, чтобы понять, есть ли следующая страница.
+        pub fn stages(&self) -> Vec<Document> {
+            let value = match self.sort {
+                SortKey::Timestamp => Bson::String("$_header.timestamp".to_string()),
+                SortKey::Published => Bson::Document(doc! {
+                    "$ifNull": [format!("${}", PUBLISHED_FIELD), "$_header.timestamp"]
+                }),
+                SortKey::Counter => Bson::String("$_header.counter".to_string()),
+                SortKey::Reactions => Bson::Document(doc! {
+                    "$sum": {
+                        "$map": {
+                            "input": { "$objectToArray": { "$ifNull": ["$_meta-data.reactions", {}] } },
+                            "in": "$$this.v"
+                        }
+                    }
+                }),
+            };
+
+            let direction = self.order.direction();
+            let mut pipeline = vec![doc! {"$addFields": { Self::SORT_FIELD: value }}];
+
+            match self.cursor.as_ref() {
+                Some((value, id)) => {
+                    let op = if direction > 0 { "$gt" } else { "$lt" };
+
+                    pipeline.push(doc! {
+                        "$match": {
+                            "$or": [
+                                { Self::SORT_FIELD: { op: value } },
+                                { Self::SORT_FIELD: value, "_id": { op: id } }
+                            ]
+                        }
+                    });
+                    pipeline
+                        .push(doc! {"$sort": { Self::SORT_FIELD: direction, "_id": direction }});
+                }
+
+                None => {
+                    pipeline
+                        .push(doc! {"$sort": { Self::SORT_FIELD: direction, "_id": direction }});
+                    pipeline.push(doc! {"$skip": (self.page - 1) as i64 * self.limit});
+                }
+            }
+
+            pipeline.push(doc! {"$limit": self.limit + 1});
+            pipeline
+        }
+
+        /// Обрезка лишней записи и удаление временного поля.
+        /// Возвращает курсор следующей страницы, если она есть.
+        pub fn finish(&self, records: &mut Vec<Document>) -> Option<String> {
+            let has_next = records.len() as i64 > self.limit;
+            records.truncate(self.limit as usize);
+
+            let next = match records.last() {
+                Some(last) if has_next => {
+                    let value = match last.get(Self::SORT_FIELD) {
+                        Some(Bson::Int32(v)) => *v as i64,
+                        Some(Bson::Int64(v)) => *v,
+                        Some(Bson::Double(v)) => *v as i64,
+                        _ => 0,
+                    };
+
+                    last.get_str("_id")
+                        .ok()
+                        .map(|id| format!("{}_{}", value, id))
+                }
+
+                _ => None,
+            };
+
+            for record in records.iter_mut() {
+                record.remove(Self::SORT_FIELD);
+            }
+
+            next
+        }
+    }
+
+    /// Способ ранжирования записей для подборок
+    pub enum Ranking {
+        /// Нижняя граница доверительного интервала Уилсона для доли одобрительных реакций.
+        /// Запись с 5 одобрениями из 5 оказывается ниже записи с 95 из 100.
+        Top,
+
+        /// Вовлеченность (показы и реакции), затухающая со временем.
+        /// Свежие записи, быстро набирающие реакции, поднимаются выше.
+        Trending,
+    }
+
+    impl Ranking {
+        /// Имя временного поля с рейтингом записи
+        pub const RANK_FIELD: &'static str = "_rank";
+
+        /// Квантиль нормального распределения для доверительного уровня 95%
+        const Z: f64 = 1.96;
+
+        /// Скорость затухания популярности со временем
+        const GRAVITY: f64 = 1.8;
+
+        /// Стадии отбора записей за период и вычисления рейтинга.
+        /// `now` — текущее время в миллисекундах.
+        pub fn stages(&self, period: &Period, now: i64) -> Vec<Document> {
+            let mut pipeline: Vec<Document> = Vec::new();
+
+            if let Some(millis) = period.millis() {
+                pipeline.push(doc! {"$match": {"_header.timestamp": {"$gte": now - millis}}});
+            }
+
+            let reactions = |positive: Option<bool>| -> Bson {
+                let counters: Vec<Bson> = ReactionKind::all()
+                    .iter()
+                    .filter(|kind| match positive {
+                        Some(p) => kind.is_positive() == p,
+                        None => true,
+                    })
+                    .map(|kind| {
+                        let field =
+                            format!("$_meta-data.reactions.{}", kind.to_string().to_lowercase());
+                        Bson::Document(doc! {"$ifNull": [field, 0]})
+                    })
+                    .collect();
+
+                Bson::Document(doc! {"$add": counters})
+            };
+
+            match self {
+                Ranking::Top => {
+                    let z2 = Self::Z * Self::Z;
+                    let phat = doc! {"$divide": ["$_pos", "$_n"]};
+
+                    pipeline.push(doc! {"$addFields": {
+                        "_pos": reactions(Some(true)),
+                        "_n": reactions(None)
+                    }});
+                    pipeline.push(doc! {"$addFields": {
+                        Self::RANK_FIELD: {
+                            "$cond": [
+                                {"$eq": ["$_n", 0]},
+                                0.0,
+                                {"$divide": [
+                                    {"$subtract": [
+                                        {"$add": [&phat, {"$divide": [z2, {"$multiply": [2, "$_n"]}]}]},
+                                        {"$multiply": [Self::Z, {"$sqrt": {"$divide": [
+                                            {"$add": [
+                                                {"$multiply": [&phat, {"$subtract": [1, &phat]}]},
+                                                {"$divide": [z2, {"$multiply": [4, "$_n"]}]}
+                                            ]},
+                                            "$_n"
+                                        ]}}]}
+                                    ]},
+                                    {"$add": [1, {"$divide": [z2, "$_n"]}]}
+                                ]}
+                            ]
+                        }
+                    }});
+                    pipeline.push(doc! {"$project": {"_pos": 0, "_n": 0}});
+                }
+
+                Ranking::Trending => {
+                    let age_hours = doc! {"$max": [
+                        0,
+                        {"$divide": [{"$subtract": [now, "$_header.timestamp"]}, 3_600_000]}
+                    ]};
+
+                    pipeline.push(doc! {"$addFields": {
+                        Self::RANK_FIELD: {
+                            "$divide": [
+                                {"$add": [{"$divide": ["$_header.counter", 10]}, reactions(None)]},
+                                {"$pow": [{"$add": [age_hours, 2]}, Self::GRAVITY]}
+                            ]
+                        }
+                    }});
+                }
+            }
+
+            pipeline
+        }
+
+        /// Стадии объединенного результата: сортировка по рейтингу
+        pub fn tail(limit: i64) -> Vec<Document> {
+            vec![
+                doc! {"$sort": {Self::RANK_FIELD: -1, "_id": 1}},
+                doc! {"$limit": limit},
+            ]
+        }
+
+        /// Извлечение рейтинга из записи вместе с удалением временного поля
+        pub fn take_score(record: &mut Document) -> f64 {
+            match record.remove(Self::RANK_FIELD) {
+                Some(Bson::Double(v)) => v,
+                Some(Bson::Int32(v)) => v as f64,
+                Some(Bson::Int64(v)) => v as f64,
+                _ => 0.0,
+            }
+        }
+    }
+
+    /// Выполнение конвейера сразу над коллекциями нескольких категорий.
+    /// Стадии `stages` применяются к каждой коллекции отдельно, стадии `tail` — к объединенному результату.
+    pub fn union(
+        client: &Client,
+        categories: &[Category],
+        stages: Vec<Document>,
+        tail: Vec<Document>,
+    ) -> Result<Vec<Document>, HubError> {
+        let (first, rest) = match categories.split_first() {
+            Some(split) => split,
+            None => return Ok(Vec::new()),
+        };
+
+        let collection: Collection<Document> = Varys::get(client, first.clone().into());
+        let mut pipeline = stages.clone();
+
+        for category in rest {
+            let coll: Collection<Document> = Varys::get(client, category.clone().into());
+
+            pipeline.push(doc! {
+                "$unionWith": {
+                    "coll": coll.name(),
+                    "pipeline": stages.clone()
+                }
+            });
+        }
+
+        pipeline.extend(tail);
+
+        let cursor = collection
+            .aggregate(pipeline, None)
+            .map_err(|err| err_internal!("Faild to aggregate records", err))?;
+        let mut result: Vec<Document> = Vec::new();
+
+        for doc in cursor {
+            result.push(doc?);
+        }
+
+        Ok(result)
     }
 }
@@ -0,0 +1,252 @@
+use bson::{doc, Bson, Document};
+use mongodb::{
+    options::{FindOneOptions, ReplaceOptions},
+    sync::{Client, Collection},
+};
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    db::mongo::{search, shrimp::published, trash::TRASH_FIELD, varys::Varys, Crud},
+    err_internal,
+    errors::HubError,
+    macro_crud,
+    model::{
+        shrimp::Category,
+        similarity::{ClusterRecord, DuplicateCluster, Fingerprint, Similar},
+        trash::Trash,
+    },
+};
+
+macro_crud!(Fingerprint);
+impl Fingerprint {
+    /// Отпечаток записи по ее документу: учитываются все текстовые поля категории
+    pub fn of(record: &Document) -> Result<Fingerprint, HubError> {
+        let category: Category =
+            bson::from_bson(record.get("category").cloned().unwrap_or(Bson::Null))?;
+        let id = record
+            .get_str("_id")
+            .map_err(|err| err_internal!("Faild to read record id", err))?;
+
+        let text: Vec<String> = category
+            .text_fields()
+            .iter()
+            .flat_map(|(field, _)| search::texts(record, field))
+            .collect();
+
+        Ok(Fingerprint::new(id, category, &text.join(" ")))
+    }
+
+    /// Самая похожая из уже одобренных записей, если схожесть не ниже `threshold`.
+    /// Записи на модерации и отклоненные в сравнении не участвуют.
+    pub fn nearest(&self, client: &Client, threshold: f64) -> Result<Option<Similar>, HubError> {
+        let collection: Collection<Fingerprint> = Varys::get(client, Varys::Fingerprints);
+        let filter = doc! {
+            "_id": {"$ne": &self.record_id},
+            "bands": {"$in": &self.bands}
+        };
+
+        let mut candidates: Vec<Similar> = Vec::new();
+        for candidate in collection.find(filter, None)? {
+            let candidate = candidate?;
+            let similarity = self.similarity(&candidate);
+
+            if similarity >= threshold {
+                candidates.push(Similar {
+                    record_id: candidate.record_id,
+                    category: candidate.category,
+                    similarity,
+                });
+            }
+        }
+
+        candidates.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+
+        for candidate in candidates {
+            if Fingerprint::approved(client, &candidate.category, &candidate.record_id)? {
+                return Ok(Some(candidate));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Запись одобрена и не лежит в корзине
+    fn approved(client: &Client, category: &Category, record_id: &str) -> Result<bool, HubError> {
+        let collection: Collection<Document> = Varys::get(client, category.clone().into());
+
+        let options = FindOneOptions::builder()
+            .projection(doc! {"_id": 1})
+            .build();
+
+        Ok(collection
+            .find_one(published(record_id), options)?
+            .is_some())
+    }
+
+    pub fn save(&self, client: &Client) -> Result<(), HubError> {
+        let collection: Collection<Fingerprint> = Varys::get(client, Varys::Fingerprints);
+        collection.replace_one(
+            doc! {"_id": &self.record_id},
+            self,
+            ReplaceOptions::builder().upsert(true).build(),
+        )?;
+
+        Ok(())
+    }
+
+    pub fn remove(client: &Client, record_id: &str) -> Result<(), HubError> {
+        let collection: Collection<Fingerprint> = Varys::get(client, Varys::Fingerprints);
+        collection.delete_one(doc! {"_id": record_id}, None)?;
+
+        Ok(())
+    }
+
+    /// Пересчет отпечатка после изменения текста записи
+    pub fn refresh(client: &Client, category: &Category, record_id: &str) -> Result<(), HubError> {
+        let collection: Collection<Document> = Varys::get(client, category.clone().into());
+
+        match collection.find_one(Trash::alive(record_id), None)? {
+            Some(record) => Fingerprint::of(&record)?.save(client),
+            None => Fingerprint::remove(client, record_id),
+        }
+    }
+
+    /// Отпечатки записей, созданных до появления поиска дубликатов.
+    /// Записи без отпечатка ищутся порциями по `batch`, возвращает количество новых отпечатков.
+    pub fn backfill(client: &Client, batch: i64) -> Result<usize, HubError> {
+        let fingerprints: Collection<Fingerprint> = Varys::get(client, Varys::Fingerprints);
+
+        let mut count = 0;
+        for category in Category::all() {
+            let records: Collection<Document> = Varys::get(client, category.into());
+            let mut last: Option<String> = None;
+
+            loop {
+                let mut filter = doc! {TRASH_FIELD: {"$exists": false}};
+                if let Some(last) = last.as_ref() {
+                    filter.insert("_id", doc! {"$gt": last});
+                }
+
+                let pipeline = vec![
+                    doc! {"$match": filter},
+                    doc! {"$sort": {"_id": 1}},
+                    doc! {"$lookup": {
+                        "from": fingerprints.name(),
+                        "localField": "_id",
+                        "foreignField": "_id",
+                        "as": "_fingerprint"
+                    }},
+                    doc! {"$match": {"_fingerprint": {"$size": 0}}},
+                    doc! {"$project": {"_fingerprint": 0}},
+                    doc! {"$limit": batch},
+                ];
+
+                let mut missing: Vec<Document> = Vec::new();
+                for record in records.aggregate(pipeline, None)? {
+                    missing.push(record?);
+                }
+
+                let record = match missing.last() {
+                    Some(record) => record,
+                    None => break,
+                };
+                last = Some(
+                    record
+                        .get_str("_id")
+                        .map_err(|err| err_internal!("Faild to read record id", err))?
+                        .to_string(),
+                );
+
+                for record in missing.iter() {
+                    Fingerprint::of(record)?.save(client)?;
+                    count += 1;
+                }
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Группы почти-дубликатов среди существующих записей.
+    /// Записи объединяются в группу, если схожесть хотя бы одной пары не ниже `threshold`.
+    pub fn clusters(client: &Client, threshold: f64) -> Result<Vec<DuplicateCluster>, HubError> {
+        let collection: Collection<Fingerprint> = Varys::get(client, Varys::Fingerprints);
+
+        let mut fingerprints: Vec<Fingerprint> = Vec::new();
+        for fingerprint in collection.find(None, None)? {
+            fingerprints.push(fingerprint?);
+        }
+
+        // Кандидаты в дубликаты — записи с общей полосой сигнатуры
+        let mut buckets: HashMap<(usize, i64), Vec<usize>> = HashMap::new();
+        for (i, fingerprint) in fingerprints.iter().enumerate() {
+            for (band, hash) in fingerprint.bands.iter().enumerate() {
+                buckets.entry((band, *hash)).or_default().push(i);
+            }
+        }
+
+        let mut parent: Vec<usize> = (0..fingerprints.len()).collect();
+        let mut best: HashMap<usize, f64> = HashMap::new();
+        let mut compared: HashSet<(usize, usize)> = HashSet::new();
+
+        fn root(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+
+        for bucket in buckets.values().filter(|b| b.len() > 1) {
+            for (n, a) in bucket.iter().enumerate() {
+                for b in bucket.iter().skip(n + 1) {
+                    if !compared.insert((*a, *b)) {
+                        continue;
+                    }
+
+                    let similarity = fingerprints[*a].similarity(&fingerprints[*b]);
+                    if similarity < threshold {
+                        continue;
+                    }
+
+                    let (ra, rb) = (root(&mut parent, *a), root(&mut parent, *b));
+                    let merged = best
+                        .remove(&ra)
+                        .into_iter()
+                        .chain(best.remove(&rb))
+                        .fold(similarity, f64::max);
+
+                    parent[rb] = ra;
+                    best.insert(ra, merged);
+                }
+            }
+        }
+
+        let mut groups: HashMap<usize, Vec<ClusterRecord>> = HashMap::new();
+        for (i, fingerprint) in fingerprints.iter().enumerate() {
+            let r = root(&mut parent, i);
+            if best.contains_key(&r) {
+                groups.entry(r).or_default().push(ClusterRecord {
+                    id: fingerprint.record_id.clone(),
+                    category: fingerprint.category.clone(),
+                });
+            }
+        }
+
+        let mut result: Vec<DuplicateCluster> = groups
+            .into_iter()
+            .map(|(r, records)| DuplicateCluster {
+                records,
+                similarity: best[&r],
+            })
+            .collect();
+
+        result.sort_by(|a, b| {
+            b.similarity
+                .total_cmp(&a.similarity)
+                .then(b.records.len().cmp(&a.records.len()))
+        });
+
+        Ok(result)
+    }
+}
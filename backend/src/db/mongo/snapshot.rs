@@ -0,0 +1,211 @@
+use bson::{doc, Bson, Document};
+use mongodb::{
+    bson::DateTime as MongoDateTime,
+    options::{AggregateOptions, FindOptions},
+    sync::{Client, Collection},
+};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+
+use crate::{
+    db::mongo::{
+        shrimp::{aggregation::Qilter, MODIFIED_FIELD},
+        varys::Varys,
+    },
+    err_internal,
+    errors::HubError,
+    model::{
+        account::Tariff,
+        shrimp::{Category, Status},
+        snapshot::{Manifest, ManifestEntry, Tombstone},
+    },
+};
+
+impl Manifest {
+    /// Запись снимка одобренных записей всех категорий в `out` построчно (JSON Lines).
+    /// Записи идут по категориям и идентификаторам, поэтому снимок с теми же
+    /// `since` и `until` дает те же контрольные суммы.
+    ///
+    /// Инкрементальный снимок берет записи, измененные после `since`: одобренные
+    /// попадают в него целиком, а ушедшие из открытого доступа — строкой `{"_tombstone": ...}`.
+    /// Последней строкой снимка идет сам манифест `{"_manifest": ...}`, он в контрольные суммы не входит.
+    pub fn write<W: Write>(
+        client: &Client,
+        since: Option<i64>,
+        until: i64,
+        mut out: W,
+    ) -> Result<Manifest, HubError> {
+        let modified = doc! {"$ifNull": [format!("${}", MODIFIED_FIELD), "$_header.timestamp"]};
+
+        let mut window = vec![doc! {"$lte": [&modified, until]}];
+        if let Some(since) = since {
+            window.push(doc! {"$gt": [&modified, since]});
+        }
+
+        let mut pipeline = vec![doc! {"$match": {"$expr": {"$and": window}}}];
+        if since.is_none() {
+            pipeline.push(Qilter::alive());
+            pipeline.push(Qilter::status(&Status::Approved));
+        }
+        pipeline.push(doc! {"$sort": {"_id": 1}});
+
+        let options = AggregateOptions::builder().allow_disk_use(true).build();
+
+        let mut total = Sha256::new();
+        let mut manifest = Manifest {
+            since,
+            until,
+            total: 0,
+            removed: 0,
+            sha256: String::new(),
+            categories: Vec::new(),
+        };
+
+        for category in Category::all() {
+            let collection: Collection<Document> = Varys::get(client, category.clone().into());
+            let cursor = collection
+                .aggregate(pipeline.clone(), options.clone())
+                .map_err(|err| err_internal!("Faild to take snapshot", err))?;
+
+            let mut hasher = Sha256::new();
+            let (mut count, mut removed) = (0, 0);
+
+            let mut emit = |line: &Value| -> Result<(), HubError> {
+                let mut line = serde_json::to_vec(line)
+                    .map_err(|err| err_internal!("Faild to serialize record", err))?;
+                line.push(b'\n');
+
+                hasher.update(&line);
+                total.update(&line);
+                out.write_all(&line)
+                    .map_err(|err| err_internal!("Faild to write snapshot", err))
+            };
+
+            for record in cursor {
+                let record = record?;
+
+                if is_public(&record) {
+                    emit(&Category::tariffing(record, &Tariff::Enterprice)?)?;
+                    count += 1;
+                } else {
+                    emit(&tombstone(&Tombstone::new(
+                        record.get_str("_id").unwrap_or_default(),
+                        category.clone(),
+                        modified_at(&record),
+                    )))?;
+                    removed += 1;
+                }
+            }
+
+            // Окончательно удаленные записи остаются только в отметках
+            if let Some(since) = since {
+                for buried in Tombstone::roll(client, &category, since, until)? {
+                    emit(&tombstone(&buried))?;
+                    removed += 1;
+                }
+            }
+
+            manifest.total += count;
+            manifest.removed += removed;
+            manifest.categories.push(ManifestEntry {
+                category,
+                count,
+                removed,
+                sha256: format!("{:x}", hasher.finalize()),
+            });
+        }
+
+        manifest.sha256 = format!("{:x}", total.finalize());
+
+        let mut line = serde_json::to_vec(&json!({ "_manifest": &manifest }))
+            .map_err(|err| err_internal!("Faild to serialize manifest", err))?;
+        line.push(b'\n');
+        out.write_all(&line)
+            .map_err(|err| err_internal!("Faild to write snapshot", err))?;
+
+        Ok(manifest)
+    }
+}
+
+impl Tombstone {
+    /// Отметка об окончательно удаленной записи
+    pub fn bury(client: &Client, category: &Category, record_id: &str) -> Result<(), HubError> {
+        let collection: Collection<Tombstone> = Varys::get(client, Varys::Tombstones);
+        let tombstone = Tombstone::new(
+            record_id,
+            category.clone(),
+            MongoDateTime::now().timestamp_millis(),
+        );
+
+        match collection.insert_one(tombstone, None) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err_internal!("Faild to save tombstone", err)),
+        }
+    }
+
+    /// Отметки записей категории, удаленных после `since` и не позже `until`
+    fn roll(
+        client: &Client,
+        category: &Category,
+        since: i64,
+        until: i64,
+    ) -> Result<Vec<Tombstone>, HubError> {
+        let collection: Collection<Tombstone> = Varys::get(client, Varys::Tombstones);
+        let filter = doc! {
+            "category": bson::to_bson(category)?,
+            "removed_at": {"$gt": since, "$lte": until}
+        };
+        let options = FindOptions::builder()
+            .sort(doc! {"removed_at": 1, "id": 1})
+            .build();
+
+        let mut result: Vec<Tombstone> = Vec::new();
+        for tombstone in collection.find(filter, options)? {
+            result.push(tombstone?);
+        }
+
+        Ok(result)
+    }
+}
+
+/// Строка снимка об ушедшей из открытого доступа записи
+fn tombstone(tombstone: &Tombstone) -> Value {
+    json!({ "_tombstone": tombstone })
+}
+
+/// Запись одобрена и не лежит в корзине
+fn is_public(record: &Document) -> bool {
+    let tail = match record.get_document("_meta-data") {
+        Ok(tail) => tail,
+        Err(_) => return false,
+    };
+
+    let status = tail
+        .get_document("status")
+        .ok()
+        .and_then(|status| status.get_str("kind").ok());
+
+    // Записи, созданные до появления модерации, считаются одобренными
+    let approved = match status {
+        Some(kind) => kind == Status::Approved.to_string(),
+        None => true,
+    };
+
+    approved && !tail.contains_key("trash")
+}
+
+/// Время последнего изменения записи, для неизменявшихся — время создания
+fn modified_at(record: &Document) -> i64 {
+    let head = match record.get_document("_header") {
+        Ok(head) => head,
+        Err(_) => return 0,
+    };
+
+    match head.get("modified").or_else(|| head.get("timestamp")) {
+        Some(Bson::Int64(value)) => *value,
+        Some(Bson::Int32(value)) => i64::from(*value),
+        Some(Bson::Double(value)) => *value as i64,
+        _ => 0,
+    }
+}
@@ -0,0 +1,231 @@
+use bson::{doc, Document};
+use mongodb::{
+    bson::DateTime as MongoDateTime,
+    options::UpdateOptions,
+    sync::{Client, Collection},
+};
+use std::collections::HashMap;
+
+use crate::{
+    db::mongo::{
+        shrimp::{
+            aggregation::{self, Qilter},
+            MODIFIED_FIELD,
+        },
+        varys::Varys,
+        Crud,
+    },
+    err_internal,
+    errors::HubError,
+    macro_crud,
+    model::{
+        shrimp::{Category, Status},
+        tag::{canonize, normalize, validate, Tag, TagInfo},
+    },
+};
+
+macro_crud!(Tag);
+impl Tag {
+    /// Поиск тега по каноническому имени или синониму
+    pub fn lookup(client: &Client, name: &str) -> Result<Option<Tag>, HubError> {
+        let collection: Collection<Tag> = Varys::get(client, Varys::Tags);
+        let filter = doc! {"$or": [{"_id": name}, {"aliases": name}]};
+
+        Ok(collection.find_one(filter, None)?)
+    }
+
+    /// Приведение тегов записи к каноническим именам после проверки.
+    /// Неизвестные теги остаются в нормализованном виде, в реестр их заносит `register`.
+    pub fn resolve(client: &Client, tags: &[String]) -> Result<Vec<String>, HubError> {
+        validate(tags)?;
+
+        let names: Vec<String> = tags.iter().map(|tag| normalize(tag)).collect();
+        canonize(tags, &Tag::known(client, &names)?)
+    }
+
+    /// Канонические имена для нормализованных имен `names` одним запросом к реестру.
+    /// Неизвестные имена в результат не попадают.
+    pub fn known(client: &Client, names: &[String]) -> Result<HashMap<String, String>, HubError> {
+        let mut result: HashMap<String, String> = HashMap::new();
+        if names.is_empty() {
+            return Ok(result);
+        }
+
+        let collection: Collection<Tag> = Varys::get(client, Varys::Tags);
+        let filter = doc! {"$or": [{"_id": {"$in": names}}, {"aliases": {"$in": names}}]};
+
+        for tag in collection.find(filter, None)? {
+            let tag = tag?;
+            for name in tag.names() {
+                if names.contains(&name) {
+                    result.insert(name, tag.name.clone());
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Регистрация тегов сохраненной записи в реестре; известные теги не меняются
+    pub fn register(client: &Client, names: &[String]) -> Result<(), HubError> {
+        let collection: Collection<Tag> = Varys::get(client, Varys::Tags);
+
+        for name in names {
+            collection
+                .update_one(
+                    doc! {"_id": name},
+                    doc! {"$setOnInsert": {"aliases": []}},
+                    UpdateOptions::builder().upsert(true).build(),
+                )
+                .map_err(|err| err_internal!("Faild to register tag", err))?;
+        }
+
+        Ok(())
+    }
+
+    /// Все имена, под которыми тег может встречаться в записях:
+    /// как он указан, в нормализованном виде, каноническое имя и синонимы.
+    pub fn names_of(client: &Client, tag: &str) -> Result<Vec<String>, HubError> {
+        let mut names = vec![tag.to_string(), normalize(tag)];
+
+        if let Some(found) = Tag::lookup(client, &normalize(tag))? {
+            names.extend(found.names());
+        }
+
+        names.sort();
+        names.dedup();
+
+        Ok(names)
+    }
+
+    /// Список тегов с количеством одобренных записей из `usage`, в которых они используются
+    pub fn list(
+        client: &Client,
+        prefix: Option<&str>,
+        usage: &HashMap<String, i64>,
+    ) -> Result<Vec<TagInfo>, HubError> {
+        let collection: Collection<Tag> = Varys::get(client, Varys::Tags);
+        let filter = prefix.map(|p| {
+            let pattern = format!("^{}", regex::escape(&normalize(p)));
+            doc! {"$or": [
+                {"_id": {"$regex": &pattern}},
+                {"aliases": {"$regex": &pattern}}
+            ]}
+        });
+
+        let mut result: Vec<TagInfo> = Vec::new();
+
+        for tag in collection.find(filter, None)? {
+            let tag = tag?;
+            let count = tag
+                .names()
+                .iter()
+                .map(|name| usage.get(name).cloned().unwrap_or_default())
+                .sum();
+
+            result.push(TagInfo::new(tag, count));
+        }
+
+        result.sort_by(|a, b| b.count.cmp(&a.count).then(a.name.cmp(&b.name)));
+        Ok(result)
+    }
+
+    /// Количество одобренных записей всех категорий для каждого имени тега
+    pub fn usage(client: &Client) -> Result<HashMap<String, i64>, HubError> {
+        let stages = vec![
+            Qilter::alive(),
+            Qilter::status(&Status::Approved),
+            doc! {"$project": {"tags": "$_meta-data.tags"}},
+        ];
+        let tail = vec![
+            doc! {"$unwind": "$tags"},
+            doc! {"$group": {"_id": "$tags", "count": {"$sum": 1}}},
+        ];
+
+        let mut result: HashMap<String, i64> = HashMap::new();
+        for record in aggregation::union(client, &Category::all(), stages, tail)? {
+            if let Ok(name) = record.get_str("_id") {
+                let count = record
+                    .get_i32("count")
+                    .map(i64::from)
+                    .or_else(|_| record.get_i64("count"))
+                    .unwrap_or_default();
+
+                result.insert(name.to_string(), count);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Слияние тега `source` с тегом `target`.
+    /// Имена `source` становятся синонимами `target`, а записи всех категорий
+    /// получают каноническое имя вместо старых.
+    pub fn merge(client: &Client, source: &str, target: &str) -> Result<Tag, HubError> {
+        let collection: Collection<Tag> = Varys::get(client, Varys::Tags);
+
+        let (source, target) = (normalize(source), normalize(target));
+        if source.is_empty() || target.is_empty() || source == target {
+            return Err(HubError::new_unprocessable("Tag is invalid", None));
+        }
+
+        let mut target = match Tag::lookup(client, &target)? {
+            Some(tag) => tag,
+            None => Tag::new(target),
+        };
+
+        let source_names = match Tag::lookup(client, &source)? {
+            Some(tag) if tag.name == target.name => {
+                return Err(HubError::new_unprocessable("Tags are already merged", None))
+            }
+            Some(tag) => {
+                collection.delete_one(doc! {"_id": &tag.name}, None)?;
+                tag.names()
+            }
+            None => vec![source],
+        };
+
+        for name in source_names.iter() {
+            if !target.aliases.contains(name) {
+                target.aliases.push(name.clone());
+            }
+        }
+
+        collection.update_one(
+            doc! {"_id": &target.name},
+            doc! {"$set": {"aliases": &target.aliases}},
+            UpdateOptions::builder().upsert(true).build(),
+        )?;
+
+        // Замена старых имен в записях на каноническое
+        let update: Vec<Document> = vec![doc! {
+            "$set": {
+                "_meta-data.tags": {
+                    "$setUnion": [
+                        {
+                            "$filter": {
+                                "input": "$_meta-data.tags",
+                                "cond": {"$not": [{"$in": ["$$this", &source_names]}]}
+                            }
+                        },
+                        [&target.name]
+                    ]
+                },
+                MODIFIED_FIELD: MongoDateTime::now().timestamp_millis()
+            }
+        }];
+
+        for category in Category::all() {
+            let records: Collection<Document> = Varys::get(client, category.into());
+            records
+                .update_many(
+                    doc! {"_meta-data.tags": {"$in": &source_names}},
+                    update.clone(),
+                    None,
+                )
+                .map_err(|err| err_internal!("Faild to retag records", err))?;
+        }
+
+        Ok(target)
+    }
+}
@@ -0,0 +1,234 @@
+use bson::{doc, Document};
+use mongodb::{
+    options::ReplaceOptions,
+    sync::{Client, Collection},
+};
+
+use crate::{
+    db::mongo::{shrimp::aggregation::Qilter, varys::Varys, Crud},
+    err_internal, err_not_found,
+    errors::HubError,
+    macro_crud,
+    model::{
+        shrimp::{Category, Status},
+        translation::{Sibling, Translation},
+    },
+};
+
+macro_crud!(Translation);
+impl Translation {
+    /// Группа, в которую входит запись
+    pub fn of(client: &Client, record_id: &str) -> Result<Option<Translation>, HubError> {
+        let collection: Collection<Translation> = Varys::get(client, Varys::Translations);
+
+        Ok(collection.find_one(doc! {"records": record_id}, None)?)
+    }
+
+    /// Связывание двух записей как переводов друг друга.
+    /// Если записи уже входят в группы, группы объединяются.
+    pub fn link(
+        client: &Client,
+        category: Category,
+        record_id: &str,
+        other_id: &str,
+    ) -> Result<Vec<Sibling>, HubError> {
+        if record_id == other_id {
+            return Err(HubError::new_unprocessable(
+                "Record cannot be a translation of itself",
+                None,
+            ));
+        }
+
+        let group = Translation::of(client, record_id)?;
+        let other = Translation::of(client, other_id)?;
+
+        let mut records: Vec<String> = Vec::new();
+        for (id, group) in [(record_id, group.as_ref()), (other_id, other.as_ref())] {
+            let ids = match group {
+                Some(group) => group.records.clone(),
+                None => vec![id.to_string()],
+            };
+
+            for id in ids {
+                if !records.contains(&id) {
+                    records.push(id);
+                }
+            }
+        }
+
+        let siblings = Translation::siblings(client, &category, &records)?;
+        if siblings.len() != records.len() {
+            return Err(err_not_found!("record"));
+        }
+        Translation::check(&siblings)?;
+
+        let collection: Collection<Translation> = Varys::get(client, Varys::Translations);
+
+        // Объединенная группа сохраняет идентификатор одной из прежних групп
+        let mut translation = match (group, other) {
+            (Some(group), Some(other)) if group.id != other.id => {
+                collection.delete_one(doc! {"_id": &other.id}, None)?;
+                group
+            }
+            (Some(group), _) | (None, Some(group)) => group,
+            (None, None) => Translation::new(category, Vec::new()),
+        };
+        translation.records = records;
+
+        collection
+            .replace_one(
+                doc! {"_id": &translation.id},
+                &translation,
+                ReplaceOptions::builder().upsert(true).build(),
+            )
+            .map_err(|err| err_internal!("Faild to link translation", err))?;
+
+        Ok(siblings)
+    }
+
+    /// Исключение записи из группы переводов
+    pub fn unlink(client: &Client, record_id: &str) -> Result<(), HubError> {
+        match Translation::of(client, record_id)? {
+            Some(group) => group.remove(client, record_id),
+            None => Err(err_not_found!("translation")),
+        }
+    }
+
+    /// Исключение удаленной записи из группы, если она в нее входила
+    pub fn forget(client: &Client, record_id: &str) -> Result<(), HubError> {
+        match Translation::of(client, record_id)? {
+            Some(group) => group.remove(client, record_id),
+            None => Ok(()),
+        }
+    }
+
+    /// Группа из одной записи больше не нужна и удаляется
+    fn remove(&self, client: &Client, record_id: &str) -> Result<(), HubError> {
+        let collection: Collection<Translation> = Varys::get(client, Varys::Translations);
+
+        if self.records.len() <= 2 {
+            collection.delete_one(doc! {"_id": &self.id}, None)?;
+        } else {
+            collection.update_one(
+                doc! {"_id": &self.id},
+                doc! {"$pull": {"records": record_id}},
+                None,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Одобренные переводы записи и их языки, сама запись в список не входит
+    pub fn translations(
+        client: &Client,
+        category: &Category,
+        record_id: &str,
+    ) -> Result<Vec<Sibling>, HubError> {
+        let mut result: Vec<Sibling> = Vec::new();
+        for record in Translation::approved(client, category, record_id)? {
+            result.push(Sibling {
+                id: record
+                    .get_str("_id")
+                    .map_err(|err| err_internal!("Faild to read record id", err))?
+                    .to_string(),
+                language: record
+                    .get_document("_meta-data")
+                    .and_then(|tail| tail.get_str("language"))
+                    .unwrap_or_default()
+                    .to_string(),
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// Идентификатор записи группы на языке `lang`: сама запись, если она на этом языке,
+    /// или ее перевод
+    pub fn resolve(
+        client: &Client,
+        category: &Category,
+        record_id: &str,
+        lang: &str,
+    ) -> Result<String, HubError> {
+        let records = match Translation::of(client, record_id)? {
+            Some(group) => group.records,
+            None => vec![record_id.to_string()],
+        };
+
+        let siblings = Translation::siblings(client, category, &records)?;
+        if !siblings.iter().any(|sibling| sibling.id == record_id) {
+            return Err(err_not_found!("record"));
+        }
+
+        match siblings
+            .into_iter()
+            .find(|sibling| sibling.language == lang)
+        {
+            Some(sibling) => Ok(sibling.id),
+            None => Err(HubError::new_not_found(
+                "Translation is not found",
+                Some(vec![format!("Record has no translation into '{}'", lang)]),
+            )),
+        }
+    }
+
+    /// Одобренные переводы записи целиком, для выдачи вместе с самой записью
+    pub fn approved(
+        client: &Client,
+        category: &Category,
+        record_id: &str,
+    ) -> Result<Vec<Document>, HubError> {
+        let mut records = match Translation::of(client, record_id)? {
+            Some(group) => group.records,
+            None => return Ok(Vec::new()),
+        };
+        records.retain(|id| id != record_id);
+
+        let collection: Collection<Document> = Varys::get(client, category.clone().into());
+        let pipeline = vec![
+            doc! {"$match": {"_id": {"$in": &records}}},
+            Qilter::alive(),
+            Qilter::status(&Status::Approved),
+            doc! {"$sort": {"_meta-data.language": 1}},
+        ];
+
+        let mut result: Vec<Document> = Vec::new();
+        for record in collection.aggregate(pipeline, None)? {
+            result.push(record?);
+        }
+
+        Ok(result)
+    }
+
+    /// Существующие записи из списка `records` и их языки
+    fn siblings(
+        client: &Client,
+        category: &Category,
+        records: &[String],
+    ) -> Result<Vec<Sibling>, HubError> {
+        let collection: Collection<Document> = Varys::get(client, category.clone().into());
+        let pipeline = vec![
+            doc! {"$match": {"_id": {"$in": records}}},
+            Qilter::alive(),
+            doc! {"$project": {"_id": 1, "language": "$_meta-data.language"}},
+        ];
+
+        let mut result: Vec<Sibling> = Vec::new();
+        for record in collection.aggregate(pipeline, None)? {
+            let record = record?;
+            result.push(Sibling {
+                id: record
+                    .get_str("_id")
+                    .map_err(|err| err_internal!("Faild to read record id", err))?
+                    .to_string(),
+                language: record.get_str("language").unwrap_or_default().to_string(),
+            });
+        }
+
+        // Порядок записей группы сохраняется
+        result.sort_by_key(|sibling| records.iter().position(|id| *id == sibling.id));
+
+        Ok(result)
+    }
+}
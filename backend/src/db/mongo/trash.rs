@@ -0,0 +1,262 @@
+use bson::{doc, Document};
+use chrono::{Duration, Utc};
+use mongodb::{
+    options::IndexOptions,
+    sync::{Client, Collection},
+    IndexModel,
+};
+use std::{thread, time};
+
+use crate::{
+    db::mongo::{
+        is_duplicate,
+        shrimp::{aggregation, MODIFIED_FIELD},
+        varys::Varys,
+    },
+    err_internal, err_not_found,
+    errors::HubError,
+    model::{
+        comment::Comment,
+        shrimp::{Category, Status},
+        similarity::Fingerprint,
+        snapshot::Tombstone,
+        translation::Translation,
+        trash::Trash,
+    },
+};
+
+/// Поле отметки об удалении в документе записи
+pub const TRASH_FIELD: &str = "_meta-data.trash";
+
+/// Как часто проверяется срок хранения записей в корзине
+const PURGE_INTERVAL: time::Duration = time::Duration::from_secs(60 * 60);
+
+impl Trash {
+    /// Фильтр записи, которая не лежит в корзине
+    pub fn alive(record_id: &str) -> Document {
+        doc! {"_id": record_id, TRASH_FIELD: {"$exists": false}}
+    }
+
+    /// Фильтр записи, которая лежит в корзине
+    fn trashed(record_id: &str) -> Document {
+        doc! {"_id": record_id, TRASH_FIELD: {"$exists": true}}
+    }
+
+    /// Перемещение записи в корзину.
+    /// Запись в корзине не участвует в поиске почти-дубликатов.
+    pub fn put(
+        client: &Client,
+        category: &Category,
+        record_id: &str,
+        deleted_by: &str,
+    ) -> Result<(), HubError> {
+        let collection: Collection<Document> = Varys::get(client, category.clone().into());
+        let trash = Trash::new(deleted_by);
+        let update = doc! {"$set": {
+            TRASH_FIELD: bson::to_bson(&trash)?,
+            MODIFIED_FIELD: trash.deleted_at
+        }};
+
+        match collection.update_one(Trash::alive(record_id), update, None) {
+            Ok(ur) if ur.matched_count > 0 => Fingerprint::remove(client, record_id),
+            Ok(_) => Err(err_not_found!(collection.name())),
+            Err(err) => Err(err_internal!("Faild to move record to trash", err)),
+        }
+    }
+
+    /// Возвращение записи из корзины
+    pub fn restore(client: &Client, category: &Category, record_id: &str) -> Result<(), HubError> {
+        let collection: Collection<Document> = Varys::get(client, category.clone().into());
+        let update = doc! {
+            "$unset": {TRASH_FIELD: ""},
+            "$set": {MODIFIED_FIELD: Utc::now().timestamp_millis()}
+        };
+
+        match collection.update_one(Trash::trashed(record_id), update, None) {
+            Ok(ur) if ur.matched_count > 0 => Fingerprint::refresh(client, category, record_id),
+            Ok(_) => Err(err_not_found!("trash")),
+            Err(err) if is_duplicate(&err) => Err(HubError::new_unprocessable(
+                "The same record was added while this one was in the trash",
+                None,
+            )),
+            Err(err) => Err(err_internal!("Faild to restore record", err)),
+        }
+    }
+
+    /// Окончательное удаление записи из корзины вместе со всем, что на нее ссылается
+    pub fn purge(client: &Client, category: &Category, record_id: &str) -> Result<(), HubError> {
+        let collection: Collection<Document> = Varys::get(client, category.clone().into());
+
+        match collection.find_one_and_delete(Trash::trashed(record_id), None) {
+            Ok(Some(record)) => {
+                // Отметка нужна только потребителям, которые могли видеть запись
+                if was_published(&record) {
+                    Tombstone::bury(client, category, record_id)?;
+                }
+
+                Trash::cascade(client, category, record_id)
+            }
+            Ok(None) => Err(err_not_found!("trash")),
+            Err(err) => Err(err_internal!("Faild to purge record", err)),
+        }
+    }
+
+    /// Окончательное удаление записей, срок хранения которых истек.
+    /// Возвращает количество действительно удаленных записей.
+    pub fn purge_expired(client: &Client, retention: Duration) -> Result<usize, HubError> {
+        let before = Trash::expired_before(Utc::now().timestamp_millis(), retention);
+        let field = format!("{}.deleted_at", TRASH_FIELD);
+
+        let mut count = 0;
+        for category in Category::all() {
+            let collection: Collection<Document> = Varys::get(client, category.clone().into());
+
+            let mut expired: Vec<String> = Vec::new();
+            for record in collection.find(doc! {&field: {"$lt": before}}, None)? {
+                if let Ok(id) = record?.get_str("_id") {
+                    expired.push(id.to_string());
+                }
+            }
+
+            // Ошибка с одной записью не должна задерживать в корзине остальные
+            for record_id in expired {
+                match Trash::purge(client, &category, &record_id) {
+                    Ok(_) => count += 1,
+                    Err(err) => error!("Record {} is not purged: {:?}", record_id, err),
+                }
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Фоновая очистка корзины от записей с истекшим сроком хранения
+    pub fn spawn_purge(client: Client) {
+        let retention = Trash::retention();
+
+        thread::spawn(move || loop {
+            if let Err(err) = Trash::purge_expired(&client, retention) {
+                error!("Trash is not purged: {:?}", err);
+            }
+
+            thread::sleep(PURGE_INTERVAL);
+        });
+    }
+
+    /// Уникальные индексы текста записей для баз, созданных до появления корзины:
+    /// в индекс добавляется время удаления, чтобы запись в корзине
+    /// не мешала добавить такую же заново.
+    pub fn migrate_indexes(client: &Client) -> Result<(), HubError> {
+        let deleted_at = format!("{}.deleted_at", TRASH_FIELD);
+
+        for category in Category::all() {
+            let collection: Collection<Document> = Varys::get(client, category.clone().into());
+
+            let mut keys = Document::new();
+            let mut partial = Document::new();
+            for field in unique_fields(&category) {
+                keys.insert(*field, 1);
+                partial.insert(*field, doc! {"$type": "string"});
+            }
+
+            for index in collection.list_indexes(None)? {
+                let index = index?;
+                let options = index.options.unwrap_or_default();
+
+                if options.unique == Some(true) && index.keys == keys {
+                    let name = options.name.unwrap_or_default();
+                    collection
+                        .drop_index(name, None)
+                        .map_err(|err| err_internal!("Faild to drop unique index", err))?;
+                }
+            }
+
+            keys.insert(&deleted_at, 1);
+            let options = IndexOptions::builder()
+                .unique(true)
+                .partial_filter_expression(partial)
+                .build();
+
+            collection
+                .create_index(
+                    IndexModel::builder().keys(keys).options(options).build(),
+                    None,
+                )
+                .map_err(|err| err_internal!("Faild to create unique index", err))?;
+        }
+
+        Ok(())
+    }
+
+    /// Записи в корзине, начиная с удаленных последними
+    pub fn list(
+        client: &Client,
+        categories: &[Category],
+        limit: i64,
+    ) -> Result<Vec<Document>, HubError> {
+        let deleted_at = format!("{}.deleted_at", TRASH_FIELD);
+
+        aggregation::union(
+            client,
+            categories,
+            vec![doc! {"$match": {TRASH_FIELD: {"$exists": true}}}],
+            vec![
+                doc! {"$sort": {deleted_at: -1, "_id": 1}},
+                doc! {"$limit": limit},
+            ],
+        )
+    }
+
+    /// Реакции, избранное, история и прочие данные удаленной записи
+    fn cascade(client: &Client, category: &Category, record_id: &str) -> Result<(), HubError> {
+        Fingerprint::remove(client, record_id)?;
+        Translation::forget(client, record_id)?;
+        Comment::forget(client, record_id)?;
+
+        let by_record = doc! {"record_id": record_id};
+        let by_category = doc! {"record_id": record_id, "category": bson::to_bson(category)?};
+
+        for (varys, filter) in [
+            (Varys::Reactions, by_record.clone()),
+            (Varys::Served, by_record),
+            (Varys::Revisions, by_category.clone()),
+            (Varys::FlagAudit, by_category),
+            (Varys::Favorite, doc! {"content_id": record_id}),
+        ] {
+            let collection: Collection<Document> = Varys::get(client, varys);
+            collection.delete_many(filter, None)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Поля записи, уникальные среди записей вне корзины
+fn unique_fields(category: &Category) -> &'static [&'static str] {
+    match category {
+        Category::Anecdote | Category::Joke => &["text"],
+        Category::Punch => &["setup", "punchline"],
+        Category::Story => &["title"],
+    }
+}
+
+/// Запись хоть раз была одобрена, то есть могла попасть в снимки.
+/// Записи, созданные до появления модерации, считаются одобренными.
+fn was_published(record: &Document) -> bool {
+    let published = record
+        .get_document("_header")
+        .map(|head| head.contains_key("published"))
+        .unwrap_or(false);
+
+    let status = record
+        .get_document("_meta-data")
+        .ok()
+        .and_then(|tail| tail.get_document("status").ok())
+        .and_then(|status| status.get_str("kind").ok());
+
+    published
+        || match status {
+            Some(kind) => kind == Status::Approved.to_string(),
+            None => true,
+        }
+}
@@ -10,11 +10,23 @@ pub enum Varys {
     ApiKeys,
     Notification,
     Favorite,
+    Revisions,
+    FlagAudit,
+    Tags,
+    Reactions,
+    Served,
+    Daily,
+    Fingerprints,
+    Translations,
+    Comments,
+    CommentReactions,
+    Follows,
+    Tombstones,
 
     Anecdote,
     Joke,
     Punch,
-    // Story,
+    Story,
 }
 
 impl Varys {
@@ -32,6 +44,10 @@ impl Varys {
                 .database(dotenv!("MONGO_DATABASE_NAME"))
                 .collection("joke"),
 
+            Varys::Story => client
+                .database(dotenv!("MONGO_DATABASE_NAME"))
+                .collection("story"),
+
             Varys::Users => client
                 .database(dotenv!("MONGO_DATABASE_NAME"))
                 .collection("users"),
@@ -51,6 +67,54 @@ impl Varys {
             Varys::Favorite => client
                 .database(dotenv!("MONGO_DATABASE_NAME"))
                 .collection("favorite"),
+
+            Varys::Revisions => client
+                .database(dotenv!("MONGO_DATABASE_NAME"))
+                .collection("revisions"),
+
+            Varys::FlagAudit => client
+                .database(dotenv!("MONGO_DATABASE_NAME"))
+                .collection("flag_audit"),
+
+            Varys::Tags => client
+                .database(dotenv!("MONGO_DATABASE_NAME"))
+                .collection("tags"),
+
+            Varys::Reactions => client
+                .database(dotenv!("MONGO_DATABASE_NAME"))
+                .collection("reactions"),
+
+            Varys::Served => client
+                .database(dotenv!("MONGO_DATABASE_NAME"))
+                .collection("served"),
+
+            Varys::Daily => client
+                .database(dotenv!("MONGO_DATABASE_NAME"))
+                .collection("daily"),
+
+            Varys::Fingerprints => client
+                .database(dotenv!("MONGO_DATABASE_NAME"))
+                .collection("fingerprints"),
+
+            Varys::Translations => client
+                .database(dotenv!("MONGO_DATABASE_NAME"))
+                .collection("translations"),
+
+            Varys::Comments => client
+                .database(dotenv!("MONGO_DATABASE_NAME"))
+                .collection("comments"),
+
+            Varys::CommentReactions => client
+                .database(dotenv!("MONGO_DATABASE_NAME"))
+                .collection("comment_reactions"),
+
+            Varys::Follows => client
+                .database(dotenv!("MONGO_DATABASE_NAME"))
+                .collection("follows"),
+
+            Varys::Tombstones => client
+                .database(dotenv!("MONGO_DATABASE_NAME"))
+                .collection("tombstones"),
         }
     }
 }
@@ -61,6 +125,7 @@ impl From<Category> for Varys {
             Category::Anecdote => Varys::Anecdote,
             Category::Joke => Varys::Joke,
             Category::Punch => Varys::Punch,
+            Category::Story => Varys::Story,
         }
     }
 }
@@ -107,6 +107,7 @@ pub enum ErrorKind<'a> {
     Unprocessable(&'a str, Option<Vec<String>>),
     NotFound(&'a str, Option<Vec<String>>),
     Forbidden(&'a str, Option<Vec<String>>),
+    TooManyRequests(&'a str, Option<Vec<String>>),
 
     Unauthorized(UnauthorizedErrorKind<'a>),
 }
@@ -125,6 +126,10 @@ pub struct HubError {
 
     #[serde(skip_serializing)]
     status: Status,
+
+    /// Дополнительные заголовки ответа, например `Retry-After`
+    #[serde(skip_serializing)]
+    headers: Vec<(&'static str, String)>,
 }
 
 impl<'a> HubError {
@@ -134,12 +139,14 @@ impl<'a> HubError {
                 error: err.to_string(),
                 details: d,
                 status,
+                headers: Vec::new(),
             },
 
             None => HubError {
                 error: err.to_string(),
                 details: Vec::new(),
                 status,
+                headers: Vec::new(),
             },
         }
     }
@@ -149,6 +156,7 @@ impl<'a> HubError {
             ErrorKind::Internal(err, d) => HubError::create(err, d, Status::InternalServerError),
             ErrorKind::NotFound(err, d) => HubError::create(err, d, Status::NotFound),
             ErrorKind::Forbidden(err, d) => HubError::create(err, d, Status::Forbidden),
+            ErrorKind::TooManyRequests(err, d) => HubError::create(err, d, Status::TooManyRequests),
             ErrorKind::Unprocessable(err, d) => {
                 HubError::create(err, d, Status::UnprocessableEntity)
             }
@@ -190,6 +198,11 @@ impl<'a> HubError {
         HubError::new(ErrorKind::Forbidden(err, d))
     }
 
+    #[allow(dead_code)]
+    pub(crate) fn new_too_many_requests(err: &str, d: Option<Vec<String>>) -> HubError {
+        HubError::new(ErrorKind::TooManyRequests(err, d))
+    }
+
     #[allow(dead_code)]
     pub(crate) fn new_unauthorized(err: &str, d: Option<Vec<String>>) -> HubError {
         let kind = ErrorKind::Unauthorized(UnauthorizedErrorKind::Generic(err));
@@ -206,6 +219,12 @@ impl<'a> HubError {
         return error;
     }
 
+    // Добавить заголовок к ответу с ошибкой
+    pub fn with_header(mut self, name: &'static str, value: String) -> HubError {
+        self.headers.push((name, value));
+        self
+    }
+
     // Добавить новый элемент в список деталей
     pub fn add(&mut self, d: String) -> HubError {
         self.details.push(d);
@@ -216,10 +235,14 @@ impl<'a> HubError {
 impl<'a> RocketResponder<'a, 'static> for HubError {
     fn respond_to(self, req: &'a rocket::Request<'_>) -> rocket::response::Result<'static> {
         match Json(&self).respond_to(req) {
-            Ok(resp) => RocketResponse::build_from(resp)
-                .status(self.status)
-                .header(ContentType::JSON)
-                .ok(),
+            Ok(resp) => {
+                let mut builder = RocketResponse::build_from(resp);
+                for (name, value) in self.headers {
+                    builder.raw_header(name, value);
+                }
+
+                builder.status(self.status).header(ContentType::JSON).ok()
+            }
             Err(s) => RocketResponse::build()
                 .status(s)
                 .header(ContentType::JSON)
@@ -55,6 +55,28 @@ impl Default for Tariff {
     }
 }
 
+impl Tariff {
+    /// Максимальное количество записей на одной странице списка
+    pub fn page_limit(&self) -> i64 {
+        match self {
+            Tariff::Free => 10,
+            Tariff::Basic => 25,
+            Tariff::Standart => 50,
+            Tariff::Enterprice => 100,
+        }
+    }
+
+    /// Количество текстов, язык которых можно определить за минуту
+    pub fn detect_rate(&self) -> u32 {
+        match self {
+            Tariff::Free => 30,
+            Tariff::Basic => 120,
+            Tariff::Standart => 600,
+            Tariff::Enterprice => 3000,
+        }
+    }
+}
+
 impl fmt::Display for Tariff {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}", self)
@@ -111,6 +133,16 @@ pub struct NewUser {
     pub password: String,
 }
 
+/// Тело запроса на изменение публичного профиля
+#[derive(Clone, Validate, Deserialize)]
+pub struct EditProfile {
+    #[validate(length(min = 1, max = 64, message = "Lenght is invalid"))]
+    pub display_name: Option<String>,
+
+    #[validate(length(max = 500, message = "Lenght is invalid"))]
+    pub bio: Option<String>,
+}
+
 #[derive(Clone, Validate, Deserialize)]
 pub struct ChangePassword {
     #[validate(
@@ -181,6 +213,13 @@ pub struct User {
 
     pub theme: Theme,
 
+    /// Публичное имя автора, которое видно в его профиле
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bio: Option<String>,
+
     pub created_at: MongoDateTime,
     pub updated_at: MongoDateTime,
 }
@@ -194,6 +233,8 @@ impl From<NewUser> for User {
             tariff: Tariff::Free,
             hash: nu.password,
             theme: Theme::default(),
+            display_name: None,
+            bio: None,
             created_at: MongoDateTime::now(),
             updated_at: MongoDateTime::now(),
         }
@@ -233,6 +274,8 @@ pub struct Account {
     pub username: String,
     pub tariff: Tariff,
     pub theme: Theme,
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
     pub api_keys: Vec<ApiKeyInfo>,
     pub sessions: Vec<Sinfo>,
     pub created_at: String,
@@ -268,6 +311,8 @@ impl Account {
             username: user.username,
             tariff: user.tariff,
             theme: user.theme,
+            display_name: user.display_name,
+            bio: user.bio,
             api_keys: Vec::convert(api_keys),
             sessions: Sinfo::vec_convert(sessions),
             created_at: user.created_at.to_rfc3339_string(),
@@ -518,6 +563,10 @@ pub mod security {
             pub fn get_tariff(&self) -> Tariff {
                 self.tariff.clone()
             }
+
+            pub fn get_owner(&self) -> &str {
+                &self.owner
+            }
         }
 
         impl From<NewApiKey> for ApiKey {
@@ -635,6 +684,11 @@ pub mod security {
         pub fn get_tariff(&self) -> Tariff {
             return self.tariff.clone();
         }
+
+        /// Управлять записью может ее автор, либо пользователь с уровнем не ниже Master
+        pub fn can_manage(&self, author: &str) -> bool {
+            self.username == author || self.level != Level::Padawan
+        }
     }
 
     /// Полезная нагрузка токена обновления
@@ -758,6 +812,45 @@ pub mod security {
         }
     }
 
+    /// Охранник потребителя контента.
+    /// Определяет, от чьего имени выполняется действие: пользователь из токена доступа
+    /// или владелец Api-Key. Если нет ни того, ни другого, доступ запрещен.
+    pub struct IdentityGuard(pub String);
+
+    #[rocket::async_trait]
+    impl<'r> FromRequest<'r> for IdentityGuard {
+        type Error = HubError;
+
+        async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+            if request.headers().get_one("Authorization").is_some() {
+                return match request.guard::<AuthGuard>().await {
+                    Outcome::Success(auth) => {
+                        Outcome::Success(IdentityGuard(auth.0.get_username()))
+                    }
+                    Outcome::Failure(failure) => Outcome::Failure(failure),
+                    Outcome::Forward(forward) => Outcome::Forward(forward),
+                };
+            }
+
+            match request.guard::<ApiKeyGuard>().await {
+                Outcome::Success(ApiKeyGuard(Some(key))) => {
+                    Outcome::Success(IdentityGuard(key.get_owner().to_string()))
+                }
+
+                Outcome::Success(ApiKeyGuard(None)) => Outcome::Failure((
+                    Status::Unauthorized,
+                    err_unauthorized!(
+                        "Api-Key is not found",
+                        "Api-Key must be set in the header with the name `Api-Key`"
+                    ),
+                )),
+
+                Outcome::Failure(failure) => Outcome::Failure(failure),
+                Outcome::Forward(forward) => Outcome::Forward(forward),
+            }
+        }
+    }
+
     /// Индивидуальный охранник, необходим когда авторизация необязательна.
     /// Если в заголовке нет токена доступа, тогда по умолчанию применяется тариф Free
     /// Если токен присутствует, используется тариф содержащийся в токене
@@ -801,10 +894,28 @@ pub mod security {
                 Cow::Borrowed("delete_joke"),
                 Cow::Borrowed("delete_punch"),
                 Cow::Borrowed("delete_anecdote"),
+                Cow::Borrowed("delete_story"),
+                Cow::Borrowed("flag_set"),
+                Cow::Borrowed("flag_clear"),
+                Cow::Borrowed("flag_audit"),
+                Cow::Borrowed("queue"),
+                Cow::Borrowed("mislabeled"),
+                Cow::Borrowed("approve"),
+                Cow::Borrowed("reject"),
+                Cow::Borrowed("duplicates"),
+                Cow::Borrowed("lexicon_reload"),
+                Cow::Borrowed("trash_list"),
+                Cow::Borrowed("trash_restore"),
+                Cow::Borrowed("tag_merge"),
+                Cow::Borrowed("import_records"),
+                Cow::Borrowed("export_fortune"),
             ];
             // Маршруты которые защищены уровнем Sith
-            let sith_level: Vec<Cow<str>> =
-                [vec![Cow::Borrowed("privilege")], master_level.clone()].concat(); // маршруты доступные Master доступны и Sith
+            let sith_level: Vec<Cow<str>> = [
+                vec![Cow::Borrowed("privilege"), Cow::Borrowed("trash_purge")],
+                master_level.clone(),
+            ]
+            .concat(); // маршруты доступные Master доступны и Sith
 
             match user_level {
                 Level::Padawan => false,
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
-use crate::model::shrimp::{default_tags, Paws, Category};
+use crate::model::shrimp::{default_tags, Paws, Category, Flag};
 use shrimplib::Paws;
 
 #[derive(Clone, Serialize, Deserialize, Paws)]
@@ -17,6 +17,14 @@ pub struct NewAnecdote {
 
     #[serde(default = "default_tags")]
     pub tags: Vec<String>,
+
+    /// Флаги деликатности, которые автор выставляет сам
+    #[serde(default)]
+    pub flags: Vec<Flag>,
+
+    /// Язык записи (код ISO 639-1), если автор указывает его сам
+    #[serde(default)]
+    pub lang: Option<String>,
 }
 
 impl From<NewAnecdote> for Anecdote {
@@ -47,6 +55,8 @@ mod tests {
         let na = super::NewAnecdote {
             text: text.to_string(),
             tags: vec![],
+            flags: vec![],
+            lang: None,
         };
 
         match na.validate() {
@@ -0,0 +1,126 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::{
+    account::User,
+    shrimp::{Category, ReactionKind},
+};
+
+/// Публичный профиль автора.
+/// Собирается только из открытых полей пользователя: уровень доступа, тариф и хеш пароля в него не попадают.
+#[derive(Serialize)]
+pub struct AuthorProfile {
+    username: String,
+    display_name: Option<String>,
+    bio: Option<String>,
+    joined_at: String,
+    stats: AuthorStats,
+}
+
+impl AuthorProfile {
+    pub fn new(user: User, stats: AuthorStats) -> Self {
+        AuthorProfile {
+            username: user.username,
+            display_name: user.display_name,
+            bio: user.bio,
+            joined_at: user.created_at.to_rfc3339_string(),
+            stats,
+        }
+    }
+}
+
+/// Статистика автора по одобренным записям
+#[derive(Serialize, Debug)]
+pub struct AuthorStats {
+    /// Количество записей в каждой категории
+    pub records: HashMap<Category, usize>,
+
+    /// Сумма просмотров всех записей
+    pub views: usize,
+
+    pub reactions: HashMap<ReactionKind, usize>,
+}
+
+impl Default for AuthorStats {
+    fn default() -> Self {
+        AuthorStats {
+            records: Category::all().into_iter().map(|c| (c, 0)).collect(),
+            views: 0,
+            reactions: ReactionKind::all().into_iter().map(|k| (k, 0)).collect(),
+        }
+    }
+}
+
+impl AuthorStats {
+    /// Учет итогов одной категории
+    pub fn add(
+        &mut self,
+        category: Category,
+        records: usize,
+        views: usize,
+        reactions: HashMap<ReactionKind, usize>,
+    ) {
+        *self.records.entry(category).or_default() += records;
+        self.views += views;
+
+        for (kind, count) in reactions {
+            *self.reactions.entry(kind).or_default() += count;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{AuthorProfile, AuthorStats};
+    use crate::model::{
+        account::{NewUser, User},
+        shrimp::{Category, ReactionKind},
+    };
+
+    #[test]
+    fn profile_is_public() {
+        let mut user = User::from(NewUser {
+            username: String::from("author"),
+            password: String::from("password"),
+        });
+        user.bio = Some(String::from("bio"));
+
+        let profile =
+            serde_json::to_value(AuthorProfile::new(user, AuthorStats::default())).unwrap();
+
+        assert_eq!(profile["username"], "author");
+        assert_eq!(profile["bio"], "bio");
+        assert!(profile["display_name"].is_null());
+
+        for field in ["hash", "level", "tariff", "theme", "id", "_id"] {
+            assert!(profile.get(field).is_none(), "{} leaked", field);
+        }
+    }
+
+    #[test]
+    fn stats_sum() {
+        let mut stats = AuthorStats::default();
+
+        stats.add(
+            Category::Joke,
+            2,
+            10,
+            HashMap::from([(ReactionKind::Fire, 3), (ReactionKind::Laughing, 1)]),
+        );
+        stats.add(
+            Category::Story,
+            1,
+            5,
+            HashMap::from([(ReactionKind::Fire, 2)]),
+        );
+
+        assert_eq!(stats.records[&Category::Joke], 2);
+        assert_eq!(stats.records[&Category::Story], 1);
+        assert_eq!(stats.records[&Category::Punch], 0);
+        assert_eq!(stats.views, 15);
+        assert_eq!(stats.reactions[&ReactionKind::Fire], 5);
+        assert_eq!(stats.reactions[&ReactionKind::ThumbsDown], 0);
+    }
+}
@@ -0,0 +1,223 @@
+use bson::oid::ObjectId;
+use mongodb::bson::DateTime as MongoDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::shrimp::{Category, ReactionKind};
+use crate::errors::HubError;
+
+/// Сколько минут после публикации автор может изменить комментарий
+pub const EDIT_WINDOW_MINUTES: i64 = 15;
+
+/// Комментарий к записи.
+/// Ответ на другой комментарий хранит его в `parent_id`, а первый комментарий ветки — в `thread_id`.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Comment {
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub record_id: String,
+    pub category: Category,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    pub thread_id: String,
+
+    pub author: String,
+    pub text: String,
+
+    #[serde(default)]
+    pub reactions: HashMap<ReactionKind, usize>,
+
+    /// Количество прямых ответов на комментарий
+    #[serde(default)]
+    pub replies: usize,
+
+    /// Удаленный комментарий, на который уже ответили, остается в ветке без текста
+    #[serde(default)]
+    pub deleted: bool,
+
+    pub created_at: MongoDateTime,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edited_at: Option<MongoDateTime>,
+}
+
+impl Comment {
+    pub fn new(record_id: &str, category: Category, author: &str, text: &str) -> Self {
+        let id = Uuid::new_v4().to_string();
+
+        Comment {
+            thread_id: id.clone(),
+            id,
+            record_id: record_id.to_string(),
+            category,
+            parent_id: None,
+            author: author.to_string(),
+            text: text.to_string(),
+            reactions: HashMap::new(),
+            replies: 0,
+            deleted: false,
+            created_at: MongoDateTime::now(),
+            edited_at: None,
+        }
+    }
+
+    /// Ответ на комментарий `parent` попадает в его ветку
+    pub fn reply_to(mut self, parent: &Comment) -> Self {
+        self.parent_id = Some(parent.id.clone());
+        self.thread_id = parent.thread_id.clone();
+        self
+    }
+
+    /// Курсор следующей страницы после этого комментария: `<время публикации>_<id>`
+    pub fn cursor(&self) -> String {
+        format!("{}_{}", self.created_at.timestamp_millis(), self.id)
+    }
+
+    pub fn parse_cursor(cursor: &str) -> Result<(i64, String), HubError> {
+        cursor
+            .split_once('_')
+            .and_then(|(at, id)| Some((at.parse::<i64>().ok()?, id.to_string())))
+            .ok_or_else(|| HubError::new_unprocessable("Cursor is invalid", None))
+    }
+
+    /// Изменить комментарий можно только в течение `EDIT_WINDOW_MINUTES` после публикации.
+    /// `now` — текущее время в миллисекундах.
+    pub fn check_edit_window(&self, now: i64) -> Result<(), HubError> {
+        let window = EDIT_WINDOW_MINUTES * 60 * 1000;
+
+        match now - self.created_at.timestamp_millis() <= window {
+            true => Ok(()),
+            false => Err(HubError::new_forbidden(
+                "Comment can no longer be edited",
+                Some(vec![format!(
+                    "Comments can be edited within {} minutes after publication",
+                    EDIT_WINDOW_MINUTES
+                )]),
+            )),
+        }
+    }
+}
+
+/// Тело запроса на публикацию комментария
+#[derive(Clone, Deserialize, Validate, Debug)]
+pub struct NewComment {
+    #[validate(length(min = 1, max = 2000, message = "Lenght is invalid"))]
+    pub text: String,
+
+    /// Комментарий, на который дается ответ
+    #[serde(default)]
+    pub parent_id: Option<String>,
+}
+
+/// Тело запроса на изменение комментария
+#[derive(Clone, Deserialize, Validate, Debug)]
+pub struct EditComment {
+    #[validate(length(min = 1, max = 2000, message = "Lenght is invalid"))]
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CommentInfo {
+    id: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_id: Option<String>,
+    author: Option<String>,
+    text: Option<String>,
+    reactions: HashMap<ReactionKind, usize>,
+    replies: usize,
+    created_at: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    edited_at: Option<String>,
+}
+
+impl From<Comment> for CommentInfo {
+    fn from(c: Comment) -> Self {
+        // У удаленного комментария остается только место в ветке
+        let (author, text) = match c.deleted {
+            true => (None, None),
+            false => (Some(c.author), Some(c.text)),
+        };
+
+        Self {
+            id: c.id,
+            parent_id: c.parent_id,
+            author,
+            text,
+            reactions: c.reactions,
+            replies: c.replies,
+            created_at: c.created_at.to_rfc3339_string(),
+            edited_at: c.edited_at.map(|at| at.to_rfc3339_string()),
+        }
+    }
+}
+
+/// Реакция на комментарий.
+/// Каждый потребитель держит не больше одной реакции на комментарий.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CommentReaction {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub comment_id: String,
+    pub record_id: String,
+    pub identity: String,
+    pub kind: ReactionKind,
+    pub created_at: MongoDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use mongodb::bson::DateTime as MongoDateTime;
+    use test_case::test_case;
+
+    use super::{Comment, CommentInfo, EDIT_WINDOW_MINUTES};
+    use crate::model::shrimp::Category;
+
+    #[test_case(0, true ; "just_published")]
+    #[test_case(EDIT_WINDOW_MINUTES * 60 * 1000, true ; "window_end")]
+    #[test_case(EDIT_WINDOW_MINUTES * 60 * 1000 + 1, false ; "window_expired")]
+    fn edit_window(elapsed: i64, expected: bool) {
+        let mut comment = Comment::new("record", Category::Joke, "author", "text");
+        comment.created_at = MongoDateTime::from_millis(1_000_000);
+
+        assert_eq!(
+            comment.check_edit_window(1_000_000 + elapsed).is_ok(),
+            expected
+        );
+    }
+
+    #[test]
+    fn reply_thread() {
+        let root = Comment::new("record", Category::Joke, "author", "root");
+        let reply = Comment::new("record", Category::Joke, "author", "reply").reply_to(&root);
+        let nested = Comment::new("record", Category::Joke, "author", "nested").reply_to(&reply);
+
+        assert_eq!(root.thread_id, root.id);
+        assert_eq!(reply.parent_id.as_ref(), Some(&root.id));
+        assert_eq!(nested.parent_id.as_ref(), Some(&reply.id));
+        assert_eq!(nested.thread_id, root.id);
+    }
+
+    #[test_case("1000_abc", Some((1000, "abc")) ; "valid")]
+    #[test_case("abc", None ; "no_separator")]
+    #[test_case("x_abc", None ; "invalid_time")]
+    fn cursor(cursor: &str, expected: Option<(i64, &str)>) {
+        let result = Comment::parse_cursor(cursor).ok();
+
+        assert_eq!(result.as_ref().map(|(at, id)| (*at, id.as_str())), expected);
+    }
+
+    #[test]
+    fn deleted_comment_info() {
+        let mut comment = Comment::new("record", Category::Joke, "author", "text");
+        comment.deleted = true;
+
+        let info = serde_json::to_value(CommentInfo::from(comment)).unwrap();
+        assert!(info["text"].is_null());
+        assert!(info["author"].is_null());
+    }
+}
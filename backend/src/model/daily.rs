@@ -0,0 +1,126 @@
+use chrono::{FixedOffset, NaiveDate, NaiveDateTime};
+use mongodb::bson::DateTime as MongoDateTime;
+use serde::{Deserialize, Serialize};
+
+use super::shrimp::Category;
+use crate::{err_internal, errors::HubError};
+
+/// Выбор записи дня.
+/// Идентификатор состоит из даты и ключа фильтра, поэтому для одинаковых
+/// фильтров в один календарный день все получают одну и ту же запись.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Daily {
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub date: String,
+    pub key: String,
+    pub record_id: String,
+    pub category: Category,
+    pub created_at: MongoDateTime,
+}
+
+impl Daily {
+    /// Период неповторения по умолчанию, в днях
+    pub const DEFAULT_PERIOD: u32 = 30;
+
+    /// Максимальный период неповторения, в днях
+    pub const MAX_PERIOD: u32 = 365;
+
+    pub fn new(date: &NaiveDate, key: &str, record_id: &str, category: Category) -> Self {
+        Daily {
+            id: Daily::id(date, key),
+            date: date.to_string(),
+            key: key.to_string(),
+            record_id: record_id.to_string(),
+            category,
+            created_at: MongoDateTime::now(),
+        }
+    }
+
+    pub fn id(date: &NaiveDate, key: &str) -> String {
+        format!("{}|{}", date, key)
+    }
+}
+
+/// Разбор часового пояса в виде смещения от UTC: `UTC`, `Z`, `+03:00`, `+0300`, `-5`.
+/// Если пояс не указан, используется UTC.
+pub fn parse_offset(tz: Option<&str>) -> Result<FixedOffset, HubError> {
+    let invalid = || HubError::new_unprocessable("Timezone is invalid", None);
+
+    let tz = match tz.map(str::trim) {
+        None | Some("") | Some("UTC") | Some("utc") | Some("Z") => {
+            return FixedOffset::east_opt(0).ok_or_else(invalid)
+        }
+        Some(tz) => tz,
+    };
+
+    let (sign, rest) = match tz.split_at(1) {
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return Err(invalid()),
+    };
+
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((h, m)) => (h, m),
+        None if rest.len() == 4 => rest.split_at(2),
+        None => (rest, "0"),
+    };
+
+    let hours: i32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+
+    if !(0..=14).contains(&hours) || !(0..60).contains(&minutes) {
+        return Err(invalid());
+    }
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).ok_or_else(invalid)
+}
+
+/// Сколько секунд осталось до полуночи после `now`, но не меньше секунды
+pub fn seconds_to_midnight(now: &NaiveDateTime) -> Result<i64, HubError> {
+    let midnight = now
+        .date()
+        .succ_opt()
+        .and_then(|next| next.and_hms_opt(0, 0, 0))
+        .ok_or_else(|| err_internal!("Faild to find the end of the day"))?;
+
+    Ok((midnight - *now).num_seconds().max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use test_case::test_case;
+
+    #[test_case(2022, 6, 15, 0, 0, 0, Some(86_400) ; "start_of_day")]
+    #[test_case(2022, 6, 15, 23, 59, 30, Some(30) ; "before_midnight")]
+    #[test_case(2022, 12, 31, 12, 0, 0, Some(43_200) ; "end_of_year")]
+    #[test_case(262_143, 12, 31, 12, 0, 0, None ; "last_representable_day")]
+    fn seconds_to_midnight(
+        year: i32,
+        month: u32,
+        day: u32,
+        hour: u32,
+        min: u32,
+        sec: u32,
+        expected: Option<i64>,
+    ) {
+        let now = NaiveDate::from_ymd_opt(year, month, day)
+            .and_then(|date| date.and_hms_opt(hour, min, sec))
+            .expect("valid date");
+
+        assert_eq!(super::seconds_to_midnight(&now).ok(), expected);
+    }
+
+    #[test_case(None, Some(0) ; "default_utc")]
+    #[test_case(Some("UTC"), Some(0) ; "utc")]
+    #[test_case(Some("+03:00"), Some(3 * 3600) ; "positive_with_colon")]
+    #[test_case(Some("-0530"), Some(-(5 * 3600 + 30 * 60)) ; "negative_compact")]
+    #[test_case(Some("+5"), Some(5 * 3600) ; "hours_only")]
+    #[test_case(Some("+15:00"), None ; "out_of_range")]
+    #[test_case(Some("Europe/Moscow"), None ; "named_zone")]
+    fn parse_offset(tz: Option<&str>, expected: Option<i32>) {
+        let result = super::parse_offset(tz).ok().map(|o| o.local_minus_utc());
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,79 @@
+use bson::oid::ObjectId;
+use mongodb::bson::DateTime as MongoDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::HubError;
+
+/// Подписка пользователя `follower` на автора `author`
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Follow {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub follower: String,
+    pub author: String,
+    pub created_at: MongoDateTime,
+}
+
+impl Follow {
+    pub fn new(follower: &str, author: &str) -> Result<Self, HubError> {
+        if follower == author {
+            return Err(HubError::new_unprocessable(
+                "You can't follow yourself",
+                None,
+            ));
+        }
+
+        Ok(Follow {
+            id: ObjectId::new(),
+            follower: follower.to_string(),
+            author: author.to_string(),
+            created_at: MongoDateTime::now(),
+        })
+    }
+}
+
+/// Элемент списка подписчиков или подписок
+#[derive(Serialize, Deserialize)]
+pub struct FollowInfo {
+    username: String,
+    followed_at: String,
+}
+
+impl FollowInfo {
+    /// Подписчик автора
+    pub fn follower(f: Follow) -> Self {
+        FollowInfo {
+            username: f.follower,
+            followed_at: f.created_at.to_rfc3339_string(),
+        }
+    }
+
+    /// Автор, на которого подписан пользователь
+    pub fn author(f: Follow) -> Self {
+        FollowInfo {
+            username: f.author,
+            followed_at: f.created_at.to_rfc3339_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::{Follow, FollowInfo};
+
+    #[test_case("reader", "author", true ; "other_author")]
+    #[test_case("author", "author", false ; "yourself")]
+    fn follow(follower: &str, author: &str, expected: bool) {
+        assert_eq!(Follow::new(follower, author).is_ok(), expected);
+    }
+
+    #[test]
+    fn follow_info() {
+        let follow = Follow::new("reader", "author").unwrap();
+
+        assert_eq!(FollowInfo::follower(follow.clone()).username, "reader");
+        assert_eq!(FollowInfo::author(follow).username, "author");
+    }
+}
@@ -0,0 +1,174 @@
+use super::{
+    anecdote::NewAnecdote,
+    import::{ImportRow, NewShrimp},
+    joke::NewJoke,
+    shrimp::{default_tags, Category},
+};
+use crate::errors::HubError;
+
+/// Разделитель записей в файлах fortune(6)
+pub const DELIMITER: char = '%';
+
+/// Версия формата индекса strfile(8)
+const STRFILE_VERSION: u32 = 2;
+
+/// Разбор файла fortune: записи отделяются строкой из одного `%`.
+/// Все записи получают категорию `category` и теги `tags`.
+pub fn parse(data: &str, category: &Category, tags: &[String]) -> Result<Vec<ImportRow>, HubError> {
+    let tags = if tags.is_empty() {
+        default_tags()
+    } else {
+        tags.to_vec()
+    };
+
+    let mut cookies: Vec<String> = Vec::new();
+    let mut cookie = String::new();
+
+    for line in data.lines() {
+        if line == DELIMITER.to_string() {
+            cookies.push(std::mem::take(&mut cookie));
+        } else {
+            cookie.push_str(line);
+            cookie.push('\n');
+        }
+    }
+    cookies.push(cookie);
+
+    let mut rows: Vec<ImportRow> = Vec::new();
+    for text in cookies.into_iter().map(|c| c.trim_end().to_string()) {
+        if text.trim().is_empty() {
+            continue;
+        }
+
+        let shrimp = match category {
+            Category::Joke => NewShrimp::Joke(NewJoke {
+                text,
+                tags: tags.clone(),
+                flags: Vec::new(),
+                lang: None,
+            }),
+            Category::Anecdote => NewShrimp::Anecdote(NewAnecdote {
+                text,
+                tags: tags.clone(),
+                flags: Vec::new(),
+                lang: None,
+            }),
+            _ => {
+                return Err(HubError::new_unprocessable(
+                    "Category is not supported by fortune files",
+                    None,
+                ))
+            }
+        };
+
+        rows.push((rows.len() + 1, Ok(shrimp)));
+    }
+
+    Ok(rows)
+}
+
+/// Файл выгрузки fortune: сами записи или индекс strfile к ним
+#[derive(Clone, PartialEq, FromFormField, Default, Debug)]
+pub enum FortuneFile {
+    #[default]
+    Text,
+    Dat,
+}
+
+/// Файл fortune вместе с индексом strfile (`.dat`)
+pub struct Fortune {
+    pub text: String,
+    pub dat: Vec<u8>,
+}
+
+impl Fortune {
+    pub fn new(cookies: &[String]) -> Self {
+        let mut text = String::new();
+        let mut offsets: Vec<u32> = Vec::new();
+        let (mut longest, mut shortest) = (0u32, u32::MAX);
+
+        for cookie in cookies {
+            // Строка из одного разделителя внутри записи разбила бы файл
+            let cookie: Vec<String> = cookie
+                .trim_end()
+                .lines()
+                .map(|line| match line == DELIMITER.to_string() {
+                    true => format!(" {}", line),
+                    false => line.to_string(),
+                })
+                .collect();
+            let cookie = format!("{}\n", cookie.join("\n"));
+
+            let len = cookie.len() as u32;
+            longest = longest.max(len);
+            shortest = shortest.min(len);
+
+            offsets.push(text.len() as u32);
+            text.push_str(&cookie);
+            text.push(DELIMITER);
+            text.push('\n');
+        }
+        offsets.push(text.len() as u32);
+
+        if cookies.is_empty() {
+            shortest = 0;
+        }
+
+        // Заголовок strfile: версия, число записей, длины самой длинной и
+        // самой короткой записи, флаги и разделитель; все числа в сетевом порядке байт
+        let mut dat: Vec<u8> = Vec::new();
+        for value in [STRFILE_VERSION, cookies.len() as u32, longest, shortest, 0] {
+            dat.extend(value.to_be_bytes());
+        }
+        dat.extend([DELIMITER as u8, 0, 0, 0]);
+
+        for offset in offsets {
+            dat.extend(offset.to_be_bytes());
+        }
+
+        Fortune { text, dat }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Fortune};
+    use crate::model::{import::NewShrimp, shrimp::Category};
+
+    #[test]
+    fn parse_cookies() {
+        let data = "First cookie\nwith two lines\n%\n%\nSecond cookie\n%\n";
+        let rows = parse(data, &Category::Joke, &["unix".to_string()]).unwrap();
+
+        assert_eq!(rows.len(), 2);
+        match &rows[0] {
+            (1, Ok(NewShrimp::Joke(nj))) => {
+                assert_eq!(nj.text, "First cookie\nwith two lines");
+                assert_eq!(nj.tags, vec!["unix"]);
+            }
+            _ => panic!("cookie was not parsed"),
+        }
+
+        assert!(parse(data, &Category::Punch, &[]).is_err());
+    }
+
+    #[test]
+    fn strfile_index() {
+        let fortune = Fortune::new(&["ab".to_string(), "c\n%\nd".to_string()]);
+
+        assert_eq!(fortune.text, "ab\n%\nc\n %\nd\n%\n");
+
+        let words: Vec<u32> = fortune.dat[..20]
+            .chunks(4)
+            .map(|c| u32::from_be_bytes([c[0], c[1], c[2], c[3]]))
+            .collect();
+        assert_eq!(words, vec![2, 2, 7, 3, 0]);
+        assert_eq!(&fortune.dat[20..24], &[b'%', 0, 0, 0]);
+
+        let offsets: Vec<u32> = fortune.dat[24..]
+            .chunks(4)
+            .map(|c| u32::from_be_bytes([c[0], c[1], c[2], c[3]]))
+            .collect();
+        assert_eq!(offsets, vec![0, 5, fortune.text.len() as u32]);
+    }
+}
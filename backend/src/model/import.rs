@@ -0,0 +1,306 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use validator::Validate;
+
+use super::{anecdote::NewAnecdote, fortune, joke::NewJoke, punch::NewPunch, shrimp::Category};
+use crate::errors::HubError;
+
+/// Формат файла массового импорта
+#[derive(Clone, PartialEq, FromFormField, Default, Debug)]
+pub enum ImportFormat {
+    #[default]
+    Jsonl,
+    Csv,
+    Fortune,
+}
+
+/// Запись из файла импорта.
+/// Категория указывается в поле `category` каждой строки.
+#[derive(Clone, Deserialize, Debug)]
+#[serde(tag = "category")]
+pub enum NewShrimp {
+    #[serde(rename = "anecdote")]
+    Anecdote(NewAnecdote),
+
+    #[serde(rename = "joke")]
+    Joke(NewJoke),
+
+    #[serde(rename = "punch")]
+    Punch(NewPunch),
+}
+
+impl NewShrimp {
+    pub fn category(&self) -> Category {
+        match self {
+            NewShrimp::Anecdote(_) => Category::Anecdote,
+            NewShrimp::Joke(_) => Category::Joke,
+            NewShrimp::Punch(_) => Category::Punch,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), HubError> {
+        match self {
+            NewShrimp::Anecdote(na) => na.validate()?,
+            NewShrimp::Joke(nj) => nj.validate()?,
+            NewShrimp::Punch(np) => np.validate()?,
+        };
+
+        Ok(())
+    }
+
+    /// Текст, по которому определяется язык записи и флаги по словарям
+    pub fn text(&self) -> String {
+        match self {
+            NewShrimp::Anecdote(na) => na.text.clone(),
+            NewShrimp::Joke(nj) => nj.text.clone(),
+            NewShrimp::Punch(np) => np.full_text(),
+        }
+    }
+
+    /// Теги, указанные в строке файла
+    pub fn tags(&self) -> &[String] {
+        match self {
+            NewShrimp::Anecdote(na) => &na.tags,
+            NewShrimp::Joke(nj) => &nj.tags,
+            NewShrimp::Punch(np) => &np.tags,
+        }
+    }
+
+    /// Язык, указанный в строке файла
+    pub fn lang(&self) -> Option<&str> {
+        match self {
+            NewShrimp::Anecdote(na) => na.lang.as_deref(),
+            NewShrimp::Joke(nj) => nj.lang.as_deref(),
+            NewShrimp::Punch(np) => np.lang.as_deref(),
+        }
+    }
+}
+
+/// Строка файла импорта с ее номером (начиная с 1)
+pub type ImportRow = (usize, Result<NewShrimp, HubError>);
+
+/// Разбор файла импорта. Пустые строки пропускаются.
+/// В JSON Lines и CSV категория и теги указываются в каждой строке,
+/// записи файла fortune получают категорию `category` и теги `tags`.
+pub fn parse(
+    data: &str,
+    format: &ImportFormat,
+    category: &Category,
+    tags: &[String],
+) -> Result<Vec<ImportRow>, HubError> {
+    match format {
+        ImportFormat::Jsonl => Ok(parse_jsonl(data)),
+        ImportFormat::Csv => Ok(parse_csv(data)),
+        ImportFormat::Fortune => fortune::parse(data, category, tags),
+    }
+}
+
+fn parse_jsonl(data: &str) -> Vec<ImportRow> {
+    data.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| (i + 1, serde_json::from_str(line).map_err(row_error)))
+        .collect()
+}
+
+/// CSV с заголовком. Колонки `tags` и `flags` перечисляются через `;`.
+fn parse_csv(data: &str) -> Vec<ImportRow> {
+    let mut records = csv_records(data).into_iter();
+    let header = match records.next() {
+        Some((_, header)) => header,
+        None => return Vec::new(),
+    };
+
+    records
+        .filter(|(_, fields)| fields.iter().any(|f| !f.trim().is_empty()))
+        .map(|(line, fields)| {
+            if fields.len() != header.len() {
+                let error = HubError::new_unprocessable(
+                    "Row is invalid",
+                    Some(vec![format!(
+                        "Expected {} columns, found {}",
+                        header.len(),
+                        fields.len()
+                    )]),
+                );
+                return (line, Err(error));
+            }
+
+            let mut object = Map::new();
+            for (name, field) in header.iter().zip(fields) {
+                let name = name.trim();
+                let value = match name {
+                    "tags" | "flags" => {
+                        let items: Vec<Value> = field
+                            .split(';')
+                            .map(str::trim)
+                            .filter(|item| !item.is_empty())
+                            .map(|item| Value::String(item.to_string()))
+                            .collect();
+
+                        // Пустой список тегов заменяется тегами по умолчанию
+                        if items.is_empty() {
+                            continue;
+                        }
+                        Value::Array(items)
+                    }
+                    _ => Value::String(field),
+                };
+
+                object.insert(name.to_string(), value);
+            }
+
+            (
+                line,
+                serde_json::from_value(Value::Object(object)).map_err(row_error),
+            )
+        })
+        .collect()
+}
+
+/// Разбор CSV (RFC 4180): поля в кавычках могут содержать запятые,
+/// переводы строк и удвоенные кавычки. Для каждой записи возвращается номер ее первой строки.
+fn csv_records(data: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records: Vec<(usize, Vec<String>)> = Vec::new();
+    let mut fields: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let (mut line, mut start) = (1, 1);
+
+    let mut chars = data.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                fields.push(std::mem::take(&mut field));
+                records.push((start, std::mem::take(&mut fields)));
+                line += 1;
+                start = line;
+            }
+            ('\n', true) => {
+                field.push(c);
+                line += 1;
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((start, fields));
+    }
+
+    records
+}
+
+fn row_error(err: serde_json::Error) -> HubError {
+    HubError::new_unprocessable("Row is invalid", Some(vec![err.to_string()]))
+}
+
+/// Ошибка в строке файла импорта
+#[derive(Clone, Serialize, Debug)]
+pub struct ImportFailure {
+    pub row: usize,
+
+    #[serde(flatten)]
+    pub error: HubError,
+}
+
+/// Итог импорта.
+/// Записи, которые уже есть в базе (в том числе почти-дубликаты), пропускаются,
+/// поэтому повторная загрузка файла безопасна.
+#[derive(Clone, Serialize, Default, Debug)]
+pub struct ImportReport {
+    pub total: usize,
+    pub inserted: usize,
+    pub duplicates: usize,
+    pub failed: Vec<ImportFailure>,
+}
+
+impl ImportReport {
+    pub fn fail(&mut self, row: usize, error: HubError) {
+        self.failed.push(ImportFailure { row, error });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::{ImportFormat, ImportRow, NewShrimp};
+    use crate::model::shrimp::Category;
+
+    fn parse(data: &str, format: &ImportFormat) -> Vec<ImportRow> {
+        super::parse(data, format, &Category::Joke, &[]).unwrap()
+    }
+
+    #[test]
+    fn parse_jsonl() {
+        let data = concat!(
+            r#"{"category": "joke", "text": "Как каннибал называет Пашу? Паштет."}"#,
+            "\n\n",
+            r#"{"category": "story", "title": "Нет"}"#,
+            "\n",
+            r#"{"category": "punch", "setup": "Как каннибал называет Пашу?", "punchline": "Паштет", "tags": ["food"]}"#,
+        );
+
+        let rows = parse(data, &ImportFormat::Jsonl);
+        assert_eq!(rows.len(), 3);
+
+        assert!(matches!(rows[0], (1, Ok(NewShrimp::Joke(_)))));
+        assert!(matches!(rows[1], (3, Err(_))));
+        match &rows[2] {
+            (4, Ok(NewShrimp::Punch(np))) => assert_eq!(np.tags, vec!["food"]),
+            _ => panic!("punch was not parsed"),
+        }
+    }
+
+    #[test]
+    fn parse_csv() {
+        let data = concat!(
+            "category,text,tags,flags\r\n",
+            "joke,\"Say \"\"hi\"\", then, go\",work;office,nsfw\r\n",
+            "anecdote,\"Первая строка\nвторая строка\",,\n",
+            "joke,short\n",
+        );
+
+        let rows = parse(data, &ImportFormat::Csv);
+        assert_eq!(rows.len(), 3);
+
+        match &rows[0] {
+            (2, Ok(NewShrimp::Joke(nj))) => {
+                assert_eq!(nj.text, "Say \"hi\", then, go");
+                assert_eq!(nj.tags, vec!["work", "office"]);
+                assert_eq!(nj.flags.len(), 1);
+            }
+            _ => panic!("joke was not parsed"),
+        }
+
+        match &rows[1] {
+            (3, Ok(NewShrimp::Anecdote(na))) => {
+                assert_eq!(na.text, "Первая строка\nвторая строка");
+                assert_eq!(na.tags, vec!["general"]);
+            }
+            _ => panic!("anecdote was not parsed"),
+        }
+
+        // Колонок меньше, чем в заголовке
+        assert!(matches!(rows[2], (5, Err(_))));
+    }
+
+    #[test_case(r#"{"category": "joke", "text": "short"}"#, false ; "too_short")]
+    #[test_case(r#"{"category": "joke", "text": "Long enough joke text"}"#, true ; "valid")]
+    fn validate(line: &str, is_valid: bool) {
+        let rows = parse(line, &ImportFormat::Jsonl);
+        let shrimp = rows[0].1.as_ref().expect("row is parsed");
+
+        assert_eq!(shrimp.validate().is_ok(), is_valid);
+    }
+}
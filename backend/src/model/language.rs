@@ -0,0 +1,283 @@
+use lazy_static::lazy_static;
+use lingua::Language;
+use serde::{Deserialize, Serialize};
+use std::env;
+use validator::{Validate, ValidationError};
+
+use crate::errors::HubError;
+
+/// Языки контента по умолчанию, если не задана переменная `LANGUAGES`
+const DEFAULT_LANGUAGES: &str = "ru,en,uk,de,es,fr";
+
+/// Ниже этой уверенности детектор уступает языку, указанному автором
+pub const LOW_CONFIDENCE: f64 = 0.3;
+
+lazy_static! {
+    /// Языки контента: коды ISO 639-1 через запятую из переменной окружения `LANGUAGES`.
+    /// Доступны только языки, модели которых включены в зависимости lingua.
+    pub static ref SUPPORTED_LANGUAGES: Vec<Language> = configured(
+        &env::var("LANGUAGES").unwrap_or_else(|_| DEFAULT_LANGUAGES.to_string())
+    );
+}
+
+/// Разбор списка языков, неизвестные коды пропускаются
+pub fn configured(codes: &str) -> Vec<Language> {
+    let mut result: Vec<Language> = Vec::new();
+    for language in codes.split(',').filter_map(from_code) {
+        if !result.contains(&language) {
+            result.push(language);
+        }
+    }
+
+    result
+}
+
+/// Код ISO 639-1, под которым язык хранится в записях
+pub fn code(language: &Language) -> String {
+    language.iso_code_639_1().to_string()
+}
+
+/// Язык по коду ISO 639-1 или по названию (`ru`, `russian`).
+/// Названия хранились в записях до перехода на коды.
+pub fn from_code(value: &str) -> Option<Language> {
+    let value = value.trim().to_lowercase();
+
+    Language::all()
+        .into_iter()
+        .find(|language| code(language) == value || language.to_string() == value)
+}
+
+/// Коды поддерживаемых языков
+pub fn codes() -> Vec<String> {
+    SUPPORTED_LANGUAGES.iter().map(code).collect()
+}
+
+/// Проверка языка из параметра запроса, в ответе его код ISO 639-1
+pub fn validate(value: &str) -> Result<String, HubError> {
+    supported(value).map(|language| code(&language))
+}
+
+/// Поддерживаемый язык по коду или названию
+pub fn supported(value: &str) -> Result<Language, HubError> {
+    match from_code(value) {
+        Some(language) if SUPPORTED_LANGUAGES.contains(&language) => Ok(language),
+        _ => Err(HubError::new_unprocessable(
+            "Language is not supported",
+            Some(vec![format!("Supported languages: {}", codes().join(", "))]),
+        )),
+    }
+}
+
+/// Как был выбран язык записи
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct Detection {
+    /// Язык, который выбрал детектор, если он смог его определить
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detected: Option<String>,
+
+    /// Насколько язык записи вероятнее следующего за ним языка, от 0 до 1.
+    /// Низкая уверенность означает, что язык записи мог быть определен неверно.
+    pub confidence: f64,
+
+    /// Язык указан автором
+    pub declared: bool,
+}
+
+impl Detection {
+    /// Выбор языка записи по оценкам детектора (`values` по убыванию, лучший язык с оценкой 1.0).
+    /// Язык автора принимается, если он совпадает с выбором детектора или детектор не уверен.
+    pub fn new(
+        values: &[(Language, f64)],
+        declared: Option<Language>,
+    ) -> Result<(Language, Detection), HubError> {
+        let confidence = |language: &Language| -> f64 {
+            let value = values
+                .iter()
+                .find(|(l, _)| l == language)
+                .map(|(_, v)| *v)
+                .unwrap_or_default();
+            let rival = values
+                .iter()
+                .filter(|(l, _)| l != language)
+                .map(|(_, v)| *v)
+                .fold(0.0, f64::max);
+
+            ((value - rival).clamp(0.0, 1.0) * 1000.0).round() / 1000.0
+        };
+
+        // При равных оценках детектор не выбирает ни один язык
+        let detected = values
+            .first()
+            .map(|(language, _)| language.clone())
+            .filter(|language| confidence(language) > 0.0);
+
+        match (detected, declared) {
+            (Some(detected), None) => Ok((
+                detected.clone(),
+                Detection {
+                    detected: Some(code(&detected)),
+                    confidence: confidence(&detected),
+                    declared: false,
+                },
+            )),
+
+            (None, None) => Err(HubError::new_unprocessable(
+                "Unable to determine what language is used in the text",
+                Some(vec![String::from(
+                    "Specify the language in the `lang` field",
+                )]),
+            )),
+
+            (Some(detected), Some(declared))
+                if detected != declared && confidence(&detected) >= LOW_CONFIDENCE =>
+            {
+                Err(HubError::new_unprocessable(
+                    "Declared language does not match the text",
+                    Some(vec![format!(
+                        "Detected language: {} ({:.2})",
+                        code(&detected),
+                        confidence(&detected)
+                    )]),
+                ))
+            }
+
+            (detected, Some(declared)) => Ok((
+                declared.clone(),
+                Detection {
+                    detected: detected.as_ref().map(code),
+                    confidence: confidence(&declared),
+                    declared: true,
+                },
+            )),
+        }
+    }
+}
+
+/// Тело запроса на определение языка нескольких текстов
+#[derive(Clone, Deserialize, Validate, Debug)]
+pub struct DetectRequest {
+    #[validate(
+        length(min = 1, message = "Lenght is invalid"),
+        custom(function = "validate_texts", message = "Text is invalid")
+    )]
+    pub texts: Vec<String>,
+}
+
+fn validate_texts(texts: &[String]) -> Result<(), ValidationError> {
+    match texts
+        .iter()
+        .all(|t| (1..=10000).contains(&t.chars().count()))
+    {
+        true => Ok(()),
+        false => Err(ValidationError::new("texts")),
+    }
+}
+
+/// Язык-кандидат и его оценка относительно самого вероятного языка
+#[derive(Clone, Serialize, Debug)]
+pub struct Candidate {
+    pub language: String,
+    pub confidence: f64,
+}
+
+/// Результат определения языка одного текста
+#[derive(Clone, Serialize, Debug)]
+pub struct Guess {
+    /// Язык, который будет выбран при отправке записи, если детектор смог его определить
+    pub language: Option<String>,
+    pub confidence: f64,
+
+    /// Все возможные языки по убыванию оценки
+    pub candidates: Vec<Candidate>,
+}
+
+impl Guess {
+    pub fn new(values: &[(Language, f64)]) -> Self {
+        let (language, confidence) = match Detection::new(values, None) {
+            Ok((language, detection)) => (Some(code(&language)), detection.confidence),
+            Err(_) => (None, 0.0),
+        };
+
+        Guess {
+            language,
+            confidence,
+            candidates: values
+                .iter()
+                .map(|(language, value)| Candidate {
+                    language: code(language),
+                    confidence: (value * 1000.0).round() / 1000.0,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lingua::Language;
+    use test_case::test_case;
+
+    use super::{code, configured, from_code, validate, Detection, Guess};
+
+    #[test_case("ru", Some(Language::Russian) ; "iso_code")]
+    #[test_case(" EN ", Some(Language::English) ; "case_and_spaces")]
+    #[test_case("russian", Some(Language::Russian) ; "legacy_name")]
+    #[test_case("xx", None ; "unknown")]
+    fn language_from_code(value: &str, expected: Option<Language>) {
+        assert_eq!(from_code(value), expected);
+    }
+
+    #[test]
+    fn language_code() {
+        assert_eq!(code(&Language::Russian), "ru");
+        assert_eq!(code(&Language::English), "en");
+    }
+
+    #[test]
+    fn configured_languages() {
+        assert_eq!(
+            configured("en,xx,ru,en"),
+            vec![Language::English, Language::Russian]
+        );
+    }
+
+    #[test_case("english", Some("en") ; "legacy_name")]
+    #[test_case("ru", Some("ru") ; "iso_code")]
+    #[test_case("klingon", None ; "unsupported")]
+    fn validate_language(value: &str, expected: Option<&str>) {
+        assert_eq!(validate(value).ok().as_deref(), expected);
+    }
+
+    #[test_case(vec![(Language::Russian, 1.0), (Language::English, 0.2)], None, Some((Language::Russian, 0.8, false)) ; "detected")]
+    #[test_case(vec![(Language::Russian, 1.0), (Language::English, 1.0)], None, None ; "ambiguous")]
+    #[test_case(vec![], None, None ; "nothing_detected")]
+    #[test_case(vec![(Language::Russian, 1.0), (Language::English, 1.0)], Some(Language::English), Some((Language::English, 0.0, true)) ; "ambiguous_declared")]
+    #[test_case(vec![(Language::English, 1.0), (Language::Russian, 0.9)], Some(Language::Russian), Some((Language::Russian, 0.0, true)) ; "low_confidence_declared")]
+    #[test_case(vec![(Language::English, 1.0), (Language::Russian, 0.1)], Some(Language::English), Some((Language::English, 0.9, true)) ; "matching_declared")]
+    #[test_case(vec![(Language::English, 1.0), (Language::Russian, 0.1)], Some(Language::Russian), None ; "mismatching_declared")]
+    fn detection(
+        values: Vec<(Language, f64)>,
+        declared: Option<Language>,
+        expected: Option<(Language, f64, bool)>,
+    ) {
+        let result = Detection::new(&values, declared)
+            .ok()
+            .map(|(language, detection)| (language, detection.confidence, detection.declared));
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn guess() {
+        let guess = Guess::new(&[(Language::Russian, 1.0), (Language::English, 0.25)]);
+
+        assert_eq!(guess.language.as_deref(), Some("ru"));
+        assert_eq!(guess.confidence, 0.75);
+        assert_eq!(guess.candidates.len(), 2);
+        assert_eq!(guess.candidates[1].language, "en");
+
+        let guess = Guess::new(&[]);
+        assert_eq!(guess.language, None);
+        assert!(guess.candidates.is_empty());
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use super::shrimp::Flag;
+use crate::{err_internal, errors::HubError};
+
+/// Каталог словарей по умолчанию, если не задана переменная `LEXICON_DIR`
+const DEFAULT_DIR: &str = "lexicons";
+
+/// Термин словаря: одно или несколько слов.
+/// Слово со звездочкой на конце совпадает с любым словом, которое с него начинается.
+#[derive(Clone, Debug)]
+struct Term {
+    source: String,
+    words: Vec<String>,
+}
+
+impl Term {
+    fn new(source: &str) -> Option<Self> {
+        let words: Vec<String> = source
+            .split_whitespace()
+            .filter_map(|word| {
+                let normalized = normalize(word.trim_end_matches('*'));
+                match (normalized.is_empty(), word.ends_with('*')) {
+                    (true, _) => None,
+                    (false, true) => Some(format!("{}*", normalized)),
+                    (false, false) => Some(normalized),
+                }
+            })
+            .collect();
+
+        match words.is_empty() {
+            true => None,
+            false => Some(Term {
+                source: source.trim().to_lowercase(),
+                words,
+            }),
+        }
+    }
+
+    fn matches(&self, text: &[String]) -> bool {
+        text.windows(self.words.len()).any(|window| {
+            window
+                .iter()
+                .zip(self.words.iter())
+                .all(|(word, term)| match term.strip_suffix('*') {
+                    Some(prefix) => word.starts_with(prefix),
+                    None => word == term,
+                })
+        })
+    }
+}
+
+/// Словари деликатных терминов для автоматической расстановки флагов.
+/// Для каждого флага словарь читается из файлов `<флаг>*.txt` каталога словарей,
+/// например `nsfw.txt` или `political.ru.txt`: по термину на строку, `#` — комментарий.
+#[derive(Clone, Default, Debug)]
+pub struct Lexicon {
+    terms: Vec<(Flag, Term)>,
+}
+
+impl Lexicon {
+    /// Каталог словарей: переменная окружения `LEXICON_DIR` читается при запуске,
+    /// поэтому словари можно менять без пересборки
+    pub fn dir() -> PathBuf {
+        env::var("LEXICON_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_DIR))
+    }
+
+    pub fn load(dir: &Path) -> Result<Lexicon, HubError> {
+        let entries =
+            fs::read_dir(dir).map_err(|err| err_internal!("Faild to read lexicons", err))?;
+
+        let mut lexicon = Lexicon::default();
+        for entry in entries {
+            let path = entry
+                .map_err(|err| err_internal!("Faild to read lexicons", err))?
+                .path();
+
+            if path.extension().and_then(|e| e.to_str()) != Some("txt") {
+                continue;
+            }
+
+            let name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+            let flag = match Flag::all().into_iter().find(|flag| {
+                name.split('.').next() == Some(flag.to_string().to_lowercase().as_str())
+            }) {
+                Some(flag) => flag,
+                None => continue,
+            };
+
+            let content = fs::read_to_string(&path)
+                .map_err(|err| err_internal!("Faild to read lexicon", path.display(), err))?;
+            lexicon.extend(flag, &content);
+        }
+
+        Ok(lexicon)
+    }
+
+    /// Добавление терминов флага из текста словаря
+    pub fn extend(&mut self, flag: Flag, content: &str) {
+        for line in content.lines() {
+            let line = line.trim();
+            if line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(term) = Term::new(line) {
+                self.terms.push((flag.clone(), term));
+            }
+        }
+    }
+
+    /// Количество терминов для каждого флага
+    pub fn size(&self) -> HashMap<Flag, usize> {
+        let mut result: HashMap<Flag, usize> = HashMap::new();
+        for (flag, _) in self.terms.iter() {
+            *result.entry(flag.clone()).or_default() += 1;
+        }
+
+        result
+    }
+
+    /// Флаги, которые стоит выставить записи, и термины, из-за которых они выставлены
+    pub fn suggest(&self, text: &str) -> HashMap<Flag, Vec<String>> {
+        let words: Vec<String> = normalize(text)
+            .split(' ')
+            .map(|word| word.to_string())
+            .collect();
+
+        let mut result: HashMap<Flag, Vec<String>> = HashMap::new();
+        for (flag, term) in self.terms.iter() {
+            if term.matches(&words) {
+                let triggers = result.entry(flag.clone()).or_default();
+                if !triggers.contains(&term.source) {
+                    triggers.push(term.source.clone());
+                }
+            }
+        }
+
+        result
+    }
+}
+
+/// Нижний регистр, «ё» как «е», знаки препинания заменяются пробелами
+fn normalize(text: &str) -> String {
+    super::similarity::normalize(&text.replace('ё', "е").replace('Ё', "Е"))
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::Lexicon;
+    use crate::model::shrimp::Flag;
+
+    fn lexicon() -> Lexicon {
+        let mut lexicon = Lexicon::default();
+        lexicon.extend(Flag::Religious, "# комментарий\nцерк*\ngod\n\n");
+        lexicon.extend(Flag::Sexist, "женская логика\nтёща");
+        lexicon
+    }
+
+    #[test_case("Батюшка вышел из церкви", vec![(Flag::Religious, "церк*")] ; "prefix")]
+    #[test_case("Oh my God!", vec![(Flag::Religious, "god")] ; "case_and_punctuation")]
+    #[test_case("Goddess", vec![] ; "whole_word")]
+    #[test_case("Это женская, логика", vec![(Flag::Sexist, "женская логика")] ; "phrase")]
+    #[test_case("Пришла теща", vec![(Flag::Sexist, "тёща")] ; "yo")]
+    #[test_case("Логика женская", vec![] ; "phrase_order")]
+    fn suggest(text: &str, expected: Vec<(Flag, &str)>) {
+        let result = lexicon().suggest(text);
+
+        assert_eq!(result.len(), expected.len());
+        for (flag, term) in expected {
+            assert_eq!(result[&flag], vec![term.to_string()]);
+        }
+    }
+
+    #[test]
+    fn load_builtin_lexicons() {
+        let lexicon = Lexicon::load(&Lexicon::dir()).unwrap();
+        let size = lexicon.size();
+
+        for flag in Flag::all() {
+            assert!(size.get(&flag).cloned().unwrap_or_default() > 0);
+        }
+    }
+}
@@ -1,14 +1,23 @@
 pub mod account;
 pub mod anecdote;
+pub mod author;
+pub mod comment;
+pub mod daily;
+pub mod follow;
+pub mod fortune;
+pub mod import;
 pub mod joke;
+pub mod language;
+pub mod lexicon;
 pub mod punch;
+pub mod search;
 pub mod shrimp;
-
-use lazy_static::lazy_static;
-
-lazy_static! {
-    pub(crate) static ref SUPPORTED_LANGUAGES: Vec<&'static str> = ["ru", "en"].to_vec();
-}
+pub mod similarity;
+pub mod snapshot;
+pub mod story;
+pub mod tag;
+pub mod translation;
+pub mod trash;
 
 pub mod validation {
     use regex::Regex;
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use shrimplib::Paws;
 
-use crate::model::shrimp::{default_tags, Paws, Category};
+use crate::model::shrimp::{default_tags, Paws, Category, Flag};
 use validator::Validate;
 
 #[derive(Clone, Serialize, Deserialize, Paws)]
@@ -22,6 +22,21 @@ pub struct NewPunch {
 
     #[serde(default = "default_tags")]
     pub tags: Vec<String>,
+
+    /// Флаги деликатности, которые автор выставляет сам
+    #[serde(default)]
+    pub flags: Vec<Flag>,
+
+    /// Язык записи (код ISO 639-1), если автор указывает его сам
+    #[serde(default)]
+    pub lang: Option<String>,
+}
+
+impl NewPunch {
+    /// Завязка и развязка одной строкой, используется для определения языка
+    pub fn full_text(&self) -> String {
+        format!("{}\n{}", self.setup, self.punchline)
+    }
 }
 
 impl From<NewPunch> for Punch {
@@ -62,6 +77,8 @@ mod tests {
             setup: setup.to_string(),
             punchline: punchline.to_string(),
             tags: vec![],
+            flags: vec![],
+            lang: None,
         };
 
         match np.validate() {
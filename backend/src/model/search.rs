@@ -0,0 +1,174 @@
+/// Подсветка совпадений с поисковым запросом в тексте записи.
+/// Слова сравниваются по грубой основе (без окончаний), поэтому
+/// «каннибалы» подсветит и «каннибал», и «каннибалу».
+pub struct Highlighter {
+    stems: Vec<String>,
+}
+
+impl Highlighter {
+    /// Максимальная длина фрагмента в символах
+    const FRAGMENT: usize = 160;
+
+    /// Сколько символов оставлять перед первым совпадением
+    const LEAD: usize = 60;
+
+    pub fn new(query: &str) -> Self {
+        let stems = words(query)
+            .into_iter()
+            .map(|(start, end)| {
+                let word: String = query.chars().skip(start).take(end - start).collect();
+                stem(&word)
+            })
+            .filter(|stem| stem.chars().count() > 1)
+            .collect();
+
+        Highlighter { stems }
+    }
+
+    fn matches(&self, word: &str) -> bool {
+        let word = stem(word);
+        let short = word.chars().count() < 3;
+
+        self.stems
+            .iter()
+            .any(|s| (!short && s.starts_with(&word)) || word.starts_with(s.as_str()))
+    }
+
+    /// Фрагмент текста с отмеченными `<em>` совпадениями.
+    /// Если совпадений нет, возвращается `None`.
+    pub fn highlight(&self, text: &str) -> Option<String> {
+        let chars: Vec<char> = text.chars().collect();
+        let matched: Vec<(usize, usize)> = words(text)
+            .into_iter()
+            .filter(|(start, end)| {
+                let word: String = chars[*start..*end].iter().collect();
+                self.matches(&word)
+            })
+            .collect();
+
+        let first = matched.first()?.0;
+        let (from, to) = if chars.len() <= Self::FRAGMENT {
+            (0, chars.len())
+        } else {
+            let from = first
+                .saturating_sub(Self::LEAD)
+                .min(chars.len() - Self::FRAGMENT);
+            (from, from + Self::FRAGMENT)
+        };
+
+        let mut result = String::new();
+        if from > 0 {
+            result.push('…');
+        }
+
+        let mut pos = from;
+        for (start, end) in matched {
+            if start < from || end > to {
+                continue;
+            }
+
+            result.extend(&chars[pos..start]);
+            result.push_str("<em>");
+            result.extend(&chars[start..end]);
+            result.push_str("</em>");
+            pos = end;
+        }
+
+        result.extend(&chars[pos..to]);
+        if to < chars.len() {
+            result.push('…');
+        }
+
+        Some(result)
+    }
+
+    /// Не более `max` фрагментов из набора текстов
+    pub fn fragments(&self, texts: &[String], max: usize) -> Vec<String> {
+        texts
+            .iter()
+            .filter_map(|text| self.highlight(text))
+            .take(max)
+            .collect()
+    }
+}
+
+/// Границы слов в тексте (в символах)
+fn words(text: &str) -> Vec<(usize, usize)> {
+    let mut result = Vec::new();
+    let mut start: Option<usize> = None;
+
+    for (i, c) in text.chars().enumerate() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                result.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(s) = start {
+        result.push((s, text.chars().count()));
+    }
+
+    result
+}
+
+/// Грубая основа слова: у длинных слов отбрасываются два последних символа
+fn stem(word: &str) -> String {
+    let word = word.to_lowercase();
+    let len = word.chars().count();
+
+    if len <= 4 {
+        word
+    } else {
+        word.chars().take((len - 2).max(4)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::Highlighter;
+
+    #[test_case(
+        "каннибал",
+        "Как каннибал называет Пашу?",
+        Some("Как <em>каннибал</em> называет Пашу?") ;
+        "exact"
+    )]
+    #[test_case(
+        "каннибалы",
+        "Как каннибал называет Пашу?",
+        Some("Как <em>каннибал</em> называет Пашу?") ;
+        "stemmed"
+    )]
+    #[test_case(
+        "Cats",
+        "My cat hates other cats",
+        Some("My <em>cat</em> hates other <em>cats</em>") ;
+        "case_insensitive"
+    )]
+    #[test_case(
+        "штирлиц",
+        "Как каннибал называет Пашу?",
+        None ;
+        "no_match"
+    )]
+    fn highlight(query: &str, text: &str, expected: Option<&str>) {
+        let highlighter = Highlighter::new(query);
+        assert_eq!(highlighter.highlight(text).as_deref(), expected);
+    }
+
+    #[test]
+    fn highlight_long_text() {
+        let text = format!("{} Штирлиц {}", "а".repeat(200), "б".repeat(200));
+        let result = Highlighter::new("Штирлиц").highlight(&text).unwrap();
+
+        assert!(result.starts_with('…'));
+        assert!(result.ends_with('…'));
+        assert!(result.contains("<em>Штирлиц</em>"));
+    }
+}
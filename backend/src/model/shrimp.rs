@@ -7,8 +7,14 @@ use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::fmt;
 use uuid::Uuid;
-
-use super::account::Tariff;
+use validator::Validate;
+
+use super::{
+    account::Tariff,
+    language::{self, Detection},
+    similarity::Similar,
+    trash::Trash,
+};
 use crate::errors::HubError;
 
 /// Заголовок любой записи контента
@@ -16,6 +22,15 @@ use crate::errors::HubError;
 pub struct Head {
     pub counter: usize,
     pub timestamp: i64,
+
+    /// Время последнего изменения тела, статуса или флагов записи (мс);
+    /// у записей, которые не менялись после создания, отсутствует
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<i64>,
+
+    /// Время первого одобрения записи модератором (мс), с него запись видна всем
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published: Option<i64>,
 }
 
 impl Head {
@@ -23,16 +38,27 @@ impl Head {
         Head {
             counter: 0,
             timestamp: MongoDateTime::now().timestamp_millis(),
+            modified: None,
+            published: None,
         }
     }
 }
 
-#[derive(Clone, PartialEq, FromFormField, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, FromFormField, Serialize, Deserialize, Debug)]
 pub enum Flag {
+    #[serde(rename = "nsfw")]
     Nsfw,
+
+    #[serde(rename = "religious")]
     Religious,
+
+    #[serde(rename = "political")]
     Political,
+
+    #[serde(rename = "racist")]
     Racist,
+
+    #[serde(rename = "sexist")]
     Sexist,
 }
 
@@ -42,6 +68,35 @@ impl fmt::Display for Flag {
     }
 }
 
+impl Flag {
+    /// Список всех флагов деликатности
+    pub fn all() -> Vec<Flag> {
+        vec![
+            Flag::Nsfw,
+            Flag::Religious,
+            Flag::Political,
+            Flag::Racist,
+            Flag::Sexist,
+        ]
+    }
+}
+
+impl<'a> FromParam<'a> for Flag {
+    type Error = HubError;
+
+    fn from_param(param: &'a str) -> Result<Self, Self::Error> {
+        match param {
+            "nsfw" => Ok(Self::Nsfw),
+            "religious" => Ok(Self::Religious),
+            "political" => Ok(Self::Political),
+            "racist" => Ok(Self::Racist),
+            "sexist" => Ok(Self::Sexist),
+
+            _ => Err(HubError::new_unprocessable("Flag is invalid", None)),
+        }
+    }
+}
+
 /// Флаги деликатности контента
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Flags {
@@ -99,20 +154,142 @@ impl Flags {
         self.sexist = !self.sexist;
         return self.clone();
     }
+
+    pub fn get(&self, flag: &Flag) -> bool {
+        match flag {
+            Flag::Nsfw => self.nsfw,
+            Flag::Religious => self.religious,
+            Flag::Political => self.political,
+            Flag::Racist => self.racist,
+            Flag::Sexist => self.sexist,
+        }
+    }
+
+    /// Установка флага в нужное значение.
+    /// Если флаг уже имеет это значение, флаги остаются без изменений.
+    pub fn set(&mut self, flag: &Flag, value: bool) -> Self {
+        if self.get(flag) == value {
+            return self.clone();
+        }
+
+        match flag {
+            Flag::Nsfw => self.nsfw_coup(),
+            Flag::Religious => self.religious_coup(),
+            Flag::Political => self.political_coup(),
+            Flag::Racist => self.racist_coup(),
+            Flag::Sexist => self.sexist_coup(),
+        }
+    }
+}
+
+/// Флаги, которые автор выставил своей записи при отправке
+impl From<&Vec<Flag>> for Flags {
+    fn from(list: &Vec<Flag>) -> Self {
+        let mut flags = Flags::default();
+
+        for flag in list {
+            flags.set(flag, true);
+        }
+
+        flags
+    }
+}
+
+/// Этап проверки записи модераторами
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(tag = "kind")]
+pub enum Status {
+    #[serde(rename = "pending")]
+    Pending,
+
+    #[serde(rename = "approved")]
+    Approved,
+
+    #[serde(rename = "rejected")]
+    Rejected { reason: String },
+}
+
+/// Записи, созданные до появления модерации, считаются одобренными
+impl Default for Status {
+    fn default() -> Self {
+        Status::Approved
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Pending => write!(f, "pending"),
+            Status::Approved => write!(f, "approved"),
+            Status::Rejected { .. } => write!(f, "rejected"),
+        }
+    }
+}
+
+/// Состояние записи до смены статуса модератором
+pub struct StatusChange {
+    pub author: String,
+    pub previous: Status,
+
+    /// Время первого одобрения, если запись уже одобрялась
+    pub published: Option<i64>,
+}
+
+impl StatusChange {
+    /// Запись впервые стала видна всем, в том числе после пересмотра отказа.
+    /// Повторное одобрение или одобрение после правки автора новостью не считается
+    pub fn is_first_approval(&self) -> bool {
+        self.previous != Status::Approved && self.published.is_none()
+    }
+}
+
+/// Тело запроса при отклонении записи модератором
+#[derive(Clone, Deserialize, Validate, Debug)]
+pub struct Rejection {
+    #[validate(length(min = 5, max = 280, message = "Lenght is invalid"))]
+    pub reason: String,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Tail {
     pub flags: Flags,
 
+    #[serde(default)]
+    pub status: Status,
+
     pub author: String,
 
     pub tags: Vec<String>,
 
     pub reactions: HashMap<ReactionKind, usize>,
 
+    /// Количество комментариев к записи
+    #[serde(default)]
+    pub comments_count: usize,
+
+    /// Код языка ISO 639-1
     #[serde(rename = "language")]
     pub lang: String,
+
+    /// Уверенность в языке записи; у записей, созданных до ее появления, отсутствует
+    #[serde(
+        rename = "language_detection",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub detection: Option<Detection>,
+
+    /// Похожая запись, найденная при создании: такие записи требуют внимания модератора
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similar: Option<Similar>,
+
+    /// Термины словарей, по которым флаги были выставлены автоматически; видны только модераторам
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub triggers: HashMap<Flag, Vec<String>>,
+
+    /// Отметка об удалении, пока запись лежит в корзине
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trash: Option<Trash>,
 }
 
 pub(crate) fn default_tags() -> Vec<String> {
@@ -123,12 +300,34 @@ impl Tail {
     pub fn new(flags: Flags, lang: Language, author: String, tags: &Vec<String>) -> Self {
         Tail {
             flags,
-            lang: lang.to_string(),
+            status: Status::Pending,
+            lang: language::code(&lang),
+            detection: None,
             author,
             reactions: HashMap::new(),
+            comments_count: 0,
             tags: tags.to_vec(),
+            similar: None,
+            triggers: HashMap::new(),
+            trash: None,
         }
     }
+
+    /// Как был выбран язык записи
+    pub fn detection(mut self, detection: Detection) -> Self {
+        self.detection = Some(detection);
+        self
+    }
+
+    /// Флаги, предложенные по словарям, выставляются в дополнение к флагам автора
+    pub fn suggest(mut self, triggers: HashMap<Flag, Vec<String>>) -> Self {
+        for flag in triggers.keys() {
+            self.flags.set(flag, true);
+        }
+
+        self.triggers = triggers;
+        self
+    }
 }
 
 /// Общее тело для любого текстового контента.
@@ -154,6 +353,9 @@ where
     pub tail: Tail,
 }
 
+/// Поля мета-данных, которые видят только модераторы
+const MODERATION_FIELDS: [&str; 2] = ["similar", "triggers"];
+
 pub trait Paws {
     fn get_category(&self) -> Category;
 }
@@ -172,30 +374,39 @@ where
         }
     }
 
-    /// Сериализация контента согласно пользовательскому тарифу
+    /// Сериализация контента согласно пользовательскому тарифу.
+    /// Служебные поля модерации потребителям не отдаются.
     pub fn tariffing(&self, tariff: &Tariff, err: &Option<HubError>) -> Value {
-        match tariff {
-            Tariff::Free => {
-                let base = json!(self.body);
-                Self::err_union(base, err)
-            }
+        let base = match tariff {
+            Tariff::Free => json!(self.body),
             Tariff::Basic => {
                 let mut base = json!({"id": self.id});
 
                 Self::merge(&mut base, json!(self.body));
                 Self::merge(&mut base, json!({"_meta-data": self.tail}));
 
-                Self::err_union(base, err)
+                base
             }
-            Tariff::Standart => {
-                let base = json!(self);
-                Self::err_union(base, err)
-            }
-            Tariff::Enterprice => {
-                let base = json!(self);
-                Self::err_union(base, err)
+            Tariff::Standart | Tariff::Enterprice => json!(self),
+        };
+
+        Self::err_union(Self::conceal(base), err)
+    }
+
+    /// Полная запись для модераторов вместе со служебными полями
+    pub fn moderation(&self) -> Value {
+        json!(self)
+    }
+
+    /// Удаление служебных полей модерации из мета-данных
+    fn conceal(mut base: Value) -> Value {
+        if let Some(tail) = base.get_mut("_meta-data").and_then(Value::as_object_mut) {
+            for field in MODERATION_FIELDS {
+                tail.remove(field);
             }
         }
+
+        base
     }
 
     /// Быстрое слияние json объектов
@@ -223,7 +434,7 @@ where
     }
 }
 
-#[derive(Clone, Serialize, PartialEq, Deserialize, FromFormField, Debug)]
+#[derive(Clone, Serialize, PartialEq, Eq, Hash, Deserialize, FromFormField, Debug)]
 pub enum Category {
     #[serde(rename = "anecdote")]
     Anecdote,
@@ -233,6 +444,9 @@ pub enum Category {
 
     #[serde(rename = "punch")]
     Punch,
+
+    #[serde(rename = "story")]
+    Story,
 }
 
 impl<'a> FromParam<'a> for Category {
@@ -243,16 +457,39 @@ impl<'a> FromParam<'a> for Category {
             "anecdote" => Ok(Self::Anecdote),
             "joke" => Ok(Self::Joke),
             "punch" => Ok(Self::Punch),
+            "story" => Ok(Self::Story),
 
-            _ => Err(HubError::new_unprocessable(
-                "Reaction kind is invalid",
-                None,
-            )),
+            _ => Err(HubError::new_unprocessable("Category is invalid", None)),
         }
     }
 }
 
 impl Category {
+    /// Список всех доступных категорий
+    pub fn all() -> Vec<Category> {
+        vec![
+            Category::Anecdote,
+            Category::Joke,
+            Category::Punch,
+            Category::Story,
+        ]
+    }
+
+    /// Текстовые поля записи, по которым идет полнотекстовый поиск, и их веса
+    pub fn text_fields(&self) -> Vec<(&'static str, i32)> {
+        match self {
+            Category::Anecdote => vec![("text", 1)],
+            Category::Joke => vec![("text", 1)],
+            Category::Punch => vec![("setup", 1), ("punchline", 1)],
+            Category::Story => vec![
+                ("title", 3),
+                ("text", 1),
+                ("parts.title", 2),
+                ("parts.text", 1),
+            ],
+        }
+    }
+
     /// Выбор случайной категории.
     /// Если есть предпочитаемые категории, выбирается случайная из предоставленных.
     /// Если список предпочтений пуст, выбирается из общего списка категоий.
@@ -263,10 +500,8 @@ impl Category {
     ///
     /// Если значение FALSE, пустой массив не перезаписывается.
     pub fn random(mut list: Option<Vec<Category>>, rf: bool) -> (Option<Category>, Vec<Category>) {
-        use super::shrimp::Category::{Anecdote, Joke, Punch};
-
         if rf {
-            let list = list.get_or_insert(vec![Anecdote, Joke, Punch]);
+            let list = list.get_or_insert(Category::all());
             let random_category = list.choose(&mut rand::thread_rng()).unwrap().to_owned();
 
             // Удаляю выбранную категорию из доступных
@@ -325,6 +560,23 @@ pub enum ReactionKind {
     ThumbsDown,
 }
 
+impl ReactionKind {
+    pub fn all() -> Vec<ReactionKind> {
+        vec![
+            ReactionKind::Laughing,
+            ReactionKind::Enraged,
+            ReactionKind::Fire,
+            ReactionKind::ThumbsUp,
+            ReactionKind::ThumbsDown,
+        ]
+    }
+
+    /// Одобрительная ли реакция, используется при подсчете рейтинга
+    pub fn is_positive(&self) -> bool {
+        !matches!(self, ReactionKind::Enraged | ReactionKind::ThumbsDown)
+    }
+}
+
 impl fmt::Display for ReactionKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}", self)
@@ -349,3 +601,368 @@ impl<'a> FromParam<'a> for ReactionKind {
         }
     }
 }
+
+/// Период, за который отбираются записи для подборок
+#[derive(Clone, PartialEq, FromFormField, Debug)]
+pub enum Period {
+    #[field(value = "day")]
+    Day,
+
+    #[field(value = "week")]
+    Week,
+
+    #[field(value = "month")]
+    Month,
+
+    #[field(value = "year")]
+    Year,
+
+    #[field(value = "all")]
+    All,
+}
+
+impl Period {
+    /// Длительность периода в миллисекундах
+    pub fn millis(&self) -> Option<i64> {
+        const DAY: i64 = 24 * 60 * 60 * 1000;
+
+        match self {
+            Period::Day => Some(DAY),
+            Period::Week => Some(7 * DAY),
+            Period::Month => Some(30 * DAY),
+            Period::Year => Some(365 * DAY),
+            Period::All => None,
+        }
+    }
+}
+
+/// Поле, по которому упорядочивается список записей
+#[derive(Clone, PartialEq, FromFormField, Debug)]
+pub enum SortKey {
+    /// Время создания записи
+    #[field(value = "timestamp")]
+    Timestamp,
+
+    /// Время первого одобрения записи, у старых записей — время создания
+    #[field(value = "published")]
+    Published,
+
+    /// Количество показов
+    #[field(value = "counter")]
+    Counter,
+
+    /// Сумма всех реакций
+    #[field(value = "reactions")]
+    Reactions,
+}
+
+impl Default for SortKey {
+    fn default() -> Self {
+        SortKey::Timestamp
+    }
+}
+
+#[derive(Clone, PartialEq, FromFormField, Debug)]
+pub enum SortOrder {
+    #[field(value = "asc")]
+    Asc,
+
+    #[field(value = "desc")]
+    Desc,
+}
+
+impl Default for SortOrder {
+    fn default() -> Self {
+        SortOrder::Desc
+    }
+}
+
+impl SortOrder {
+    pub fn direction(&self) -> i32 {
+        match self {
+            SortOrder::Asc => 1,
+            SortOrder::Desc => -1,
+        }
+    }
+}
+
+pub mod revision {
+    use bson::{oid::ObjectId, Document};
+    use mongodb::bson::DateTime as MongoDateTime;
+    use serde::{Deserialize, Serialize};
+
+    use super::Category;
+
+    /// Предыдущая версия записи.
+    /// Сохраняется целиком при каждом редактировании или откате записи.
+    #[derive(Clone, Serialize, Deserialize)]
+    pub struct Revision {
+        #[serde(rename = "_id")]
+        pub id: ObjectId,
+        pub record_id: String,
+        pub category: Category,
+        pub editor: String,
+        pub snapshot: Document,
+        pub created_at: MongoDateTime,
+    }
+
+    impl Revision {
+        pub fn new(record_id: &str, category: Category, editor: &str, snapshot: Document) -> Self {
+            Self {
+                id: ObjectId::new(),
+                record_id: record_id.to_string(),
+                category,
+                editor: editor.to_string(),
+                snapshot,
+                created_at: MongoDateTime::now(),
+            }
+        }
+
+        /// Поля тела записи из снимка, без заголовка и мета-данных
+        pub fn body(&self) -> Document {
+            self.snapshot
+                .iter()
+                .filter(|(key, _)| !key.starts_with('_'))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect()
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct RevisionInfo {
+        id: String,
+        editor: String,
+        snapshot: Document,
+        created_at: String,
+    }
+
+    impl From<Revision> for RevisionInfo {
+        fn from(r: Revision) -> Self {
+            Self {
+                id: r.id.to_hex(),
+                editor: r.editor,
+                snapshot: r.snapshot,
+                created_at: r.created_at.to_rfc3339_string(),
+            }
+        }
+    }
+}
+
+pub mod moderation {
+    use bson::oid::ObjectId;
+    use mongodb::bson::DateTime as MongoDateTime;
+    use serde::{Deserialize, Serialize};
+
+    use super::{Category, Flag};
+
+    /// Запись журнала изменения флагов деликатности
+    #[derive(Clone, Serialize, Deserialize)]
+    pub struct FlagAudit {
+        #[serde(rename = "_id")]
+        pub id: ObjectId,
+        pub record_id: String,
+        pub category: Category,
+        pub flag: Flag,
+        pub value: bool,
+        pub moderator: String,
+        pub created_at: MongoDateTime,
+    }
+
+    impl FlagAudit {
+        pub fn new(
+            record_id: &str,
+            category: Category,
+            flag: Flag,
+            value: bool,
+            moderator: &str,
+        ) -> Self {
+            Self {
+                id: ObjectId::new(),
+                record_id: record_id.to_string(),
+                category,
+                flag,
+                value,
+                moderator: moderator.to_string(),
+                created_at: MongoDateTime::now(),
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct FlagAuditInfo {
+        flag: Flag,
+        value: bool,
+        moderator: String,
+        created_at: String,
+    }
+
+    impl From<FlagAudit> for FlagAuditInfo {
+        fn from(fa: FlagAudit) -> Self {
+            Self {
+                flag: fa.flag,
+                value: fa.value,
+                moderator: fa.moderator,
+                created_at: fa.created_at.to_rfc3339_string(),
+            }
+        }
+    }
+}
+
+pub mod history {
+    use bson::oid::ObjectId;
+    use mongodb::bson::DateTime as MongoDateTime;
+    use serde::{Deserialize, Serialize};
+
+    use super::Category;
+
+    /// Запись, показанная потребителю через неповторяющийся `/random`
+    #[derive(Clone, Serialize, Deserialize)]
+    pub struct Served {
+        #[serde(rename = "_id")]
+        pub id: ObjectId,
+        pub identity: String,
+        pub record_id: String,
+        pub category: Category,
+        pub served_at: MongoDateTime,
+    }
+
+    impl Served {
+        /// Окно истории по умолчанию, в часах
+        pub const DEFAULT_WINDOW: u32 = 24;
+
+        /// Максимальное окно истории, в часах.
+        /// Старые записи удаляются TTL-индексом коллекции.
+        pub const MAX_WINDOW: u32 = 24 * 30;
+
+        pub fn new(identity: &str, record_id: &str, category: Category) -> Self {
+            Self {
+                id: ObjectId::new(),
+                identity: identity.to_string(),
+                record_id: record_id.to_string(),
+                category,
+                served_at: MongoDateTime::now(),
+            }
+        }
+    }
+}
+
+pub mod reaction {
+    use bson::oid::ObjectId;
+    use mongodb::bson::DateTime as MongoDateTime;
+    use serde::{Deserialize, Serialize};
+
+    use super::{Category, ReactionKind};
+
+    /// Реакция на запись.
+    /// Каждый потребитель (владелец Api-Key или пользователь) держит не больше одной реакции на запись.
+    #[derive(Clone, Serialize, Deserialize)]
+    pub struct Reaction {
+        #[serde(rename = "_id")]
+        pub id: ObjectId,
+        pub record_id: String,
+        pub category: Category,
+        pub identity: String,
+        pub kind: ReactionKind,
+        pub created_at: MongoDateTime,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct ReactionInfo {
+        kind: ReactionKind,
+        created_at: String,
+    }
+
+    impl From<Reaction> for ReactionInfo {
+        fn from(r: Reaction) -> Self {
+            Self {
+                kind: r.kind,
+                created_at: r.created_at.to_rfc3339_string(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lingua::Language;
+    use test_case::test_case;
+
+    use super::{Category, Flag, Flags, Shrimp, Status, StatusChange, Tail};
+    use crate::model::{account::Tariff, joke::Joke, similarity::Similar};
+
+    #[test_case(Status::Pending, None, true ; "first_approval" )]
+    #[test_case(Status::Pending, Some(1), false ; "approval_after_edit" )]
+    #[test_case(Status::Approved, None, false ; "already_approved" )]
+    #[test_case(Status::Rejected { reason: String::from("spam") }, None, true ; "after_rejection" )]
+    #[test_case(Status::Rejected { reason: String::from("spam") }, Some(1), false ; "rejected_after_publication" )]
+    fn status_change_first_approval(previous: Status, published: Option<i64>, expected: bool) {
+        let change = StatusChange {
+            author: String::from("upadawan"),
+            previous,
+            published,
+        };
+
+        assert_eq!(change.is_first_approval(), expected);
+    }
+
+    #[test_case(Flag::Nsfw, true ; "set_nsfw" )]
+    #[test_case(Flag::Religious, true ; "set_religious" )]
+    #[test_case(Flag::Political, false ; "clear_political" )]
+    #[test_case(Flag::Racist, false ; "clear_racist" )]
+    #[test_case(Flag::Sexist, true ; "set_sexist" )]
+    fn flags_set(flag: Flag, value: bool) {
+        let mut flags = Flags::new(false, false, true, true, false);
+
+        // Повторная установка того же значения не должна переключать флаг
+        flags.set(&flag, value);
+        flags.set(&flag, value);
+
+        assert_eq!(flags.get(&flag), value);
+    }
+
+    #[test]
+    fn flags_from_list() {
+        let flags = Flags::from(&vec![Flag::Nsfw, Flag::Sexist, Flag::Nsfw]);
+
+        assert!(flags.nsfw);
+        assert!(flags.sexist);
+        assert!(!flags.religious);
+        assert!(!flags.political);
+        assert!(!flags.racist);
+    }
+
+    #[test_case(Tariff::Basic ; "basic")]
+    #[test_case(Tariff::Standart ; "standart")]
+    #[test_case(Tariff::Enterprice ; "enterprice")]
+    fn tariffing_conceals_moderation(tariff: Tariff) {
+        let mut tail = Tail::new(
+            Flags::default(),
+            Language::English,
+            String::from("author"),
+            &vec![],
+        );
+        tail.similar = Some(Similar {
+            record_id: String::from("other"),
+            category: Category::Joke,
+            similarity: 0.8,
+        });
+        tail.triggers
+            .insert(Flag::Religious, vec![String::from("church")]);
+
+        let joke = Shrimp::new(
+            Joke {
+                category: Category::Joke,
+                text: String::from("Some joke text"),
+            },
+            tail,
+        );
+
+        let tail = &joke.tariffing(&tariff, &None)["_meta-data"];
+        assert!(tail["similar"].is_null());
+        assert!(tail["triggers"].is_null());
+
+        let tail = &joke.moderation()["_meta-data"];
+        assert!(tail["similar"].is_object());
+        assert!(tail["triggers"].is_object());
+    }
+}
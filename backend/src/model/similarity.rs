@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+
+use super::shrimp::Category;
+use crate::errors::HubError;
+
+/// Количество хеш-функций в сигнатуре MinHash
+const PERMUTATIONS: usize = 64;
+
+/// Сигнатура делится на полосы для поиска кандидатов (LSH):
+/// записи, совпавшие хотя бы в одной полосе, сравниваются целиком
+const BANDS: usize = 16;
+const ROWS: usize = PERMUTATIONS / BANDS;
+
+/// Длина шингла в символах
+const SHINGLE: usize = 5;
+
+/// Начиная с этой схожести новая запись отклоняется как дубликат
+pub const REJECT_THRESHOLD: f64 = 0.9;
+
+/// Начиная с этой схожести запись принимается, но помечается для модераторов
+pub const FLAG_THRESHOLD: f64 = 0.7;
+
+/// Отпечаток текста записи для поиска почти-дубликатов
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Fingerprint {
+    #[serde(rename = "_id")]
+    pub record_id: String,
+    pub category: Category,
+    pub signature: Vec<i64>,
+    pub bands: Vec<i64>,
+}
+
+impl Fingerprint {
+    pub fn new(record_id: &str, category: Category, text: &str) -> Self {
+        let signature = signature(&normalize(text));
+        let bands = signature
+            .chunks(ROWS)
+            .enumerate()
+            .map(|(i, rows)| {
+                let mut hash = fnv(&(i as u64).to_be_bytes());
+                for value in rows {
+                    hash = mix(hash ^ *value as u64);
+                }
+                hash as i64
+            })
+            .collect();
+
+        Fingerprint {
+            record_id: record_id.to_string(),
+            category,
+            signature,
+            bands,
+        }
+    }
+
+    /// Оценка коэффициента Жаккара между текстами двух записей
+    pub fn similarity(&self, other: &Fingerprint) -> f64 {
+        let equal = self
+            .signature
+            .iter()
+            .zip(other.signature.iter())
+            .filter(|(a, b)| a == b)
+            .count();
+
+        equal as f64 / PERMUTATIONS as f64
+    }
+}
+
+/// Похожая запись, уже существующая в базе
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Similar {
+    pub record_id: String,
+    pub category: Category,
+    pub similarity: f64,
+}
+
+impl Similar {
+    pub fn is_duplicate(&self) -> bool {
+        self.similarity >= REJECT_THRESHOLD
+    }
+
+    /// Отказ в сохранении записи, слишком похожей на эту
+    pub fn rejection(&self) -> HubError {
+        HubError::new_unprocessable(
+            "Record is a near-duplicate of an existing one",
+            Some(vec![
+                format!(
+                    "{}/{}",
+                    self.category.to_string().to_lowercase(),
+                    self.record_id
+                ),
+                format!("Similarity {:.2}", self.similarity),
+            ]),
+        )
+    }
+}
+
+/// Группа записей, попарно похожих друг на друга
+#[derive(Clone, Serialize, Debug)]
+pub struct DuplicateCluster {
+    pub records: Vec<ClusterRecord>,
+
+    /// Наибольшая схожесть двух записей группы
+    pub similarity: f64,
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct ClusterRecord {
+    pub id: String,
+    pub category: Category,
+}
+
+/// Приведение текста к виду, в котором лишние пробелы,
+/// регистр и знаки препинания не влияют на сравнение
+pub fn normalize(text: &str) -> String {
+    let mut result = String::new();
+
+    for c in text.to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            result.push(c);
+        } else if !result.is_empty() && !result.ends_with(' ') {
+            result.push(' ');
+        }
+    }
+
+    result.trim_end().to_string()
+}
+
+fn signature(text: &str) -> Vec<i64> {
+    let chars: Vec<char> = text.chars().collect();
+    let shingles: Vec<u64> = if chars.len() <= SHINGLE {
+        vec![fnv(text.as_bytes())]
+    } else {
+        chars
+            .windows(SHINGLE)
+            .map(|w| fnv(w.iter().collect::<String>().as_bytes()))
+            .collect()
+    };
+
+    (0..PERMUTATIONS as u64)
+        .map(|i| {
+            let seed = mix(i + 1);
+            shingles
+                .iter()
+                .map(|shingle| mix(shingle ^ seed))
+                .min()
+                .unwrap_or_default() as i64
+        })
+        .collect()
+}
+
+/// FNV-1a: хеш не зависит от версии компилятора, поэтому отпечатки можно хранить
+fn fnv(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Перемешивание битов (splitmix64)
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::{normalize, Fingerprint};
+    use crate::model::shrimp::Category;
+
+    #[test_case("Как каннибал  называет Пашу?", "как каннибал называет пашу" ; "spaces_and_punctuation")]
+    #[test_case("  -- Hello,World!! ", "hello world" ; "leading_symbols")]
+    fn normalize_text(text: &str, expected: &str) {
+        assert_eq!(normalize(text), expected);
+    }
+
+    fn similarity(a: &str, b: &str) -> f64 {
+        Fingerprint::new("a", Category::Joke, a).similarity(&Fingerprint::new(
+            "b",
+            Category::Joke,
+            b,
+        ))
+    }
+
+    #[test]
+    fn near_duplicates() {
+        let text = "Штирлиц долго смотрел в одну точку. Потом в другую. Двоеточие, понял Штирлиц.";
+
+        assert_eq!(
+            similarity(
+                text,
+                "Штирлиц  долго смотрел в одну точку, потом в другую... Двоеточие - понял Штирлиц!"
+            ),
+            1.0
+        );
+        assert!(similarity(text, "Штирлиц долго смотрел в одну точку. Потом в другую. Двоеточие, понял Штирлиц сразу.") > 0.7);
+        assert!(
+            similarity(
+                text,
+                "Why did the chicken cross the road? To get to the other side."
+            ) < 0.2
+        );
+    }
+
+    #[test]
+    fn bands_match_for_equal_texts() {
+        let a = Fingerprint::new("a", Category::Joke, "My cat hates other cats");
+        let b = Fingerprint::new("b", Category::Anecdote, "my cat, hates other cats!");
+
+        assert_eq!(a.bands, b.bands);
+    }
+}
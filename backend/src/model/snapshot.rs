@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+use super::shrimp::Category;
+
+/// Описание снимка данных: сколько записей каждой категории в него вошло
+/// и контрольные суммы SHA-256 несжатых строк JSON Lines.
+#[derive(Clone, Serialize, Debug)]
+pub struct Manifest {
+    /// Снимок включает записи, созданные или измененные после `since` и не позже `until` (мс)
+    pub since: Option<i64>,
+    pub until: i64,
+
+    pub total: usize,
+    /// Количество отметок об удаленных записях, только в инкрементальном снимке
+    pub removed: usize,
+    pub sha256: String,
+    pub categories: Vec<ManifestEntry>,
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct ManifestEntry {
+    pub category: Category,
+    pub count: usize,
+    pub removed: usize,
+    pub sha256: String,
+}
+
+/// Отметка о записи, которая пропала из открытого доступа:
+/// ушла в корзину, вернулась на модерацию или была удалена окончательно.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Tombstone {
+    pub id: String,
+    pub category: Category,
+    pub removed_at: i64,
+}
+
+impl Tombstone {
+    pub fn new(id: &str, category: Category, removed_at: i64) -> Self {
+        Tombstone {
+            id: id.to_string(),
+            category,
+            removed_at,
+        }
+    }
+}
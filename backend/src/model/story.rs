@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::model::shrimp::{default_tags, Category, Flag, Paws};
+use shrimplib::Paws;
+
+/// Длинная история, которая не помещается в лимит анекдота
+#[derive(Clone, Serialize, Deserialize, Paws)]
+pub struct Story {
+    pub category: Category,
+    pub title: String,
+
+    /// Абзацы основного текста
+    pub text: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parts: Option<Vec<Part>>,
+}
+
+/// Отдельная глава истории
+#[derive(Clone, Serialize, Deserialize, Validate, Debug)]
+pub struct Part {
+    #[validate(length(min = 2, max = 120, message = "Lenght is invalid"))]
+    pub title: String,
+
+    #[validate(
+        length(min = 1, max = 50, message = "Lenght is invalid"),
+        custom(function = "validate_paragraphs", message = "Paragraph is invalid")
+    )]
+    pub text: Vec<String>,
+}
+
+#[derive(Clone, Deserialize, Validate, Debug)]
+pub struct NewStory {
+    #[validate(length(min = 2, max = 120, message = "Lenght is invalid"))]
+    pub title: String,
+
+    #[validate(
+        length(min = 1, max = 50, message = "Lenght is invalid"),
+        custom(function = "validate_paragraphs", message = "Paragraph is invalid")
+    )]
+    pub text: Vec<String>,
+
+    #[validate(length(max = 20, message = "Lenght is invalid"))]
+    #[validate]
+    pub parts: Option<Vec<Part>>,
+
+    #[serde(default = "default_tags")]
+    pub tags: Vec<String>,
+
+    /// Флаги деликатности, которые автор выставляет сам
+    #[serde(default)]
+    pub flags: Vec<Flag>,
+
+    /// Язык записи (код ISO 639-1), если автор указывает его сам
+    #[serde(default)]
+    pub lang: Option<String>,
+}
+
+impl NewStory {
+    /// Весь текст истории одной строкой, используется для определения языка
+    pub fn full_text(&self) -> String {
+        let mut paragraphs = vec![self.title.clone()];
+        paragraphs.extend(self.text.iter().cloned());
+
+        if let Some(parts) = self.parts.as_ref() {
+            for part in parts {
+                paragraphs.push(part.title.clone());
+                paragraphs.extend(part.text.iter().cloned());
+            }
+        }
+
+        paragraphs.join("\n")
+    }
+}
+
+impl From<NewStory> for Story {
+    fn from(ns: NewStory) -> Self {
+        Story {
+            category: Category::Story,
+            title: ns.title,
+            text: ns.text,
+            parts: ns.parts,
+        }
+    }
+}
+
+/// Каждый абзац истории должен быть не короче 10 и не длиннее 5000 символов
+fn validate_paragraphs(paragraphs: &[String]) -> Result<(), validator::ValidationError> {
+    let invalid = paragraphs
+        .iter()
+        .any(|p| !(10..=5000).contains(&p.chars().count()));
+
+    if invalid {
+        Err(validator::ValidationError::new("paragraph"))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+    use validator::Validate;
+
+    #[test_case(
+        "Ёж",
+        vec!["Жил-был ёжик, который очень любил яблоки."],
+        None,
+        true ;
+        "valid"
+    )]
+    #[test_case(
+        "Ё",
+        vec!["Жил-был ёжик, который очень любил яблоки."],
+        None,
+        false ;
+        "invalid_title_short"
+    )]
+    #[test_case(
+        "Ёж",
+        vec![],
+        None,
+        false ;
+        "invalid_text_empty"
+    )]
+    #[test_case(
+        "Ёж",
+        vec!["Жил-был ёжик, который очень любил яблоки.", "Конец"],
+        None,
+        false ;
+        "invalid_paragraph_short"
+    )]
+    #[test_case(
+        "Ёж",
+        vec!["Жил-был ёжик, который очень любил яблоки."],
+        Some(vec![("Глава первая", vec!["Однажды ёжик нашёл яблоко."])]),
+        true ;
+        "valid_with_parts"
+    )]
+    #[test_case(
+        "Ёж",
+        vec!["Жил-был ёжик, который очень любил яблоки."],
+        Some(vec![("Глава первая", vec!["Ой"])]),
+        false ;
+        "invalid_part_paragraph_short"
+    )]
+    fn new_story_validation(
+        title: &str,
+        text: Vec<&str>,
+        parts: Option<Vec<(&str, Vec<&str>)>>,
+        is_valid: bool,
+    ) {
+        let ns = super::NewStory {
+            title: title.to_string(),
+            text: text.iter().map(|p| p.to_string()).collect(),
+            parts: parts.map(|parts| {
+                parts
+                    .into_iter()
+                    .map(|(title, text)| super::Part {
+                        title: title.to_string(),
+                        text: text.iter().map(|p| p.to_string()).collect(),
+                    })
+                    .collect()
+            }),
+            tags: vec![],
+            flags: vec![],
+            lang: None,
+        };
+
+        match ns.validate() {
+            Ok(_) => {
+                if is_valid {
+                    assert!(true)
+                } else {
+                    assert!(false)
+                }
+            }
+            Err(_) => {
+                if !is_valid {
+                    assert!(true)
+                } else {
+                    assert!(false)
+                }
+            }
+        }
+    }
+}
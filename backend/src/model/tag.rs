@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use validator::Validate;
+
+use crate::errors::HubError;
+
+/// Наибольшее количество тегов у записи
+pub const MAX_TAGS: usize = 10;
+
+/// Наибольшая длина тега после нормализации
+pub const MAX_TAG_LENGTH: usize = 32;
+
+/// Тег из реестра.
+/// Записи хранят только каноническое имя, синонимы (в том числе на других языках)
+/// приводятся к нему при создании записи и при фильтрации.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Tag {
+    #[serde(rename = "_id")]
+    pub name: String,
+
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+impl Tag {
+    pub fn new(name: String) -> Self {
+        Tag {
+            name,
+            aliases: Vec::new(),
+        }
+    }
+
+    /// Каноническое имя вместе со всеми синонимами
+    pub fn names(&self) -> Vec<String> {
+        let mut names = vec![self.name.clone()];
+        names.extend(self.aliases.iter().cloned());
+        names
+    }
+}
+
+/// Приведение тега к единому виду: нижний регистр,
+/// пробелы и дефисы заменяются подчеркиванием, прочие символы отбрасываются.
+pub fn normalize(tag: &str) -> String {
+    let mut result = String::new();
+
+    for c in tag.trim().to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            result.push(c);
+        } else if (c.is_whitespace() || c == '-' || c == '_') && !result.ends_with('_') {
+            result.push('_');
+        }
+    }
+
+    result.trim_matches('_').to_string()
+}
+
+/// Проверка тегов записи до их регистрации в реестре: тегов не больше `MAX_TAGS`,
+/// каждый состоит из букв, цифр, пробелов, дефисов и подчеркиваний
+/// и после нормализации не пуст и не длиннее `MAX_TAG_LENGTH`.
+pub fn validate(tags: &[String]) -> Result<(), HubError> {
+    if tags.len() > MAX_TAGS {
+        return Err(HubError::new_unprocessable(
+            "Too many tags",
+            Some(vec![format!("Limit: {}", MAX_TAGS)]),
+        ));
+    }
+
+    for tag in tags {
+        let charset = tag
+            .chars()
+            .all(|c| c.is_alphanumeric() || c.is_whitespace() || c == '-' || c == '_');
+        let length = normalize(tag).chars().count();
+
+        if !charset || length == 0 || length > MAX_TAG_LENGTH {
+            return Err(HubError::new_unprocessable(
+                "Tag is invalid",
+                Some(vec![tag.clone()]),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Приведение тегов записи к каноническим именам из `known` после проверки.
+/// Ключи `known` — нормализованные имена, неизвестные теги остаются в нормализованном виде.
+pub fn canonize(tags: &[String], known: &HashMap<String, String>) -> Result<Vec<String>, HubError> {
+    validate(tags)?;
+
+    let mut result: Vec<String> = Vec::new();
+    for tag in tags {
+        let name = normalize(tag);
+        let canonical = known.get(&name).cloned().unwrap_or(name);
+
+        if !result.contains(&canonical) {
+            result.push(canonical);
+        }
+    }
+
+    Ok(result)
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct TagInfo {
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub count: i64,
+}
+
+impl TagInfo {
+    pub fn new(tag: Tag, count: i64) -> Self {
+        TagInfo {
+            name: tag.name,
+            aliases: tag.aliases,
+            count,
+        }
+    }
+}
+
+/// Слияние тега `source` с тегом `target`.
+/// `source` и все его синонимы становятся синонимами `target`.
+#[derive(Clone, Deserialize, Validate, Debug)]
+pub struct TagMerge {
+    #[validate(length(min = 1, max = 32, message = "Lenght is invalid"))]
+    pub source: String,
+
+    #[validate(length(min = 1, max = 32, message = "Lenght is invalid"))]
+    pub target: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use test_case::test_case;
+
+    #[test_case("work", "work" ; "plain")]
+    #[test_case("  Work ", "work" ; "trim_and_case")]
+    #[test_case("Black Humor", "black_humor" ; "spaces")]
+    #[test_case("black--humor", "black_humor" ; "dashes")]
+    #[test_case("Работа!", "работа" ; "cyrillic_punctuation")]
+    #[test_case("#$%", "" ; "only_symbols")]
+    fn normalize(tag: &str, expected: &str) {
+        assert_eq!(super::normalize(tag), expected);
+    }
+
+    #[test_case(&["work", "Black Humor", "черный-юмор"], true ; "valid")]
+    #[test_case(&[], true ; "empty")]
+    #[test_case(&["work!"], false ; "punctuation")]
+    #[test_case(&["<script>"], false ; "markup")]
+    #[test_case(&["  "], false ; "blank")]
+    #[test_case(&["a_very_long_tag_that_does_not_fit_at_all"], false ; "too_long")]
+    #[test_case(&["a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k"], false ; "too_many")]
+    fn validate(tags: &[&str], valid: bool) {
+        let tags: Vec<String> = tags.iter().map(|tag| tag.to_string()).collect();
+        assert_eq!(super::validate(&tags).is_ok(), valid);
+    }
+
+    #[test_case(&["Black Humor", "dark"], &["black_humor", "dark"] ; "unknown_normalized")]
+    #[test_case(&["Work", "работа"], &["work"] ; "alias_to_canonical")]
+    #[test_case(&["work", "WORK"], &["work"] ; "deduplicated")]
+    fn canonize(tags: &[&str], expected: &[&str]) {
+        let tags: Vec<String> = tags.iter().map(|tag| tag.to_string()).collect();
+        let known = HashMap::from([
+            (String::from("work"), String::from("work")),
+            (String::from("работа"), String::from("work")),
+        ]);
+
+        assert_eq!(super::canonize(&tags, &known).unwrap(), expected);
+    }
+}
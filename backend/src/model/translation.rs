@@ -0,0 +1,76 @@
+use mongodb::bson::DateTime as MongoDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::shrimp::Category;
+use crate::errors::HubError;
+
+/// Группа переводов: записи одной категории с одним и тем же содержанием на разных языках.
+/// В группе может быть только одна запись на каждом языке.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Translation {
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub category: Category,
+    pub records: Vec<String>,
+    pub created_at: MongoDateTime,
+}
+
+impl Translation {
+    pub fn new(category: Category, records: Vec<String>) -> Self {
+        Translation {
+            id: Uuid::new_v4().to_string(),
+            category,
+            records,
+            created_at: MongoDateTime::now(),
+        }
+    }
+
+    /// Проверка, что в группе нет двух записей на одном языке
+    pub fn check(siblings: &[Sibling]) -> Result<(), HubError> {
+        for (i, sibling) in siblings.iter().enumerate() {
+            if let Some(other) = siblings[..i]
+                .iter()
+                .find(|other| other.language == sibling.language)
+            {
+                return Err(HubError::new_unprocessable(
+                    "Translation in this language already exists",
+                    Some(vec![format!(
+                        "Records {} and {} are both in '{}'",
+                        other.id, sibling.id, sibling.language
+                    )]),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Запись группы переводов и ее язык
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct Sibling {
+    pub id: String,
+    pub language: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::{Sibling, Translation};
+
+    fn sibling(id: &str, language: &str) -> Sibling {
+        Sibling {
+            id: id.to_string(),
+            language: language.to_string(),
+        }
+    }
+
+    #[test_case(vec![sibling("a", "ru"), sibling("b", "en")], true ; "different_languages")]
+    #[test_case(vec![sibling("a", "ru"), sibling("b", "en"), sibling("c", "ru")], false ; "same_language")]
+    #[test_case(vec![], true ; "empty")]
+    fn check(siblings: Vec<Sibling>, expected: bool) {
+        assert_eq!(Translation::check(&siblings).is_ok(), expected);
+    }
+}
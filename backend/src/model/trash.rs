@@ -0,0 +1,69 @@
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::env;
+
+/// Срок хранения записей в корзине по умолчанию, если не задана переменная `TRASH_RETENTION_DAYS`
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+
+/// Отметка об удалении записи.
+/// Запись в корзине не видна потребителям, но модератор может ее восстановить,
+/// пока не истек срок хранения.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Trash {
+    /// Время удаления в миллисекундах
+    pub deleted_at: i64,
+    pub deleted_by: String,
+}
+
+impl Trash {
+    pub fn new(deleted_by: &str) -> Self {
+        Trash {
+            deleted_at: Utc::now().timestamp_millis(),
+            deleted_by: deleted_by.to_string(),
+        }
+    }
+
+    /// Срок хранения записей в корзине
+    pub fn retention() -> Duration {
+        Duration::days(retention_days(
+            env::var("TRASH_RETENTION_DAYS").ok().as_deref(),
+        ))
+    }
+
+    /// Записи, удаленные раньше этого момента (в миллисекундах), удаляются окончательно
+    pub fn expired_before(now: i64, retention: Duration) -> i64 {
+        now - retention.num_milliseconds()
+    }
+}
+
+/// Срок хранения в днях, неверное или непозитивное значение заменяется значением по умолчанию
+fn retention_days(value: Option<&str>) -> i64 {
+    match value.and_then(|v| v.trim().parse::<i64>().ok()) {
+        Some(days) if days > 0 => days,
+        _ => DEFAULT_RETENTION_DAYS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use test_case::test_case;
+
+    use super::{retention_days, Trash};
+
+    #[test_case(None, 30 ; "default")]
+    #[test_case(Some("7"), 7 ; "configured")]
+    #[test_case(Some("0"), 30 ; "zero")]
+    #[test_case(Some("week"), 30 ; "invalid")]
+    fn retention(value: Option<&str>, expected: i64) {
+        assert_eq!(retention_days(value), expected);
+    }
+
+    #[test]
+    fn expired_before() {
+        assert_eq!(
+            Trash::expired_before(86_400_000 * 3, Duration::days(2)),
+            86_400_000
+        );
+    }
+}
@@ -183,6 +183,18 @@ pub fn change_theme<'f>(
     User::change_theme(client.0.as_ref(), theme_name, &_auth.0.get_username())
 }
 
+/// Публичное имя и описание автора, которые видны в его профиле
+#[put("/account/profile", data = "<jep>")]
+pub fn change_profile<'f>(
+    _auth: AuthGuard,
+    client: MongoConn<'f>,
+    jep: Json<EditProfile>,
+) -> Result<(), HubError> {
+    jep.0.validate()?;
+
+    User::change_profile(client.0.as_ref(), &jep.0, _auth.0.get_username_as_str())
+}
+
 #[post("/account/logout", data = "<jrt>")]
 pub fn logout<'f>(
     _auth: AuthGuard,
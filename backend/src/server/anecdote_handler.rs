@@ -1,10 +1,11 @@
 use mongodb::bson::doc;
 use rocket::serde::json::Json;
 use serde_json::{json, Value};
+use validator::Validate;
 
 use crate::{
     db::mongo::{varys::Varys, Crud, MongoConn},
-    err_not_found,
+    err_forbidden,
     errors::HubError,
     model::{
         account::{
@@ -12,10 +13,14 @@ use crate::{
             Tariff,
         },
         anecdote::*,
-        shrimp::{Flags, Shrimp, Tail},
+        language,
+        shrimp::{revision::Revision, Flags, Shrimp, Tail},
+        tag::Tag,
+        translation::Translation,
+        trash::Trash,
         validation::uuid_validation,
     },
-    server::lingua::Lingua,
+    server::{lexicon::Lexicons, lingua::Lingua},
     shrimp_reaction_handler,
 };
 
@@ -30,35 +35,46 @@ pub async fn create_anecdote<'f>(
     _auth: AuthGuard,
     client: MongoConn<'f>,
     lingua: Lingua<'f>,
+    lexicons: Lexicons<'f>,
     jna: Json<NewAnecdote>,
 ) -> Result<Value, HubError> {
+    let (lang, detection) = lingua.detected(jna.0.text.clone(), jna.0.lang.as_deref())?;
     let tail = Tail::new(
-        Flags::default(),
-        lingua.detected(jna.clone().0.text)?,
+        Flags::from(&jna.0.flags),
+        lang,
         _auth.0.get_username(),
-        &jna.0.tags,
-    );
+        &Tag::resolve(client.0.as_ref(), &jna.0.tags)?,
+    )
+    .detection(detection)
+    .suggest(lexicons.get()?.suggest(&jna.0.text));
     let body = Anecdote::from(jna.0);
 
-    let result = Shrimp::create(
-        Varys::get(client.0.as_ref(), Varys::Anecdote),
-        &Shrimp::new(body, tail),
-    )?;
+    let (result, similar) = Shrimp::new(body, tail).insert(client.0.as_ref())?;
 
-    let resp = json!({"id": result.inserted_id});
+    let resp = json!({"id": result.inserted_id, "similar": similar});
     Ok(resp)
 }
 
-#[get("/anecdote/<id>")]
+/// С параметром `lang` отдается перевод записи на этот язык, если он есть
+#[get("/anecdote/<id>?<lang>")]
 pub async fn get_anecdote<'f>(
     _api_key: ApiKeyGuard,
     client: MongoConn<'f>,
     id: &str,
+    lang: Option<&str>,
 ) -> Result<Value, HubError> {
-    let result: Shrimp<Anecdote> = Shrimp::get_by_id(
-        Varys::get(client.0.as_ref(), Varys::Anecdote),
-        uuid_validation(id)?,
-    )?;
+    let id = match lang {
+        Some(lang) => Translation::resolve(
+            client.0.as_ref(),
+            &Category::Anecdote,
+            uuid_validation(id)?,
+            &language::validate(lang)?,
+        )?,
+        None => uuid_validation(id)?.to_string(),
+    };
+
+    let result: Shrimp<Anecdote> =
+        Shrimp::get_by_id(Varys::get(client.0.as_ref(), Varys::Anecdote), &id)?;
 
     match _api_key.0 {
         Some(data) => Ok(result.tariffing(&data.get_tariff(), &None)),
@@ -66,21 +82,50 @@ pub async fn get_anecdote<'f>(
     }
 }
 
+#[put("/anecdote/<id>", data = "<jna>")]
+pub async fn update_anecdote<'f>(
+    _auth: AuthGuard,
+    client: MongoConn<'f>,
+    lingua: Lingua<'f>,
+    lexicons: Lexicons<'f>,
+    id: &str,
+    jna: Json<NewAnecdote>,
+) -> Result<(), HubError> {
+    jna.0.validate()?;
+
+    let id = uuid_validation(id)?;
+    let author = Category::Anecdote.record_author(client.0.as_ref(), id)?;
+
+    if !_auth.0.can_manage(&author) {
+        return Err(err_forbidden!());
+    }
+
+    let (lang, detection) = lingua.detected(jna.0.text.clone(), jna.0.lang.as_deref())?;
+
+    Revision::revise(
+        client.0.as_ref(),
+        lexicons.get()?.as_ref(),
+        Category::Anecdote,
+        id,
+        _auth.0.get_username_as_str(),
+        bson::to_document(&Anecdote::from(jna.0.clone()))?,
+        &language::code(&lang),
+        Some(&detection),
+        &jna.0.tags,
+    )
+}
+
+/// Запись попадает в корзину, откуда ее может восстановить модератор
 #[delete("/anecdote/<id>")]
 pub async fn delete_anecdote<'f>(
     _level: LevelGuard,
     client: MongoConn<'f>,
     id: &str,
 ) -> Result<(), HubError> {
-    Shrimp::<Anecdote>::del_by_id(
-        Varys::get(client.0.as_ref(), Varys::Anecdote),
+    Trash::put(
+        client.0.as_ref(),
+        &Category::Anecdote,
         uuid_validation(id)?,
+        _level.0.get_username_as_str(),
     )
-    .and_then(|d_result| {
-        if d_result.deleted_count < 1 {
-            Err(err_not_found!("anecdote"))
-        } else {
-            Ok(())
-        }
-    })
 }
@@ -0,0 +1,59 @@
+use serde_json::{json, Value};
+
+use crate::{
+    db::mongo::{
+        shrimp::aggregation::{self, Pagination, Qilter},
+        MongoConn,
+    },
+    errors::HubError,
+    model::{
+        account::{security::ApiKeyGuard, Tariff, User},
+        author::{AuthorProfile, AuthorStats},
+        shrimp::{Category, SortKey, SortOrder, Status},
+    },
+};
+
+/// Публичный профиль автора со статистикой и страницей его одобренных записей, от новых к старым
+#[get("/authors/<username>?<page>&<cursor>&<limit>")]
+pub fn author<'f>(
+    _api_key: ApiKeyGuard,
+    client: MongoConn<'f>,
+    username: &str,
+    page: Option<u64>,
+    cursor: Option<&str>,
+    limit: Option<i64>,
+) -> Result<Value, HubError> {
+    let user = User::get_by_username(client.0.as_ref(), username.to_string())?;
+    let stats = AuthorStats::collect(client.0.as_ref(), &user.username)?;
+
+    let tariff: Tariff = match _api_key.0 {
+        Some(data) => data.get_tariff(),
+        None => Tariff::default(),
+    };
+    let limit = limit
+        .unwrap_or_else(|| tariff.page_limit())
+        .clamp(1, tariff.page_limit());
+    let pagination = Pagination::new(SortKey::Timestamp, SortOrder::Desc, limit, page, cursor)?;
+
+    let mut stages = Qilter::new(Some(&user.username), None, None, None).stages();
+    stages.push(Qilter::status(&Status::Approved));
+
+    let mut records = aggregation::union(
+        client.0.as_ref(),
+        &Category::all(),
+        stages,
+        pagination.stages(),
+    )?;
+    let next = pagination.finish(&mut records);
+
+    let mut result: Vec<Value> = Vec::new();
+    for record in records {
+        result.push(Category::tariffing(record, &tariff)?);
+    }
+
+    Ok(json!({
+        "author": AuthorProfile::new(user, stats),
+        "records": result,
+        "next": next
+    }))
+}
@@ -0,0 +1,177 @@
+use chrono::Utc;
+use rocket::serde::json::Json;
+use serde_json::{json, Value};
+use validator::Validate;
+
+use crate::{
+    db::mongo::{varys::Varys, Crud, MongoConn},
+    err_forbidden,
+    errors::HubError,
+    model::{
+        account::{
+            notification::{self, Notification, NotifyKind},
+            security::{ApiKeyGuard, AuthGuard, IdentityGuard},
+            Tariff,
+        },
+        comment::{Comment, CommentInfo, EditComment, NewComment},
+        shrimp::{Category, ReactionKind},
+        validation::uuid_validation,
+    },
+    VectorConvert,
+};
+
+/// Длина отрывка комментария в уведомлении автору записи
+const EXCERPT: usize = 100;
+
+/// Комментарий к одобренной записи или ответ на комментарий из `parent_id`.
+/// Автор записи получает уведомление о новом комментарии.
+#[post("/<category>/<id>/comments", data = "<jnc>", rank = 2)]
+pub fn comment_add<'f>(
+    _auth: AuthGuard,
+    client: MongoConn<'f>,
+    category: Category,
+    id: &str,
+    jnc: Json<NewComment>,
+) -> Result<Json<CommentInfo>, HubError> {
+    jnc.0.validate()?;
+
+    let id = uuid_validation(id)?;
+    let author = category.published_author(client.0.as_ref(), id)?;
+    let username = _auth.0.get_username_as_str();
+
+    let comment = Comment::publish(client.0.as_ref(), category.clone(), id, username, &jnc.0)?;
+
+    if author != username {
+        let ntf = Notification::new(
+            username,
+            &author,
+            NotifyKind::General,
+            notification::Body::new(
+                format!(
+                    "New comment on your {}",
+                    category.to_string().to_lowercase()
+                )
+                .as_str(),
+                Some(comment.text.chars().take(EXCERPT).collect()),
+                Some(vec![notification::Action {
+                    kind: notification::ActionKind::Major,
+                    text: String::from("Open"),
+                    href: format!(
+                        "/v1/{}/{}/comments",
+                        category.to_string().to_lowercase(),
+                        id
+                    ),
+                }]),
+            ),
+        );
+
+        Notification::create(Varys::get(client.0.as_ref(), Varys::Notification), &ntf)?;
+    }
+
+    Ok(Json(comment.into()))
+}
+
+/// Комментарии верхнего уровня к одобренной записи или ответы на комментарий `parent`.
+/// Страница задается курсором `cursor` из предыдущего ответа, ее размер ограничен тарифом.
+#[get("/<category>/<id>/comments?<parent>&<cursor>&<limit>")]
+pub fn comments<'f>(
+    _api_key: ApiKeyGuard,
+    client: MongoConn<'f>,
+    category: Category,
+    id: &str,
+    parent: Option<&str>,
+    cursor: Option<&str>,
+    limit: Option<i64>,
+) -> Result<Value, HubError> {
+    let id = uuid_validation(id)?;
+    category.published_author(client.0.as_ref(), id)?;
+
+    let parent = match parent {
+        Some(parent) => Some(uuid_validation(parent)?),
+        None => None,
+    };
+
+    let tariff: Tariff = match _api_key.0 {
+        Some(data) => data.get_tariff(),
+        None => Tariff::default(),
+    };
+    let limit = limit
+        .unwrap_or_else(|| tariff.page_limit())
+        .clamp(1, tariff.page_limit());
+
+    let (comments, next) = Comment::page(client.0.as_ref(), id, parent, cursor, limit)?;
+    let comments: Vec<CommentInfo> = Vec::convert(comments);
+
+    Ok(json!({"comments": comments, "next": next}))
+}
+
+/// Изменить комментарий может только его автор, пока не истекло время на правку
+#[put("/comments/<comment_id>", data = "<jec>")]
+pub fn comment_edit<'f>(
+    _auth: AuthGuard,
+    client: MongoConn<'f>,
+    comment_id: &str,
+    jec: Json<EditComment>,
+) -> Result<(), HubError> {
+    jec.0.validate()?;
+
+    let comment = published_comment(&client, comment_id)?;
+
+    if comment.author != _auth.0.get_username_as_str() {
+        return Err(err_forbidden!());
+    }
+
+    comment.check_edit_window(Utc::now().timestamp_millis())?;
+    comment.edit(client.0.as_ref(), &jec.0.text)
+}
+
+/// Удалить комментарий может его автор или модератор
+#[delete("/comments/<comment_id>")]
+pub fn comment_delete<'f>(
+    _auth: AuthGuard,
+    client: MongoConn<'f>,
+    comment_id: &str,
+) -> Result<(), HubError> {
+    let comment = Comment::find(client.0.as_ref(), uuid_validation(comment_id)?)?;
+
+    if !_auth.0.can_manage(&comment.author) {
+        return Err(err_forbidden!());
+    }
+
+    comment.remove(client.0.as_ref())
+}
+
+/// Установка реакции на комментарий. Повторный запрос с другой реакцией заменяет прежнюю.
+#[post("/comments/<comment_id>/reaction/<reaction_kind>")]
+pub fn comment_reaction<'f>(
+    _identity: IdentityGuard,
+    client: MongoConn<'f>,
+    comment_id: &str,
+    reaction_kind: ReactionKind,
+) -> Result<(), HubError> {
+    published_comment(&client, comment_id)?.react(client.0.as_ref(), &_identity.0, reaction_kind)
+}
+
+#[delete("/comments/<comment_id>/reaction", rank = 2)]
+pub fn comment_reaction_remove<'f>(
+    _identity: IdentityGuard,
+    client: MongoConn<'f>,
+    comment_id: &str,
+) -> Result<(), HubError> {
+    Comment::unreact(
+        client.0.as_ref(),
+        uuid_validation(comment_id)?,
+        &_identity.0,
+    )
+}
+
+/// Комментарий к записи, которая одобрена и не лежит в корзине:
+/// комментарии к остальным записям скрыты так же, как в `comments`
+fn published_comment(client: &MongoConn, comment_id: &str) -> Result<Comment, HubError> {
+    let comment = Comment::find(client.0.as_ref(), uuid_validation(comment_id)?)?;
+    comment
+        .category
+        .published_author(client.0.as_ref(), &comment.record_id)?;
+
+    Ok(comment)
+}
@@ -1,12 +1,16 @@
-use mongodb::bson::doc;
-// use rocket::serde::json::Json;
+use mongodb::bson::{doc, Document};
+use serde_json::{json, Value};
+use std::collections::HashMap;
 
 use crate::{
-    db::mongo::MongoConn,
-    db::mongo::{varys::Varys, Crud},
+    db::mongo::{
+        shrimp::aggregation::{self, Qilter},
+        MongoConn,
+    },
     errors::HubError,
     model::{
-        account::{favorites::*, security::AuthGuard},
+        account::{favorites::*, security::AuthGuard, Tariff},
+        shrimp::{Category, Status},
         validation::uuid_validation,
     },
 };
@@ -17,14 +21,12 @@ pub fn favorite_add<'f>(
     client: MongoConn<'f>,
     record_id: &str,
 ) -> Result<(), HubError> {
-    let fv = Favorite::new(
-        uuid_validation(record_id)?.to_string(),
-        _auth.0.get_username(),
-    );
+    let record_id = uuid_validation(record_id)?;
 
-    Favorite::create(Varys::get(client.0.as_ref(), Varys::Favorite), &fv)?;
+    // Добавить можно только одобренную запись любой категории
+    Category::locate(client.0.as_ref(), record_id)?;
 
-    Ok(())
+    Favorite::new(record_id.to_string(), _auth.0.get_username()).add(client.0.as_ref())
 }
 
 #[delete("/account/favorite/<record_id>")]
@@ -33,14 +35,56 @@ pub fn favorite_remove<'f>(
     client: MongoConn<'f>,
     record_id: &str,
 ) -> Result<(), HubError> {
-    Favorite::del_by_record_id(client.0.as_ref(), record_id)
+    Favorite::del_by_record_id(
+        client.0.as_ref(),
+        _auth.0.get_username_as_str(),
+        uuid_validation(record_id)?,
+    )
 }
 
+/// Избранные записи пользователя с их категориями, от последних добавленных.
+/// Записи, которые успели попасть в корзину или вернуться на модерацию, пропускаются.
+#[get("/account/favorite?<page>&<limit>")]
+pub fn favorite_all<'f>(
+    _auth: AuthGuard,
+    client: MongoConn<'f>,
+    page: Option<u64>,
+    limit: Option<i64>,
+) -> Result<Value, HubError> {
+    let tariff: Tariff = _auth.0.get_tariff();
+    let limit = limit
+        .unwrap_or_else(|| tariff.page_limit())
+        .clamp(1, tariff.page_limit());
+
+    let favorites = Favorite::roll(
+        client.0.as_ref(),
+        _auth.0.get_username_as_str(),
+        page.unwrap_or(1),
+        limit,
+    )?;
+    let ids: Vec<&str> = favorites.iter().map(|f| f.content_id.as_str()).collect();
+
+    let stages = vec![
+        Qilter::alive(),
+        Qilter::status(&Status::Approved),
+        doc! {"$match": {"_id": {"$in": ids}}},
+    ];
+    let mut records: HashMap<String, Document> =
+        aggregation::union(client.0.as_ref(), &Category::all(), stages, Vec::new())?
+            .into_iter()
+            .filter_map(|record| Some((record.get_str("_id").ok()?.to_string(), record)))
+            .collect();
 
-// pub fn favorite_all<'f>(
-//     _auth: AuthGuard,
-//     client: MongoConn<'f>,
-//     record_id: &str,
-// ) -> Result<Json<Vec<Favorite>>, HubError> {
+    let mut result: Vec<Value> = Vec::new();
+    for favorite in favorites {
+        if let Some(record) = records.remove(&favorite.content_id) {
+            result.push(json!({
+                "category": record.get_str("category").unwrap_or_default(),
+                "added_at": favorite.added_at.to_rfc3339_string(),
+                "record": Category::tariffing(record, &tariff)?
+            }));
+        }
+    }
 
-// }
+    Ok(json!({ "favorites": result }))
+}
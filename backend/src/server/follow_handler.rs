@@ -0,0 +1,127 @@
+use serde_json::{json, Value};
+
+use crate::{
+    db::mongo::{
+        shrimp::aggregation::{self, Pagination, Qilter},
+        MongoConn,
+    },
+    errors::HubError,
+    model::{
+        account::{
+            security::{ApiKeyGuard, AuthGuard},
+            Tariff, User,
+        },
+        follow::{Follow, FollowInfo},
+        shrimp::{Category, Flag, SortKey, SortOrder, Status},
+    },
+};
+
+#[put("/account/following/<username>")]
+pub fn follow<'f>(_auth: AuthGuard, client: MongoConn<'f>, username: &str) -> Result<(), HubError> {
+    Follow::new(_auth.0.get_username_as_str(), username)?.subscribe(client.0.as_ref())
+}
+
+#[delete("/account/following/<username>")]
+pub fn unfollow<'f>(
+    _auth: AuthGuard,
+    client: MongoConn<'f>,
+    username: &str,
+) -> Result<(), HubError> {
+    Follow::unsubscribe(client.0.as_ref(), _auth.0.get_username_as_str(), username)
+}
+
+/// Новые записи авторов из подписок, от последних одобренных к старым.
+/// Страница задается курсором `cursor` из предыдущего ответа.
+#[get("/feed?<cursor>&<limit>&<flag>&<tag>&<lang>")]
+pub fn feed<'f>(
+    _auth: AuthGuard,
+    client: MongoConn<'f>,
+    cursor: Option<&str>,
+    limit: Option<i64>,
+    flag: Option<Vec<Flag>>,
+    tag: Option<Vec<&str>>,
+    lang: Option<&str>,
+) -> Result<Value, HubError> {
+    let tariff: Tariff = _auth.0.get_tariff();
+    let limit = limit
+        .unwrap_or_else(|| tariff.page_limit())
+        .clamp(1, tariff.page_limit());
+    let pagination = Pagination::new(SortKey::Published, SortOrder::Desc, limit, None, cursor)?;
+
+    let following = Follow::following(client.0.as_ref(), _auth.0.get_username_as_str())?;
+    if following.is_empty() {
+        return Ok(json!({"records": [], "next": null}));
+    }
+
+    let mut stages = Qilter::new(None, lang, flag, tag)
+        .authors(following)
+        .resolve(client.0.as_ref())?
+        .stages();
+    stages.push(Qilter::status(&Status::Approved));
+
+    let mut records = aggregation::union(
+        client.0.as_ref(),
+        &Category::all(),
+        stages,
+        pagination.stages(),
+    )?;
+    let next = pagination.finish(&mut records);
+
+    let mut result: Vec<Value> = Vec::new();
+    for record in records {
+        result.push(Category::tariffing(record, &tariff)?);
+    }
+
+    Ok(json!({"records": result, "next": next}))
+}
+
+#[get("/authors/<username>/followers?<page>&<limit>")]
+pub fn followers<'f>(
+    _api_key: ApiKeyGuard,
+    client: MongoConn<'f>,
+    username: &str,
+    page: Option<u64>,
+    limit: Option<i64>,
+) -> Result<Value, HubError> {
+    let (user, page, limit) = follow_page(&_api_key, &client, username, page, limit)?;
+    let result = Follow::page(client.0.as_ref(), "author", &user.username, page, limit)?;
+
+    let result: Vec<FollowInfo> = result.into_iter().map(FollowInfo::follower).collect();
+    Ok(json!({ "followers": result }))
+}
+
+#[get("/authors/<username>/following?<page>&<limit>")]
+pub fn following<'f>(
+    _api_key: ApiKeyGuard,
+    client: MongoConn<'f>,
+    username: &str,
+    page: Option<u64>,
+    limit: Option<i64>,
+) -> Result<Value, HubError> {
+    let (user, page, limit) = follow_page(&_api_key, &client, username, page, limit)?;
+    let result = Follow::page(client.0.as_ref(), "follower", &user.username, page, limit)?;
+
+    let result: Vec<FollowInfo> = result.into_iter().map(FollowInfo::author).collect();
+    Ok(json!({ "following": result }))
+}
+
+/// Проверка автора и размер страницы списка по тарифу
+fn follow_page(
+    _api_key: &ApiKeyGuard,
+    client: &MongoConn,
+    username: &str,
+    page: Option<u64>,
+    limit: Option<i64>,
+) -> Result<(User, u64, i64), HubError> {
+    let user = User::get_by_username(client.0.as_ref(), username.to_string())?;
+
+    let tariff: Tariff = match _api_key.0.as_ref() {
+        Some(data) => data.get_tariff(),
+        None => Tariff::default(),
+    };
+    let limit = limit
+        .unwrap_or_else(|| tariff.page_limit())
+        .clamp(1, tariff.page_limit());
+
+    Ok((user, page.unwrap_or(1), limit))
+}
@@ -0,0 +1,115 @@
+use rocket::{
+    data::ToByteUnit,
+    http::{ContentType, Header},
+    serde::json::Json,
+    Data,
+};
+
+use crate::{
+    db::mongo::{shrimp::aggregation::Qilter, MongoConn},
+    errors::HubError,
+    model::{
+        account::{security::LevelGuard, User},
+        fortune::{Fortune, FortuneFile},
+        import::{self, ImportFormat, ImportReport},
+        shrimp::{Category, Flag},
+    },
+    server::{lexicon::Lexicons, lingua::Lingua},
+};
+
+/// Массовый импорт анекдотов, шуток и панчей из JSON Lines, CSV или файла fortune.
+/// Ошибки разбора и валидации собираются по строкам и не прерывают импорт.
+/// Записи fortune получают категорию `category` (joke по умолчанию) и теги `tag`.
+#[allow(clippy::too_many_arguments)]
+#[post("/import?<format>&<category>&<tag>&<author>", data = "<data>")]
+pub async fn import_records<'f>(
+    _level: LevelGuard,
+    client: MongoConn<'f>,
+    lingua: Lingua<'f>,
+    lexicons: Lexicons<'f>,
+    format: Option<ImportFormat>,
+    category: Option<Category>,
+    tag: Option<Vec<String>>,
+    author: Option<&str>,
+    data: Data<'_>,
+) -> Result<Json<ImportReport>, HubError> {
+    let data = data
+        .open(8.mebibytes())
+        .into_string()
+        .await
+        .map_err(|err| {
+            HubError::new_unprocessable("Faild to read file", Some(vec![err.to_string()]))
+        })?;
+
+    if !data.is_complete() {
+        return Err(HubError::new_unprocessable("File is too large", None));
+    }
+
+    let rows = import::parse(
+        &data,
+        &format.unwrap_or_default(),
+        &category.unwrap_or(Category::Joke),
+        &tag.unwrap_or_default(),
+    )?;
+
+    // Записи можно импортировать только от имени существующего пользователя
+    let author = match author {
+        Some(author) => User::get_by_username(client.0.as_ref(), author.to_string())?.username,
+        None => _level.0.get_username(),
+    };
+
+    let report = ImportReport::run(
+        client.0.as_ref(),
+        lingua.0,
+        lexicons.get()?.as_ref(),
+        &author,
+        rows,
+    )?;
+
+    Ok(Json(report))
+}
+
+/// Файл для скачивания
+#[derive(Responder)]
+pub struct Attachment {
+    inner: (ContentType, Vec<u8>),
+    disposition: Header<'static>,
+}
+
+impl Attachment {
+    pub fn new(content: Vec<u8>, content_type: ContentType, name: &str) -> Self {
+        Attachment {
+            inner: (content_type, content),
+            disposition: Header::new(
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", name),
+            ),
+        }
+    }
+}
+
+/// Экспорт записей в файл fortune, с `file=dat` — индекс strfile к нему.
+/// Оба файла строятся одной выборкой по одному и тому же фильтру.
+#[allow(clippy::too_many_arguments)]
+#[get("/export/fortune?<file>&<category>&<flag>&<tag>&<author>&<lang>")]
+pub fn export_fortune<'f>(
+    _level: LevelGuard,
+    client: MongoConn<'f>,
+    file: Option<FortuneFile>,
+    category: Option<Vec<Category>>,
+    flag: Option<Vec<Flag>>,
+    tag: Option<Vec<&str>>,
+    author: Option<&str>,
+    lang: Option<&str>,
+) -> Result<Attachment, HubError> {
+    let categories = category.unwrap_or(vec![Category::Anecdote, Category::Joke, Category::Punch]);
+    let qilter = Qilter::new(author, lang, flag, tag).resolve(client.0.as_ref())?;
+    let fortune = Fortune::select(client.0.as_ref(), &categories, &qilter)?;
+
+    Ok(match file.unwrap_or_default() {
+        FortuneFile::Text => {
+            Attachment::new(fortune.text.into_bytes(), ContentType::Plain, "jokehub")
+        }
+        FortuneFile::Dat => Attachment::new(fortune.dat, ContentType::Binary, "jokehub.dat"),
+    })
+}
@@ -1,17 +1,22 @@
 use mongodb::bson::doc;
 use rocket::serde::json::Json;
 use serde_json::{json, Value};
+use validator::Validate;
 
 use crate::model::account::Tariff;
-use crate::server::lingua::Lingua;
+use crate::server::{lexicon::Lexicons, lingua::Lingua};
 use crate::{
     db::mongo::{varys::Varys, Crud, MongoConn},
-    err_not_found,
+    err_forbidden,
     errors::HubError,
     model::{
         account::security::{AuthGuard, LevelGuard},
         joke::*,
-        shrimp::{Flags, Shrimp, Tail},
+        language,
+        shrimp::{revision::Revision, Flags, Shrimp, Tail},
+        tag::Tag,
+        translation::Translation,
+        trash::Trash,
         validation::uuid_validation,
     },
     shrimp_reaction_handler,
@@ -28,36 +33,46 @@ pub async fn create_joke<'f>(
     _auth: AuthGuard,
     client: MongoConn<'f>,
     lingua: Lingua<'f>,
+    lexicons: Lexicons<'f>,
     jnj: Json<NewJoke>,
 ) -> Result<Value, HubError> {
+    let (lang, detection) = lingua.detected(jnj.0.text.clone(), jnj.0.lang.as_deref())?;
     let tail = Tail::new(
-        Flags::default(),
-        lingua.detected(jnj.clone().0.text)?,
+        Flags::from(&jnj.0.flags),
+        lang,
         _auth.0.get_username(),
-        &jnj.0.tags,
-    );
+        &Tag::resolve(client.0.as_ref(), &jnj.0.tags)?,
+    )
+    .detection(detection)
+    .suggest(lexicons.get()?.suggest(&jnj.0.text));
 
     let body = Joke::from(jnj.0);
 
-    let result = Shrimp::create(
-        Varys::get(client.0.as_ref(), Varys::Joke),
-        &Shrimp::new(body, tail),
-    )?;
-    let resp = json!({"id": result.inserted_id});
+    let (result, similar) = Shrimp::new(body, tail).insert(client.0.as_ref())?;
+    let resp = json!({"id": result.inserted_id, "similar": similar});
 
     Ok(resp)
 }
 
-#[get("/joke/<id>")]
+/// С параметром `lang` отдается перевод записи на этот язык, если он есть
+#[get("/joke/<id>?<lang>")]
 pub async fn get_joke<'f>(
     _api_key: ApiKeyGuard,
     client: MongoConn<'f>,
     id: &str,
+    lang: Option<&str>,
 ) -> Result<Value, HubError> {
-    let result: Shrimp<Joke> = Shrimp::get_by_id(
-        Varys::get(client.0.as_ref(), Varys::Joke),
-        uuid_validation(id)?,
-    )?;
+    let id = match lang {
+        Some(lang) => Translation::resolve(
+            client.0.as_ref(),
+            &Category::Joke,
+            uuid_validation(id)?,
+            &language::validate(lang)?,
+        )?,
+        None => uuid_validation(id)?.to_string(),
+    };
+
+    let result: Shrimp<Joke> = Shrimp::get_by_id(Varys::get(client.0.as_ref(), Varys::Joke), &id)?;
 
     match _api_key.0 {
         Some(data) => Ok(result.tariffing(&data.get_tariff(), &None)),
@@ -65,21 +80,50 @@ pub async fn get_joke<'f>(
     }
 }
 
+#[put("/joke/<id>", data = "<jnj>")]
+pub async fn update_joke<'f>(
+    _auth: AuthGuard,
+    client: MongoConn<'f>,
+    lingua: Lingua<'f>,
+    lexicons: Lexicons<'f>,
+    id: &str,
+    jnj: Json<NewJoke>,
+) -> Result<(), HubError> {
+    jnj.0.validate()?;
+
+    let id = uuid_validation(id)?;
+    let author = Category::Joke.record_author(client.0.as_ref(), id)?;
+
+    if !_auth.0.can_manage(&author) {
+        return Err(err_forbidden!());
+    }
+
+    let (lang, detection) = lingua.detected(jnj.0.text.clone(), jnj.0.lang.as_deref())?;
+
+    Revision::revise(
+        client.0.as_ref(),
+        lexicons.get()?.as_ref(),
+        Category::Joke,
+        id,
+        _auth.0.get_username_as_str(),
+        bson::to_document(&Joke::from(jnj.0.clone()))?,
+        &language::code(&lang),
+        Some(&detection),
+        &jnj.0.tags,
+    )
+}
+
+/// Запись попадает в корзину, откуда ее может восстановить модератор
 #[delete("/joke/<id>")]
 pub async fn delete_joke<'f>(
     _level: LevelGuard,
     client: MongoConn<'f>,
     id: &str,
 ) -> Result<(), HubError> {
-    Shrimp::<Joke>::del_by_id(
-        Varys::get(client.0.as_ref(), Varys::Joke),
+    Trash::put(
+        client.0.as_ref(),
+        &Category::Joke,
         uuid_validation(id)?,
+        _level.0.get_username_as_str(),
     )
-    .and_then(|d_result| {
-        if d_result.deleted_count < 1 {
-            Err(err_not_found!("joke"))
-        } else {
-            Ok(())
-        }
-    })
 }
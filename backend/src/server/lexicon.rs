@@ -0,0 +1,68 @@
+use std::sync::{Arc, RwLock};
+
+use rocket::{
+    outcome::Outcome,
+    request::{self, FromRequest},
+    Build, Request, Rocket, State,
+};
+
+use crate::{err_internal, errors::HubError, model::lexicon::Lexicon};
+
+pub struct Lexicons<'a>(pub &'a State<RwLock<Arc<Lexicon>>>);
+
+impl<'a> Lexicons<'a> {
+    /// Текущие словари; перечитывание словарей не затрагивает уже выданные
+    pub fn get(&self) -> Result<Arc<Lexicon>, HubError> {
+        match self.0.read() {
+            Ok(lexicon) => Ok(Arc::clone(&lexicon)),
+            Err(_) => Err(err_internal!("Faild to read lexicons")),
+        }
+    }
+
+    /// Повторное чтение словарей из каталога без перезапуска сервера
+    pub fn reload(&self) -> Result<Arc<Lexicon>, HubError> {
+        let lexicon = Arc::new(Lexicon::load(&Lexicon::dir())?);
+
+        match self.0.write() {
+            Ok(mut current) => *current = Arc::clone(&lexicon),
+            Err(_) => return Err(err_internal!("Faild to update lexicons")),
+        }
+
+        Ok(lexicon)
+    }
+}
+
+pub trait LexiconManage {
+    fn manage_lexicon(self) -> Self;
+}
+
+impl LexiconManage for Rocket<Build> {
+    fn manage_lexicon(self) -> Self {
+        // Без словарей сервер работает, но флаги не предлагаются
+        let lexicon = match Lexicon::load(&Lexicon::dir()) {
+            Ok(lexicon) => lexicon,
+            Err(err) => {
+                error!("Lexicons are not loaded: {:?}", err);
+                Lexicon::default()
+            }
+        };
+
+        self.manage(RwLock::new(Arc::new(lexicon)))
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Lexicons<'r> {
+    type Error = HubError;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Lexicons<'r>, Self::Error> {
+        let outcome = request.guard::<&State<RwLock<Arc<Lexicon>>>>().await;
+        match outcome {
+            Outcome::Success(state) => Outcome::Success(Lexicons(state)),
+            Outcome::Failure(status) => {
+                Outcome::Failure((status.0, err_internal!("Faild to get Lexicons state")))
+            }
+            Outcome::Forward(forward) => Outcome::Forward(forward),
+        }
+    }
+}
@@ -1,39 +1,55 @@
-use lingua::{
-    Language,
-    Language::{English, Russian},
-    LanguageDetector, LanguageDetectorBuilder,
-};
+use lingua::{Language, LanguageDetector, LanguageDetectorBuilder};
 use rocket::{
     outcome::Outcome,
     request::{self, FromRequest},
     Build, Request, Rocket, State,
 };
 
-use crate::{err_internal, errors::HubError};
+use crate::{
+    err_internal,
+    errors::HubError,
+    model::language::{self, Detection, SUPPORTED_LANGUAGES},
+};
 
 pub struct Lingua<'a>(pub &'a State<Box<LanguageDetector>>);
 
 impl<'a> Lingua<'a> {
-    pub fn detected<T>(self, text: T) -> Result<Language, HubError>
+    pub fn detected<T>(
+        &self,
+        text: T,
+        declared: Option<&str>,
+    ) -> Result<(Language, Detection), HubError>
     where
         T: Into<String>,
     {
-        match self.0.detect_language_of(text) {
-            Some(language) => match language {
-                English => Ok(Language::English),
-                Russian => Ok(Language::Russian),
-            },
+        detect(self.0, text, declared)
+    }
+}
 
-            None => {
-                let error = HubError::new_unprocessable(
-                    "Unable to determine what language is used in the text",
-                    None,
-                );
+/// Определение языка по всему тексту записи с учетом языка, указанного автором.
+/// Используется и сервером, и утилитой импорта, у которой нет состояния Rocket.
+pub fn detect<T>(
+    detector: &LanguageDetector,
+    text: T,
+    declared: Option<&str>,
+) -> Result<(Language, Detection), HubError>
+where
+    T: Into<String>,
+{
+    let declared = declared.map(language::supported).transpose()?;
+    let values = detector.compute_language_confidence_values(text);
 
-                Err(error)
-            }
-        }
-    }
+    Detection::new(&values, declared)
+}
+
+/// Детектор для поддерживаемых языков
+pub fn detector() -> LanguageDetector {
+    assert!(
+        SUPPORTED_LANGUAGES.len() > 1,
+        "At least two content languages are required"
+    );
+
+    LanguageDetectorBuilder::from_languages(&SUPPORTED_LANGUAGES).build()
 }
 
 pub trait LinguaManage {
@@ -42,9 +58,7 @@ pub trait LinguaManage {
 
 impl LinguaManage for Rocket<Build> {
     fn manage_lingua(self) -> Self {
-        let languages = vec![English, Russian];
-        let detector: Box<LanguageDetector> =
-            Box::new(LanguageDetectorBuilder::from_languages(&languages).build());
+        let detector: Box<LanguageDetector> = Box::new(detector());
 
         self.manage(detector)
     }
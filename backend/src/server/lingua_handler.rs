@@ -0,0 +1,78 @@
+use std::net::IpAddr;
+
+use chrono::Utc;
+use rocket::{http::Header, serde::json::Json};
+use validator::Validate;
+
+use crate::{
+    err_forbidden,
+    errors::HubError,
+    model::{
+        account::{security::ApiKeyGuard, Tariff},
+        language::{DetectRequest, Guess},
+    },
+    server::{lingua::Lingua, rate_limit::RateLimit},
+};
+
+/// Ответ с остатком квоты потребителя
+#[derive(Responder)]
+pub struct Limited {
+    inner: Json<Vec<Guess>>,
+    limit: Header<'static>,
+    remaining: Header<'static>,
+}
+
+/// Определение языка нескольких текстов до отправки записи.
+/// Квота считается в текстах за минуту и зависит от тарифа Api-Key,
+/// без Api-Key действует тариф Free для адреса клиента.
+/// При превышении квоты ответ 429 содержит `Retry-After`.
+#[post("/lingua/detect", data = "<jdr>")]
+pub fn detect_languages<'f>(
+    _api_key: ApiKeyGuard,
+    lingua: Lingua<'f>,
+    rate_limit: RateLimit<'f>,
+    ip: Option<IpAddr>,
+    jdr: Json<DetectRequest>,
+) -> Result<Limited, HubError> {
+    jdr.0.validate()?;
+
+    // Клиенты без адреса не делят одну общую квоту: им нужен Api-Key
+    let (tariff, identity) = match (_api_key.0, ip) {
+        (Some(data), _) => (data.get_tariff(), format!("key:{}", data.get_owner())),
+        (None, Some(ip)) => (Tariff::default(), format!("ip:{}", ip)),
+        (None, None) => {
+            return Err(err_forbidden!(
+                "Api-Key is required when the client address is unknown"
+            ))
+        }
+    };
+
+    let batch = tariff.page_limit() as usize;
+    if jdr.0.texts.len() > batch {
+        return Err(HubError::new_unprocessable(
+            "Too many texts in one request",
+            Some(vec![format!("Maximum for your tariff: {}", batch)]),
+        ));
+    }
+
+    let limit = tariff.detect_rate();
+    let remaining = rate_limit.0.take(
+        &identity,
+        limit,
+        jdr.0.texts.len() as u32,
+        Utc::now().timestamp(),
+    )?;
+
+    let result = jdr
+        .0
+        .texts
+        .iter()
+        .map(|text| Guess::new(&lingua.0.compute_language_confidence_values(text)))
+        .collect();
+
+    Ok(Limited {
+        inner: Json(result),
+        limit: Header::new("X-RateLimit-Limit", limit.to_string()),
+        remaining: Header::new("X-RateLimit-Remaining", remaining.to_string()),
+    })
+}
@@ -1,21 +1,42 @@
 mod account_handler;
 mod anecdote_handler;
+mod author_handler;
 mod base_handler;
+mod comment_handler;
 mod favorite_handler;
+mod follow_handler;
+mod import_handler;
 mod joke_handler;
+mod lingua_handler;
+mod moderation_handler;
 mod punch_handler;
+mod search_handler;
 mod shrimp_handler;
+mod snapshot_handler;
+mod story_handler;
+mod tag_handler;
+mod translation_handler;
+mod trash_handler;
 
 mod config;
-mod lingua;
+pub mod lexicon;
+pub mod lingua;
+pub mod rate_limit;
+pub mod tag_usage;
 
 use crate::db::DbManage;
 
-use self::lingua::LinguaManage;
+use self::{
+    lexicon::LexiconManage, lingua::LinguaManage, rate_limit::RateLimitManage,
+    tag_usage::TagUsageManage,
+};
 
 use {
-    account_handler::*, anecdote_handler::*, base_handler::*, favorite_handler::*, joke_handler::*,
-    punch_handler::*, shrimp_handler::*,
+    account_handler::*, anecdote_handler::*, author_handler::*, base_handler::*,
+    comment_handler::*, favorite_handler::*, follow_handler::*, import_handler::*, joke_handler::*,
+    lingua_handler::*, moderation_handler::*, punch_handler::*, search_handler::*,
+    shrimp_handler::*, snapshot_handler::*, story_handler::*, tag_handler::*,
+    translation_handler::*, trash_handler::*,
 };
 
 #[launch]
@@ -23,6 +44,9 @@ pub fn rocket() -> _ {
     rocket::custom(config::from_env())
         .manage_mongodb()
         .manage_lingua()
+        .manage_lexicon()
+        .manage_rate_limit()
+        .manage_tag_usage()
         .mount("/", rocket::routes![ping])
         .mount(
             "/v1",
@@ -30,20 +54,82 @@ pub fn rocket() -> _ {
                 // Anecdote methods
                 create_anecdote,
                 get_anecdote,
+                update_anecdote,
                 delete_anecdote,
                 reaction_anecdote,
                 // Punch methods
                 create_punch,
                 get_punch,
+                update_punch,
                 delete_punch,
                 reaction_punch,
                 // Jokes methods
                 create_joke,
                 get_joke,
+                update_joke,
                 delete_joke,
                 reaction_joke,
+                // Story methods
+                create_story,
+                get_story,
+                update_story,
+                delete_story,
+                reaction_story,
                 // Shrimp methods
                 random,
+                reset_history,
+                daily,
+                list,
+                search,
+                top,
+                trending,
+                my_reaction,
+                remove_reaction,
+                revisions,
+                rollback,
+                // Comment methods
+                comment_add,
+                comments,
+                comment_edit,
+                comment_delete,
+                comment_reaction,
+                comment_reaction_remove,
+                // Moderation methods
+                flag_set,
+                flag_clear,
+                flag_audit,
+                queue,
+                mislabeled,
+                approve,
+                reject,
+                duplicates,
+                lexicon_reload,
+                // Tag methods
+                tags,
+                tag_merge,
+                // Translation methods
+                translations,
+                link_translation,
+                unlink_translation,
+                // Trash methods
+                trash_list,
+                trash_restore,
+                trash_purge,
+                // Author methods
+                author,
+                followers,
+                following,
+                // Follow methods
+                follow,
+                unfollow,
+                feed,
+                // Import methods
+                import_records,
+                export_fortune,
+                // Lingua methods
+                detect_languages,
+                // Snapshot methods
+                snapshot,
                 // Accounts methods
                 password_strength,
                 registration,
@@ -52,6 +138,7 @@ pub fn rocket() -> _ {
                 refresh_token,
                 change_password,
                 change_theme,
+                change_profile,
                 logout,
                 logout_any,
                 delete_account,
@@ -61,7 +148,8 @@ pub fn rocket() -> _ {
                 del_api_key,
                 // Favorite methods
                 favorite_add,
-                favorite_remove
+                favorite_remove,
+                favorite_all
             ],
         )
         .register("/", catchers![not_found, unauthorized, internal, forbidden])
@@ -0,0 +1,269 @@
+use mongodb::bson::doc;
+use rocket::serde::json::Json;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use validator::Validate;
+
+use crate::{
+    db::mongo::{
+        shrimp::aggregation::{self, Qilter},
+        varys::Varys,
+        Crud, MongoConn,
+    },
+    errors::HubError,
+    model::{
+        account::{
+            notification::{self, Notification, NotifyKind},
+            security::LevelGuard,
+        },
+        follow::Follow,
+        language::LOW_CONFIDENCE,
+        shrimp::{
+            moderation::{FlagAudit, FlagAuditInfo},
+            Category, Flag, Rejection, Status,
+        },
+        similarity::{DuplicateCluster, Fingerprint, FLAG_THRESHOLD},
+        validation::uuid_validation,
+    },
+    server::lexicon::Lexicons,
+    VectorConvert,
+};
+
+#[put("/<category>/<id>/flags/<flag>")]
+pub fn flag_set<'f>(
+    _level: LevelGuard,
+    client: MongoConn<'f>,
+    category: Category,
+    id: &str,
+    flag: Flag,
+) -> Result<(), HubError> {
+    FlagAudit::apply(
+        client.0.as_ref(),
+        category,
+        uuid_validation(id)?,
+        flag,
+        true,
+        _level.0.get_username_as_str(),
+    )
+}
+
+#[delete("/<category>/<id>/flags/<flag>")]
+pub fn flag_clear<'f>(
+    _level: LevelGuard,
+    client: MongoConn<'f>,
+    category: Category,
+    id: &str,
+    flag: Flag,
+) -> Result<(), HubError> {
+    FlagAudit::apply(
+        client.0.as_ref(),
+        category,
+        uuid_validation(id)?,
+        flag,
+        false,
+        _level.0.get_username_as_str(),
+    )
+}
+
+#[get("/<category>/<id>/flags/audit")]
+pub fn flag_audit<'f>(
+    _level: LevelGuard,
+    client: MongoConn<'f>,
+    category: Category,
+    id: &str,
+) -> Result<Json<Vec<FlagAuditInfo>>, HubError> {
+    let result = FlagAudit::roll(client.0.as_ref(), &category, uuid_validation(id)?)?;
+
+    Ok(Json(Vec::convert(result)))
+}
+
+/// Записи, ожидающие проверки. `id` оставляет в очереди только указанные записи.
+#[allow(clippy::too_many_arguments)]
+#[get("/queue?<category>&<flag>&<tag>&<author>&<lang>&<id>&<limit>")]
+pub fn queue<'f>(
+    _level: LevelGuard,
+    client: MongoConn<'f>,
+    category: Option<Vec<Category>>,
+    flag: Option<Vec<Flag>>,
+    tag: Option<Vec<&str>>,
+    author: Option<&str>,
+    lang: Option<&str>,
+    id: Option<Vec<&str>>,
+    limit: Option<i64>,
+) -> Result<Value, HubError> {
+    let qilter = Qilter::new(author, lang, flag, tag).resolve(client.0.as_ref())?;
+
+    let mut stages = qilter.stages();
+    stages.push(Qilter::status(&Status::Pending));
+
+    if let Some(ids) = id {
+        let ids = ids
+            .into_iter()
+            .map(uuid_validation)
+            .collect::<Result<Vec<&str>, HubError>>()?;
+        stages.push(doc! {"$match": {"_id": {"$in": ids}}});
+    }
+
+    // Первыми на проверку попадают самые старые записи
+    let tail = vec![
+        doc! {"$sort": {"_header.timestamp": 1}},
+        doc! {"$limit": limit.unwrap_or(50).clamp(1, 100)},
+    ];
+
+    let records = aggregation::union(
+        client.0.as_ref(),
+        &category.unwrap_or(Category::all()),
+        stages,
+        tail,
+    )?;
+
+    let mut result: Vec<Value> = Vec::new();
+    for record in records {
+        result.push(Category::moderation(record)?);
+    }
+
+    Ok(json!(result))
+}
+
+/// Записи, в языке которых детектор не уверен: их язык мог быть указан или определен неверно.
+/// Первыми идут записи с наименьшей уверенностью.
+#[get("/mislabeled?<category>&<lang>&<confidence>&<limit>")]
+pub fn mislabeled<'f>(
+    _level: LevelGuard,
+    client: MongoConn<'f>,
+    category: Option<Vec<Category>>,
+    lang: Option<&str>,
+    confidence: Option<f64>,
+    limit: Option<i64>,
+) -> Result<Value, HubError> {
+    let qilter = Qilter::new(None, lang, None, None).resolve(client.0.as_ref())?;
+    let confidence = confidence.unwrap_or(LOW_CONFIDENCE).clamp(0.0, 1.0);
+
+    let mut stages = qilter.stages();
+    stages.push(doc! {
+        "$match": {"_meta-data.language_detection.confidence": {"$lt": confidence}}
+    });
+
+    let tail = vec![
+        doc! {"$sort": {"_meta-data.language_detection.confidence": 1, "_id": 1}},
+        doc! {"$limit": limit.unwrap_or(50).clamp(1, 100)},
+    ];
+
+    let records = aggregation::union(
+        client.0.as_ref(),
+        &category.unwrap_or(Category::all()),
+        stages,
+        tail,
+    )?;
+
+    let mut result: Vec<Value> = Vec::new();
+    for record in records {
+        result.push(Category::moderation(record)?);
+    }
+
+    Ok(json!(result))
+}
+
+#[put("/<category>/<id>/status/approve")]
+pub fn approve<'f>(
+    _level: LevelGuard,
+    client: MongoConn<'f>,
+    category: Category,
+    id: &str,
+) -> Result<(), HubError> {
+    let id = uuid_validation(id)?;
+    let change = category.set_status(client.0.as_ref(), id, &Status::Approved)?;
+
+    // Уведомление автору о решении модератора
+    let ntf = Notification::new(
+        _level.0.get_username_as_str(),
+        &change.author,
+        NotifyKind::Success,
+        notification::Body::new(
+            format!(
+                "Your {} has been approved",
+                category.to_string().to_lowercase()
+            )
+            .as_str(),
+            None,
+            Some(vec![record_action(&category, id)]),
+        ),
+    );
+
+    Notification::create(Varys::get(client.0.as_ref(), Varys::Notification), &ntf)?;
+
+    // Подписчики автора узнают о записи, когда она впервые становится видна всем
+    if change.is_first_approval() {
+        Follow::notify(client.0.as_ref(), &change.author, &category, id)?;
+    }
+
+    Ok(())
+}
+
+#[put("/<category>/<id>/status/reject", data = "<jr>")]
+pub fn reject<'f>(
+    _level: LevelGuard,
+    client: MongoConn<'f>,
+    category: Category,
+    id: &str,
+    jr: Json<Rejection>,
+) -> Result<(), HubError> {
+    jr.0.validate()?;
+
+    let id = uuid_validation(id)?;
+    let status = Status::Rejected {
+        reason: jr.0.reason.clone(),
+    };
+    let author = category.set_status(client.0.as_ref(), id, &status)?.author;
+
+    // Уведомление автору о решении модератора
+    let ntf = Notification::new(
+        _level.0.get_username_as_str(),
+        &author,
+        NotifyKind::Attention,
+        notification::Body::new(
+            format!(
+                "Your {} has been rejected",
+                category.to_string().to_lowercase()
+            )
+            .as_str(),
+            Some(jr.0.reason),
+            Some(vec![record_action(&category, id)]),
+        ),
+    );
+
+    Notification::create(Varys::get(client.0.as_ref(), Varys::Notification), &ntf)?;
+
+    Ok(())
+}
+
+/// Ссылка на запись для уведомления
+fn record_action(category: &Category, id: &str) -> notification::Action {
+    notification::Action {
+        kind: notification::ActionKind::Major,
+        text: String::from("Open"),
+        href: format!("/v1/{}/{}", category.to_string().to_lowercase(), id),
+    }
+}
+
+/// Группы почти-дубликатов среди существующих записей
+#[get("/duplicates?<threshold>")]
+pub fn duplicates<'f>(
+    _level: LevelGuard,
+    client: MongoConn<'f>,
+    threshold: Option<f64>,
+) -> Result<Json<Vec<DuplicateCluster>>, HubError> {
+    let threshold = threshold.unwrap_or(FLAG_THRESHOLD).clamp(0.5, 1.0);
+
+    Ok(Json(Fingerprint::clusters(client.0.as_ref(), threshold)?))
+}
+
+/// Повторное чтение словарей флагов без перезапуска сервера.
+/// В ответе количество терминов для каждого флага.
+#[post("/lexicons/reload")]
+pub fn lexicon_reload<'f>(
+    _level: LevelGuard,
+    lexicons: Lexicons<'f>,
+) -> Result<Json<HashMap<Flag, usize>>, HubError> {
+    Ok(Json(lexicons.reload()?.size()))
+}
@@ -8,16 +8,20 @@ use crate::model::{
         security::{AuthGuard, LevelGuard},
         Tariff,
     },
+    language,
     punch::*,
-    shrimp::{Flags, Shrimp, Tail},
+    shrimp::{revision::Revision, Flags, Shrimp, Tail},
+    tag::Tag,
+    translation::Translation,
+    trash::Trash,
     validation::uuid_validation,
 };
 use crate::{
     db::mongo::MongoConn,
     db::mongo::{varys::Varys, Crud},
-    err_not_found,
+    err_forbidden,
     errors::HubError,
-    server::lingua::Lingua,
+    server::{lexicon::Lexicons, lingua::Lingua},
     shrimp_reaction_handler,
 };
 
@@ -32,37 +36,48 @@ pub async fn create_punch<'f>(
     _auth: AuthGuard,
     client: MongoConn<'f>,
     lingua: Lingua<'f>,
+    lexicons: Lexicons<'f>,
     jnp: Json<NewPunch>,
 ) -> Result<Value, HubError> {
     jnp.0.validate()?;
 
+    let (lang, detection) = lingua.detected(jnp.0.full_text(), jnp.0.lang.as_deref())?;
     let tail = Tail::new(
-        Flags::default(),
-        lingua.detected(jnp.clone().0.setup)?,
+        Flags::from(&jnp.0.flags),
+        lang,
         _auth.0.get_username(),
-        &jnp.0.tags,
-    );
+        &Tag::resolve(client.0.as_ref(), &jnp.0.tags)?,
+    )
+    .detection(detection)
+    .suggest(lexicons.get()?.suggest(&jnp.0.full_text()));
     let body = Punch::from(jnp.0);
 
-    let result = Shrimp::create(
-        Varys::get(client.0.as_ref(), Varys::Punch),
-        &Shrimp::new(body, tail),
-    )?;
+    let (result, similar) = Shrimp::new(body, tail).insert(client.0.as_ref())?;
 
-    let resp = json!({"id": result.inserted_id});
+    let resp = json!({"id": result.inserted_id, "similar": similar});
     Ok(resp)
 }
 
-#[get("/punch/<id>")]
+/// С параметром `lang` отдается перевод записи на этот язык, если он есть
+#[get("/punch/<id>?<lang>")]
 pub async fn get_punch<'f>(
     _api_key: ApiKeyGuard,
     client: MongoConn<'f>,
     id: &str,
+    lang: Option<&str>,
 ) -> Result<Value, HubError> {
-    let result: Shrimp<Punch> = Shrimp::get_by_id(
-        Varys::get(client.0.as_ref(), Varys::Punch),
-        uuid_validation(id)?,
-    )?;
+    let id = match lang {
+        Some(lang) => Translation::resolve(
+            client.0.as_ref(),
+            &Category::Punch,
+            uuid_validation(id)?,
+            &language::validate(lang)?,
+        )?,
+        None => uuid_validation(id)?.to_string(),
+    };
+
+    let result: Shrimp<Punch> =
+        Shrimp::get_by_id(Varys::get(client.0.as_ref(), Varys::Punch), &id)?;
 
     match _api_key.0 {
         Some(data) => Ok(result.tariffing(&data.get_tariff(), &None)),
@@ -70,21 +85,50 @@ pub async fn get_punch<'f>(
     }
 }
 
+#[put("/punch/<id>", data = "<jnp>")]
+pub async fn update_punch<'f>(
+    _auth: AuthGuard,
+    client: MongoConn<'f>,
+    lingua: Lingua<'f>,
+    lexicons: Lexicons<'f>,
+    id: &str,
+    jnp: Json<NewPunch>,
+) -> Result<(), HubError> {
+    jnp.0.validate()?;
+
+    let id = uuid_validation(id)?;
+    let author = Category::Punch.record_author(client.0.as_ref(), id)?;
+
+    if !_auth.0.can_manage(&author) {
+        return Err(err_forbidden!());
+    }
+
+    let (lang, detection) = lingua.detected(jnp.0.full_text(), jnp.0.lang.as_deref())?;
+
+    Revision::revise(
+        client.0.as_ref(),
+        lexicons.get()?.as_ref(),
+        Category::Punch,
+        id,
+        _auth.0.get_username_as_str(),
+        bson::to_document(&Punch::from(jnp.0.clone()))?,
+        &language::code(&lang),
+        Some(&detection),
+        &jnp.0.tags,
+    )
+}
+
+/// Запись попадает в корзину, откуда ее может восстановить модератор
 #[delete("/punch/<id>")]
 pub async fn delete_punch<'f>(
     _level: LevelGuard,
     client: MongoConn<'f>,
     id: &str,
 ) -> Result<(), HubError> {
-    Shrimp::<Punch>::del_by_id(
-        Varys::get(client.0.as_ref(), Varys::Punch),
+    Trash::put(
+        client.0.as_ref(),
+        &Category::Punch,
         uuid_validation(id)?,
+        _level.0.get_username_as_str(),
     )
-    .and_then(|d_result| {
-        if d_result.deleted_count < 1 {
-            Err(err_not_found!("punch"))
-        } else {
-            Ok(())
-        }
-    })
 }
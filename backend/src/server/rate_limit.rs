@@ -0,0 +1,127 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use rocket::{
+    outcome::Outcome,
+    request::{self, FromRequest},
+    Build, Request, Rocket, State,
+};
+
+use crate::{err_internal, errors::HubError};
+
+/// Длительность окна ограничения в секундах
+pub const WINDOW: i64 = 60;
+
+/// Ограничение нагрузки для каждого потребителя в пределах минутного окна
+#[derive(Default)]
+pub struct RateLimiter {
+    windows: Mutex<Windows>,
+}
+
+/// Квоты, израсходованные потребителями в текущем окне
+#[derive(Default)]
+struct Windows {
+    /// Начало текущего окна в секундах
+    start: i64,
+    used: HashMap<String, u32>,
+}
+
+impl RateLimiter {
+    /// Списание `cost` единиц квоты `limit` в момент `now` (секунды).
+    /// Возвращает остаток квоты в текущем окне.
+    pub fn take(&self, identity: &str, limit: u32, cost: u32, now: i64) -> Result<u32, HubError> {
+        let mut windows = self
+            .windows
+            .lock()
+            .map_err(|_| err_internal!("Faild to check rate limit"))?;
+
+        let start = now - now.rem_euclid(WINDOW);
+
+        // Квоты прошлого окна больше не нужны, они сбрасываются один раз на новое окно
+        if windows.start != start {
+            windows.start = start;
+            windows.used.clear();
+        }
+
+        let used = windows.used.entry(identity.to_string()).or_insert(0);
+        if *used + cost > limit {
+            let retry = start + WINDOW - now;
+
+            return Err(HubError::new_too_many_requests(
+                "Rate limit exceeded",
+                Some(vec![
+                    format!("Limit: {} per minute", limit),
+                    format!("Retry after {} seconds", retry),
+                ]),
+            )
+            .with_header("Retry-After", retry.to_string())
+            .with_header("X-RateLimit-Limit", limit.to_string())
+            .with_header("X-RateLimit-Remaining", (limit - *used).to_string()));
+        }
+
+        *used += cost;
+        Ok(limit - *used)
+    }
+}
+
+pub struct RateLimit<'a>(pub &'a State<RateLimiter>);
+
+pub trait RateLimitManage {
+    fn manage_rate_limit(self) -> Self;
+}
+
+impl RateLimitManage for Rocket<Build> {
+    fn manage_rate_limit(self) -> Self {
+        self.manage(RateLimiter::default())
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RateLimit<'r> {
+    type Error = HubError;
+
+    async fn from_request(
+        request: &'r Request<'_>,
+    ) -> request::Outcome<RateLimit<'r>, Self::Error> {
+        let outcome = request.guard::<&State<RateLimiter>>().await;
+        match outcome {
+            Outcome::Success(state) => Outcome::Success(RateLimit(state)),
+            Outcome::Failure(status) => {
+                Outcome::Failure((status.0, err_internal!("Faild to get RateLimiter state")))
+            }
+            Outcome::Forward(forward) => Outcome::Forward(forward),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RateLimiter;
+
+    #[test]
+    fn take_within_window() {
+        let limiter = RateLimiter::default();
+
+        assert_eq!(limiter.take("a", 5, 3, 120).ok(), Some(2));
+        assert_eq!(limiter.take("a", 5, 2, 150).ok(), Some(0));
+        assert!(limiter.take("a", 5, 1, 179).is_err());
+
+        // Квоты потребителей независимы
+        assert_eq!(limiter.take("b", 5, 1, 179).ok(), Some(4));
+
+        // В новом окне квота восстанавливается
+        assert_eq!(limiter.take("a", 5, 5, 180).ok(), Some(0));
+    }
+
+    #[test]
+    fn stale_windows_dropped() {
+        let limiter = RateLimiter::default();
+
+        limiter.take("a", 5, 1, 120).unwrap();
+        limiter.take("b", 5, 1, 130).unwrap();
+        assert_eq!(limiter.windows.lock().unwrap().used.len(), 2);
+
+        // Квоты прошлого окна сбрасываются с первым запросом нового
+        limiter.take("c", 5, 1, 185).unwrap();
+        assert_eq!(limiter.windows.lock().unwrap().used.len(), 1);
+    }
+}
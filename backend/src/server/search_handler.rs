@@ -0,0 +1,87 @@
+use serde_json::{json, Value};
+
+use crate::{
+    db::mongo::{
+        search::{self as fulltext, SCORE_FIELD},
+        shrimp::aggregation::Qilter,
+        MongoConn,
+    },
+    errors::HubError,
+    model::{
+        account::{security::ApiKeyGuard, Tariff},
+        search::Highlighter,
+        shrimp::{Category, Flag, Status},
+    },
+};
+
+/// Сколько подсвеченных фрагментов возвращать для одной записи
+const MAX_FRAGMENTS: usize = 3;
+
+#[allow(clippy::too_many_arguments)]
+#[get("/search?<q>&<category>&<flag>&<tag>&<author>&<lang>&<limit>")]
+pub fn search<'f>(
+    _api_key: ApiKeyGuard,
+    client: MongoConn<'f>,
+    q: Option<&str>,
+    category: Option<Vec<Category>>,
+    flag: Option<Vec<Flag>>,
+    tag: Option<Vec<&str>>,
+    author: Option<&str>,
+    lang: Option<&str>,
+    limit: Option<i64>,
+) -> Result<Value, HubError> {
+    let query = q.map(str::trim).unwrap_or_default();
+    if !(2..=200).contains(&query.chars().count()) {
+        return Err(HubError::new_unprocessable("Search query is invalid", None));
+    }
+
+    let tariff: Tariff = match _api_key.0 {
+        Some(data) => data.get_tariff(),
+        None => Tariff::default(),
+    };
+    let limit = limit
+        .unwrap_or_else(|| tariff.page_limit())
+        .clamp(1, tariff.page_limit());
+
+    let qilter = Qilter::new(author, lang, flag, tag).resolve(client.0.as_ref())?;
+    let mut stages = qilter.stages();
+    stages.push(Qilter::status(&Status::Approved));
+
+    let records = fulltext::find(
+        client.0.as_ref(),
+        &category.unwrap_or(Category::all()),
+        query,
+        qilter.language(),
+        stages,
+        limit,
+    )?;
+
+    let highlighter = Highlighter::new(query);
+    let mut result: Vec<Value> = Vec::new();
+
+    for mut record in records {
+        let score = fulltext::score(&record);
+        record.remove(SCORE_FIELD);
+
+        let category: Category =
+            bson::from_bson(record.get("category").cloned().unwrap_or(bson::Bson::Null))?;
+
+        let mut texts: Vec<String> = Vec::new();
+        for (field, _) in category.text_fields() {
+            texts.extend(fulltext::texts(&record, field));
+        }
+
+        let mut value = Category::tariffing(record, &tariff)?;
+        if let Some(object) = value.as_object_mut() {
+            object.insert("score".to_string(), json!(score));
+            object.insert(
+                "highlights".to_string(),
+                json!(highlighter.fragments(&texts, MAX_FRAGMENTS)),
+            );
+        }
+
+        result.push(value);
+    }
+
+    Ok(json!({ "records": result }))
+}
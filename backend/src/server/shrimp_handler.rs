@@ -1,66 +1,130 @@
-use mongodb::bson::doc;
-use serde_json::Value;
+use chrono::Utc;
+use mongodb::bson::{doc, DateTime as MongoDateTime};
+use rocket::{http::Header, serde::json::Json};
+use serde_json::{json, Value};
 
 use crate::{
-    db::mongo::{shrimp::aggregation::Qilter, varys::Varys, MongoConn},
-    err_not_found,
+    db::mongo::{
+        shrimp::aggregation::{self, Pagination, Qilter, Ranking},
+        varys::Varys,
+        MongoConn,
+    },
+    err_forbidden, err_not_found, err_unauthorized,
     errors::HubError,
     model::{
-        account::security::ApiKeyGuard,
+        account::security::{ApiKeyGuard, AuthGuard, IdentityGuard},
         account::Tariff,
         anecdote::Anecdote,
+        daily::{parse_offset, seconds_to_midnight, Daily},
         joke::Joke,
         punch::Punch,
-        shrimp::{Category, Flag, Shrimp},
+        shrimp::{
+            history::Served,
+            reaction::{Reaction, ReactionInfo},
+            revision::{Revision, RevisionInfo},
+            Category, Flag, Period, Shrimp, SortKey, SortOrder, Status,
+        },
+        story::Story,
+        translation::Translation,
+        validation::uuid_validation,
     },
+    server::lexicon::Lexicons,
+    VectorConvert,
 };
 
 #[macro_export]
 macro_rules! shrimp_reaction_handler {
     ($f:ident, $path:literal, $category:tt) => {
-        use crate::model::account::security::ApiKeyGuard;
+        use crate::model::account::security::{ApiKeyGuard, IdentityGuard};
         use crate::model::shrimp::{Category, ReactionKind};
 
+        /// Установка реакции. Повторный запрос с другой реакцией заменяет прежнюю.
         #[post($path)]
         pub fn $f<'f>(
-            _api_key: ApiKeyGuard,
+            _identity: IdentityGuard,
             client: MongoConn<'f>,
             record_id: &str,
             reaction_kind: ReactionKind,
         ) -> Result<(), HubError> {
-            match _api_key.0 {
-                Some(_) => Shrimp::<$category>::add_reaction(
-                    &Varys::get(client.0.as_ref(), Category::$category.into()),
-                    record_id,
-                    reaction_kind,
-                ),
-
-                None => Err(crate::err_unauthorized!(
-                    "Api-Key is not found",
-                    "Api-Key must be set in the header with the name `Api-Key`"
-                )),
-            }
+            crate::model::shrimp::reaction::Reaction::set(
+                client.0.as_ref(),
+                Category::$category,
+                crate::model::validation::uuid_validation(record_id)?,
+                &_identity.0,
+                reaction_kind,
+            )
         }
     };
 }
 
-#[get("/random?<category>&<flag>&<tag>&<author>&<lang>")]
+/// Случайная запись.
+/// В режиме `unique` сервер помнит, какие записи уже были показаны потребителю
+/// за последние `window` часов, и не повторяет их.
+/// С `translations` вместе с записью отдаются ее одобренные переводы.
+#[allow(clippy::too_many_arguments)]
+#[get("/random?<category>&<flag>&<tag>&<author>&<lang>&<unique>&<window>&<translations>")]
 pub fn random<'f>(
     _api_key: ApiKeyGuard,
+    _identity: Option<IdentityGuard>,
     client: MongoConn<'f>,
     category: Option<Vec<Category>>,
     flag: Option<Vec<Flag>>,
     tag: Option<Vec<&str>>,
     author: Option<&str>,
     lang: Option<&str>,
+    unique: Option<bool>,
+    window: Option<u32>,
+    translations: Option<bool>,
 ) -> Result<Value, HubError> {
+    let categories = category.clone().unwrap_or(Category::all());
     let (mut random_category, mut allowed_category) = Category::random(category, true);
-    let qilter = Qilter::new(author, lang, flag, tag);
+    let mut qilter = Qilter::new(author, lang, flag, tag).resolve(client.0.as_ref())?;
     let tariff: Tariff = match _api_key.0 {
         Some(data) => data.get_tariff(),
         None => Tariff::default(),
     };
 
+    // Потребитель, для которого ведется история показов
+    let identity = match (unique.unwrap_or(false), _identity) {
+        (false, _) => None,
+        (true, Some(identity)) => Some(identity.0),
+        (true, None) => {
+            return Err(err_unauthorized!(
+                "Api-Key is not found",
+                "Non-repeating mode requires an Api-Key or an access token"
+            ))
+        }
+    };
+
+    let window = window
+        .unwrap_or(Served::DEFAULT_WINDOW)
+        .clamp(1, Served::MAX_WINDOW);
+
+    let served = match identity.as_ref() {
+        Some(identity) => Served::recent(client.0.as_ref(), identity, window)?,
+        None => Vec::new(),
+    };
+    let served_count = served.len();
+    qilter = qilter.exclude(served);
+
+    let remember = |category: Category, record_id: &str| match identity.as_ref() {
+        Some(identity) => Served::remember(client.0.as_ref(), identity, category, record_id),
+        None => Ok(()),
+    };
+
+    let attach = |category: Category, record_id: &str, mut resp: Value| {
+        if translations.unwrap_or(false) {
+            let mut result: Vec<Value> = Vec::new();
+            for record in Translation::approved(client.0.as_ref(), &category, record_id)? {
+                result.push(Category::tariffing(record, &tariff)?);
+            }
+
+            resp["translations"] = json!(result);
+        }
+
+        Ok::<Value, HubError>(resp)
+    };
+
     loop {
         match random_category.as_ref() {
             Some(Category::Anecdote) => {
@@ -71,13 +135,12 @@ pub fn random<'f>(
                     (random_category, allowed_category) =
                         Category::random(Some(allowed_category), false);
                 } else {
-                    let resp = result
-                        .as_ref()
-                        .unwrap()
-                        .inc_counter(&collection)?
-                        .tariffing(&tariff, &None);
+                    let record = result.as_ref().unwrap();
+                    remember(Category::Anecdote, &record.id)?;
 
-                    return Ok(resp);
+                    let resp = record.inc_counter(&collection)?.tariffing(&tariff, &None);
+
+                    return attach(Category::Anecdote, &record.id, resp);
                 }
             }
 
@@ -89,13 +152,12 @@ pub fn random<'f>(
                     (random_category, allowed_category) =
                         Category::random(Some(allowed_category), false);
                 } else {
-                    let resp = result
-                        .as_ref()
-                        .unwrap()
-                        .inc_counter(&collection)?
-                        .tariffing(&tariff, &None);
+                    let record = result.as_ref().unwrap();
+                    remember(Category::Joke, &record.id)?;
+
+                    let resp = record.inc_counter(&collection)?.tariffing(&tariff, &None);
 
-                    return Ok(resp);
+                    return attach(Category::Joke, &record.id, resp);
                 }
             }
 
@@ -107,17 +169,344 @@ pub fn random<'f>(
                     (random_category, allowed_category) =
                         Category::random(Some(allowed_category), false);
                 } else {
-                    let resp = result
-                        .as_ref()
-                        .unwrap()
-                        .inc_counter(&collection)?
-                        .tariffing(&tariff, &None);
+                    let record = result.as_ref().unwrap();
+                    remember(Category::Punch, &record.id)?;
 
-                    return Ok(resp);
+                    let resp = record.inc_counter(&collection)?.tariffing(&tariff, &None);
+
+                    return attach(Category::Punch, &record.id, resp);
                 }
             }
 
+            Some(Category::Story) => {
+                let collection = Varys::get::<Shrimp<Story>>(client.0.as_ref(), Varys::Story);
+                let result = Shrimp::<Story>::get_random(&collection, &qilter)?;
+
+                if result.is_none() {
+                    (random_category, allowed_category) =
+                        Category::random(Some(allowed_category), false);
+                } else {
+                    let record = result.as_ref().unwrap();
+                    remember(Category::Story, &record.id)?;
+
+                    let resp = record.inc_counter(&collection)?.tariffing(&tariff, &None);
+
+                    return attach(Category::Story, &record.id, resp);
+                }
+            }
+
+            None if served_count > 0 => {
+                // Все подходящие записи уже были показаны
+                let mut stages = qilter.without_exclude().stages();
+                stages.push(Qilter::status(&Status::Approved));
+
+                let pool = aggregation::union(
+                    client.0.as_ref(),
+                    &categories,
+                    stages,
+                    vec![doc! {"$limit": 1}],
+                )?;
+
+                if pool.is_empty() {
+                    return Err(err_not_found!("record"));
+                }
+
+                return Ok(json!({
+                    "exhausted": true,
+                    "served": served_count,
+                    "window": window
+                }));
+            }
+
             None => return Err(err_not_found!("record")),
         }
     }
 }
+
+/// Ответ, который можно кешировать до конца календарного дня
+#[derive(Responder)]
+pub struct Cached {
+    inner: Value,
+    cache_control: Header<'static>,
+    vary: Header<'static>,
+}
+
+/// Запись дня.
+/// Все потребители с одинаковыми фильтрами получают одну и ту же запись
+/// в течение календарного дня в часовом поясе `tz` (смещение от UTC, например `+03:00`).
+/// Запись не повторяется в течение `period` дней.
+#[allow(clippy::too_many_arguments)]
+#[get("/daily?<category>&<flag>&<tag>&<author>&<lang>&<tz>&<period>")]
+pub fn daily<'f>(
+    _api_key: ApiKeyGuard,
+    client: MongoConn<'f>,
+    category: Option<Vec<Category>>,
+    flag: Option<Vec<Flag>>,
+    tag: Option<Vec<&str>>,
+    author: Option<&str>,
+    lang: Option<&str>,
+    tz: Option<&str>,
+    period: Option<u32>,
+) -> Result<Cached, HubError> {
+    let tariff: Tariff = match _api_key.0 {
+        Some(data) => data.get_tariff(),
+        None => Tariff::default(),
+    };
+
+    let offset = parse_offset(tz)?;
+    let now = Utc::now().with_timezone(&offset).naive_local();
+    let date = now.date();
+
+    let period = period
+        .unwrap_or(Daily::DEFAULT_PERIOD)
+        .clamp(1, Daily::MAX_PERIOD);
+    let categories = category.unwrap_or(Category::all());
+    let qilter = Qilter::new(author, lang, flag, tag).resolve(client.0.as_ref())?;
+
+    let record = Daily::pick(client.0.as_ref(), &categories, &qilter, &date, period)?;
+
+    let mut resp = Category::tariffing(record, &tariff)?;
+    if let Some(object) = resp.as_object_mut() {
+        object.insert("date".to_string(), json!(date.to_string()));
+    }
+
+    // Ответ актуален до полуночи в часовом поясе потребителя
+    let expires = seconds_to_midnight(&now)?;
+
+    Ok(Cached {
+        inner: resp,
+        cache_control: Header::new("Cache-Control", format!("public, max-age={}", expires)),
+        vary: Header::new("Vary", "Api-Key"),
+    })
+}
+
+#[delete("/random/history")]
+pub fn reset_history<'f>(
+    _identity: IdentityGuard,
+    client: MongoConn<'f>,
+) -> Result<Value, HubError> {
+    let deleted = Served::reset(client.0.as_ref(), &_identity.0)?;
+
+    Ok(json!({ "deleted": deleted }))
+}
+
+#[allow(clippy::too_many_arguments)]
+#[get("/<category>?<page>&<cursor>&<limit>&<sort>&<order>&<flag>&<tag>&<author>&<lang>")]
+pub fn list<'f>(
+    _api_key: ApiKeyGuard,
+    client: MongoConn<'f>,
+    category: Category,
+    page: Option<u64>,
+    cursor: Option<&str>,
+    limit: Option<i64>,
+    sort: Option<SortKey>,
+    order: Option<SortOrder>,
+    flag: Option<Vec<Flag>>,
+    tag: Option<Vec<&str>>,
+    author: Option<&str>,
+    lang: Option<&str>,
+) -> Result<Value, HubError> {
+    let tariff: Tariff = match _api_key.0 {
+        Some(data) => data.get_tariff(),
+        None => Tariff::default(),
+    };
+
+    // Размер страницы ограничен тарифом
+    let limit = limit
+        .unwrap_or_else(|| tariff.page_limit())
+        .clamp(1, tariff.page_limit());
+    let pagination = Pagination::new(
+        sort.unwrap_or_default(),
+        order.unwrap_or_default(),
+        limit,
+        page,
+        cursor,
+    )?;
+
+    let mut stages = Qilter::new(author, lang, flag, tag)
+        .resolve(client.0.as_ref())?
+        .stages();
+    stages.push(Qilter::status(&Status::Approved));
+
+    let mut records =
+        aggregation::union(client.0.as_ref(), &[category], stages, pagination.stages())?;
+    let next = pagination.finish(&mut records);
+
+    let mut result: Vec<Value> = Vec::new();
+    for record in records {
+        result.push(Category::tariffing(record, &tariff)?);
+    }
+
+    Ok(json!({"records": result, "next": next}))
+}
+
+#[allow(clippy::too_many_arguments)]
+#[get("/top?<period>&<category>&<flag>&<tag>&<author>&<lang>&<limit>")]
+pub fn top<'f>(
+    _api_key: ApiKeyGuard,
+    client: MongoConn<'f>,
+    period: Option<Period>,
+    category: Option<Vec<Category>>,
+    flag: Option<Vec<Flag>>,
+    tag: Option<Vec<&str>>,
+    author: Option<&str>,
+    lang: Option<&str>,
+    limit: Option<i64>,
+) -> Result<Value, HubError> {
+    let qilter = Qilter::new(author, lang, flag, tag).resolve(client.0.as_ref())?;
+
+    ranked(
+        _api_key,
+        client,
+        Ranking::Top,
+        period.unwrap_or(Period::All),
+        category,
+        qilter,
+        limit,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+#[get("/trending?<period>&<category>&<flag>&<tag>&<author>&<lang>&<limit>")]
+pub fn trending<'f>(
+    _api_key: ApiKeyGuard,
+    client: MongoConn<'f>,
+    period: Option<Period>,
+    category: Option<Vec<Category>>,
+    flag: Option<Vec<Flag>>,
+    tag: Option<Vec<&str>>,
+    author: Option<&str>,
+    lang: Option<&str>,
+    limit: Option<i64>,
+) -> Result<Value, HubError> {
+    let qilter = Qilter::new(author, lang, flag, tag).resolve(client.0.as_ref())?;
+
+    ranked(
+        _api_key,
+        client,
+        Ranking::Trending,
+        period.unwrap_or(Period::Week),
+        category,
+        qilter,
+        limit,
+    )
+}
+
+/// Подборка одобренных записей всех категорий по рейтингу
+fn ranked(
+    _api_key: ApiKeyGuard,
+    client: MongoConn,
+    ranking: Ranking,
+    period: Period,
+    category: Option<Vec<Category>>,
+    qilter: Qilter,
+    limit: Option<i64>,
+) -> Result<Value, HubError> {
+    let tariff: Tariff = match _api_key.0 {
+        Some(data) => data.get_tariff(),
+        None => Tariff::default(),
+    };
+    let limit = limit
+        .unwrap_or_else(|| tariff.page_limit())
+        .clamp(1, tariff.page_limit());
+
+    let mut stages = qilter.stages();
+    stages.push(Qilter::status(&Status::Approved));
+    stages.extend(ranking.stages(&period, MongoDateTime::now().timestamp_millis()));
+
+    let records = aggregation::union(
+        client.0.as_ref(),
+        &category.unwrap_or(Category::all()),
+        stages,
+        Ranking::tail(limit),
+    )?;
+
+    let mut result: Vec<Value> = Vec::new();
+    for mut record in records {
+        let score = Ranking::take_score(&mut record);
+        let mut value = Category::tariffing(record, &tariff)?;
+
+        if let Some(object) = value.as_object_mut() {
+            object.insert("score".to_string(), json!(score));
+        }
+
+        result.push(value);
+    }
+
+    Ok(json!({ "records": result }))
+}
+
+// Ранг нужен, чтобы маршрут не пересекался с `/<category>/<id>/revisions`
+#[get("/<category>/reaction/<record_id>", rank = 2)]
+pub fn my_reaction<'f>(
+    _identity: IdentityGuard,
+    client: MongoConn<'f>,
+    category: Category,
+    record_id: &str,
+) -> Result<Json<ReactionInfo>, HubError> {
+    let result = Reaction::get(
+        client.0.as_ref(),
+        &category,
+        uuid_validation(record_id)?,
+        &_identity.0,
+    )?;
+
+    Ok(Json(result.into()))
+}
+
+#[delete("/<category>/reaction/<record_id>")]
+pub fn remove_reaction<'f>(
+    _identity: IdentityGuard,
+    client: MongoConn<'f>,
+    category: Category,
+    record_id: &str,
+) -> Result<(), HubError> {
+    Reaction::remove(
+        client.0.as_ref(),
+        category,
+        uuid_validation(record_id)?,
+        &_identity.0,
+    )
+}
+
+#[get("/<category>/<id>/revisions")]
+pub fn revisions<'f>(
+    _auth: AuthGuard,
+    client: MongoConn<'f>,
+    category: Category,
+    id: &str,
+) -> Result<Json<Vec<RevisionInfo>>, HubError> {
+    let id = uuid_validation(id)?;
+    let author = category.record_author(client.0.as_ref(), id)?;
+
+    if !_auth.0.can_manage(&author) {
+        return Err(err_forbidden!());
+    }
+
+    let result = Revision::roll(client.0.as_ref(), &category, id)?;
+
+    Ok(Json(Vec::convert(result)))
+}
+
+#[put("/<category>/<id>/revisions/<revision_id>")]
+pub fn rollback<'f>(
+    _auth: AuthGuard,
+    client: MongoConn<'f>,
+    lexicons: Lexicons<'f>,
+    category: Category,
+    id: &str,
+    revision_id: &str,
+) -> Result<(), HubError> {
+    let id = uuid_validation(id)?;
+    let author = category.record_author(client.0.as_ref(), id)?;
+
+    if !_auth.0.can_manage(&author) {
+        return Err(err_forbidden!());
+    }
+
+    Revision::get_by_record(client.0.as_ref(), id, revision_id)?.rollback(
+        client.0.as_ref(),
+        lexicons.get()?.as_ref(),
+        _auth.0.get_username_as_str(),
+    )
+}
@@ -0,0 +1,118 @@
+use flate2::{write::GzEncoder, Compression};
+use mongodb::bson::DateTime as MongoDateTime;
+use rocket::{
+    futures::stream::{self, StreamExt},
+    http::ContentType,
+    request::Request,
+    response::{self, stream::ReaderStream, Responder, Response},
+    tokio::sync::mpsc,
+};
+use std::{
+    io::{self, BufWriter, Cursor, Write},
+    thread,
+};
+
+use crate::{
+    db::mongo::MongoConn,
+    err_forbidden, err_internal,
+    errors::HubError,
+    model::{
+        account::{security::ApiKeyGuard, Tariff},
+        snapshot::Manifest,
+    },
+};
+
+/// Сколько сжатых кусков снимка может ждать отправки клиенту
+const SNAPSHOT_BACKLOG: usize = 16;
+
+/// Размер куска сжатого снимка
+const SNAPSHOT_CHUNK: usize = 64 * 1024;
+
+/// Снимки данных доступны только на тарифе Enterprice
+fn enterprice_only(api_key: ApiKeyGuard) -> Result<(), HubError> {
+    match api_key.0 {
+        Some(data) if data.get_tariff() == Tariff::Enterprice => Ok(()),
+        _ => Err(err_forbidden!(
+            "Snapshots are available on the Enterprice tariff"
+        )),
+    }
+}
+
+/// Сжатый gzip снимок одобренных записей всех категорий в формате JSON Lines.
+/// Для инкрементального снимка передается `since` — значение `until` предыдущего снимка.
+/// Снимок отдается по мере чтения из базы и заканчивается строкой манифеста:
+/// если ее нет, снимок оборвался и его нужно запросить заново.
+#[get("/snapshot?<since>&<until>")]
+pub fn snapshot<'f>(
+    _api_key: ApiKeyGuard,
+    client: MongoConn<'f>,
+    since: Option<i64>,
+    until: Option<i64>,
+) -> Result<Snapshot, HubError> {
+    enterprice_only(_api_key)?;
+
+    let until = until.unwrap_or_else(|| MongoDateTime::now().timestamp_millis());
+    let (sender, chunks) = mpsc::channel(SNAPSHOT_BACKLOG);
+    let client = client.0.as_ref().clone();
+
+    thread::spawn(move || {
+        let out = BufWriter::with_capacity(SNAPSHOT_CHUNK, ChunkWriter(sender));
+        let mut encoder = GzEncoder::new(out, Compression::default());
+
+        let result = Manifest::write(&client, since, until, &mut encoder).and_then(|_| {
+            encoder
+                .finish()
+                .and_then(|mut out| out.flush())
+                .map_err(|err| err_internal!("Faild to compress snapshot", err))
+        });
+
+        if let Err(err) = result {
+            error!("Snapshot is not completed: {:?}", err);
+        }
+    });
+
+    Ok(Snapshot {
+        name: format!("snapshot-{}.jsonl.gz", until),
+        chunks,
+    })
+}
+
+/// Снимок, который отдается клиенту по мере сжатия
+pub struct Snapshot {
+    name: String,
+    chunks: mpsc::Receiver<Vec<u8>>,
+}
+
+impl<'r> Responder<'r, 'static> for Snapshot {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        let chunks = stream::unfold(self.chunks, |mut chunks| async move {
+            chunks.recv().await.map(|chunk| (chunk, chunks))
+        });
+
+        Response::build()
+            .header(ContentType::new("application", "gzip"))
+            .raw_header(
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", self.name),
+            )
+            .streamed_body(ReaderStream::from(chunks.map(Cursor::new)))
+            .ok()
+    }
+}
+
+/// Передача сжатых кусков снимка из потока чтения базы в ответ
+struct ChunkWriter(mpsc::Sender<Vec<u8>>);
+
+impl Write for ChunkWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .blocking_send(buf.to_vec())
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "Snapshot client is gone"))?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use mongodb::bson::doc;
+use rocket::serde::json::Json;
+use serde_json::{json, Value};
+use validator::Validate;
+
+use crate::{
+    db::mongo::{varys::Varys, Crud, MongoConn},
+    err_forbidden,
+    errors::HubError,
+    model::{
+        account::{
+            security::{AuthGuard, LevelGuard},
+            Tariff,
+        },
+        language,
+        shrimp::{revision::Revision, Flags, Shrimp, Tail},
+        story::*,
+        tag::Tag,
+        translation::Translation,
+        trash::Trash,
+        validation::uuid_validation,
+    },
+    server::{lexicon::Lexicons, lingua::Lingua},
+    shrimp_reaction_handler,
+};
+
+shrimp_reaction_handler!(
+    reaction_story,
+    "/story/reaction/<record_id>/<reaction_kind>",
+    Story
+);
+
+#[post("/story/new", data = "<jns>")]
+pub async fn create_story<'f>(
+    _auth: AuthGuard,
+    client: MongoConn<'f>,
+    lingua: Lingua<'f>,
+    lexicons: Lexicons<'f>,
+    jns: Json<NewStory>,
+) -> Result<Value, HubError> {
+    jns.0.validate()?;
+
+    let (lang, detection) = lingua.detected(jns.0.full_text(), jns.0.lang.as_deref())?;
+    let tail = Tail::new(
+        Flags::from(&jns.0.flags),
+        lang,
+        _auth.0.get_username(),
+        &Tag::resolve(client.0.as_ref(), &jns.0.tags)?,
+    )
+    .detection(detection)
+    .suggest(lexicons.get()?.suggest(&jns.0.full_text()));
+    let body = Story::from(jns.0);
+
+    let (result, similar) = Shrimp::new(body, tail).insert(client.0.as_ref())?;
+
+    let resp = json!({"id": result.inserted_id, "similar": similar});
+    Ok(resp)
+}
+
+/// С параметром `lang` отдается перевод записи на этот язык, если он есть
+#[get("/story/<id>?<lang>")]
+pub async fn get_story<'f>(
+    _api_key: ApiKeyGuard,
+    client: MongoConn<'f>,
+    id: &str,
+    lang: Option<&str>,
+) -> Result<Value, HubError> {
+    let id = match lang {
+        Some(lang) => Translation::resolve(
+            client.0.as_ref(),
+            &Category::Story,
+            uuid_validation(id)?,
+            &language::validate(lang)?,
+        )?,
+        None => uuid_validation(id)?.to_string(),
+    };
+
+    let result: Shrimp<Story> =
+        Shrimp::get_by_id(Varys::get(client.0.as_ref(), Varys::Story), &id)?;
+
+    match _api_key.0 {
+        Some(data) => Ok(result.tariffing(&data.get_tariff(), &None)),
+        None => Ok(result.tariffing(&Tariff::default(), &None)),
+    }
+}
+
+#[put("/story/<id>", data = "<jns>")]
+pub async fn update_story<'f>(
+    _auth: AuthGuard,
+    client: MongoConn<'f>,
+    lingua: Lingua<'f>,
+    lexicons: Lexicons<'f>,
+    id: &str,
+    jns: Json<NewStory>,
+) -> Result<(), HubError> {
+    jns.0.validate()?;
+
+    let id = uuid_validation(id)?;
+    let author = Category::Story.record_author(client.0.as_ref(), id)?;
+
+    if !_auth.0.can_manage(&author) {
+        return Err(err_forbidden!());
+    }
+
+    let (lang, detection) = lingua.detected(jns.0.full_text(), jns.0.lang.as_deref())?;
+
+    Revision::revise(
+        client.0.as_ref(),
+        lexicons.get()?.as_ref(),
+        Category::Story,
+        id,
+        _auth.0.get_username_as_str(),
+        bson::to_document(&Story::from(jns.0.clone()))?,
+        &language::code(&lang),
+        Some(&detection),
+        &jns.0.tags,
+    )
+}
+
+/// Запись попадает в корзину, откуда ее может восстановить модератор
+#[delete("/story/<id>")]
+pub async fn delete_story<'f>(
+    _level: LevelGuard,
+    client: MongoConn<'f>,
+    id: &str,
+) -> Result<(), HubError> {
+    Trash::put(
+        client.0.as_ref(),
+        &Category::Story,
+        uuid_validation(id)?,
+        _level.0.get_username_as_str(),
+    )
+}
@@ -0,0 +1,47 @@
+use chrono::Utc;
+use rocket::serde::json::Json;
+use validator::Validate;
+
+use crate::{
+    db::mongo::MongoConn,
+    errors::HubError,
+    model::{
+        account::security::{ApiKeyGuard, LevelGuard},
+        tag::{Tag, TagInfo, TagMerge},
+    },
+    server::tag_usage::TagUsage,
+};
+
+/// Теги с количеством одобренных записей; количество обновляется раз в несколько минут
+#[get("/tags?<q>&<limit>")]
+pub fn tags<'f>(
+    _api_key: ApiKeyGuard,
+    client: MongoConn<'f>,
+    usage: TagUsage<'f>,
+    q: Option<&str>,
+    limit: Option<usize>,
+) -> Result<Json<Vec<TagInfo>>, HubError> {
+    let usage = usage
+        .0
+        .get(Utc::now().timestamp(), || Tag::usage(client.0.as_ref()))?;
+
+    let mut result = Tag::list(client.0.as_ref(), q, &usage)?;
+    result.truncate(limit.unwrap_or(100).clamp(1, 500));
+
+    Ok(Json(result))
+}
+
+#[post("/tags/merge", data = "<jtm>")]
+pub fn tag_merge<'f>(
+    _level: LevelGuard,
+    client: MongoConn<'f>,
+    usage: TagUsage<'f>,
+    jtm: Json<TagMerge>,
+) -> Result<(), HubError> {
+    jtm.0.validate()?;
+
+    Tag::merge(client.0.as_ref(), &jtm.0.source, &jtm.0.target)?;
+
+    // Записи слитых тегов теперь считаются под каноническим именем
+    usage.0.invalidate()
+}
@@ -0,0 +1,125 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use rocket::{
+    outcome::Outcome,
+    request::{self, FromRequest},
+    Build, Request, Rocket, State,
+};
+
+use crate::{err_internal, errors::HubError};
+
+/// Сколько секунд список тегов отдает закешированное количество записей
+pub const USAGE_TTL: i64 = 300;
+
+/// Количество одобренных записей для каждого имени тега
+pub type Usage = Arc<HashMap<String, i64>>;
+
+/// Кеш количества записей по тегам: подсчет проходит по всем категориям,
+/// поэтому выполняется не чаще раза в `USAGE_TTL` секунд
+#[derive(Default)]
+pub struct UsageCache {
+    usage: Mutex<Option<(i64, Usage)>>,
+}
+
+impl UsageCache {
+    /// Количество из кеша на момент `now` (секунды); устаревшее пересчитывается через `count`.
+    /// Пока идет пересчет, остальные запросы ждут его, а не считают заново.
+    pub fn get<F>(&self, now: i64, count: F) -> Result<Usage, HubError>
+    where
+        F: FnOnce() -> Result<HashMap<String, i64>, HubError>,
+    {
+        let mut cached = self
+            .usage
+            .lock()
+            .map_err(|_| err_internal!("Faild to read tag usage"))?;
+
+        match cached.as_ref() {
+            Some((at, usage)) if now - at < USAGE_TTL => Ok(Arc::clone(usage)),
+            _ => {
+                let usage = Arc::new(count()?);
+                *cached = Some((now, Arc::clone(&usage)));
+
+                Ok(usage)
+            }
+        }
+    }
+
+    /// Сброс кеша, например после слияния тегов
+    pub fn invalidate(&self) -> Result<(), HubError> {
+        match self.usage.lock() {
+            Ok(mut cached) => {
+                *cached = None;
+                Ok(())
+            }
+            Err(_) => Err(err_internal!("Faild to reset tag usage")),
+        }
+    }
+}
+
+pub struct TagUsage<'a>(pub &'a State<UsageCache>);
+
+pub trait TagUsageManage {
+    fn manage_tag_usage(self) -> Self;
+}
+
+impl TagUsageManage for Rocket<Build> {
+    fn manage_tag_usage(self) -> Self {
+        self.manage(UsageCache::default())
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for TagUsage<'r> {
+    type Error = HubError;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<TagUsage<'r>, Self::Error> {
+        let outcome = request.guard::<&State<UsageCache>>().await;
+        match outcome {
+            Outcome::Success(state) => Outcome::Success(TagUsage(state)),
+            Outcome::Failure(status) => {
+                Outcome::Failure((status.0, err_internal!("Faild to get UsageCache state")))
+            }
+            Outcome::Forward(forward) => Outcome::Forward(forward),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{UsageCache, USAGE_TTL};
+
+    fn count(value: i64) -> HashMap<String, i64> {
+        HashMap::from([(String::from("work"), value)])
+    }
+
+    #[test]
+    fn cached_within_ttl() {
+        let cache = UsageCache::default();
+
+        assert_eq!(cache.get(100, || Ok(count(1))).unwrap()["work"], 1);
+        assert_eq!(
+            cache.get(100 + USAGE_TTL - 1, || Ok(count(2))).unwrap()["work"],
+            1
+        );
+
+        // Устаревшее значение пересчитывается
+        assert_eq!(
+            cache.get(100 + USAGE_TTL, || Ok(count(3))).unwrap()["work"],
+            3
+        );
+    }
+
+    #[test]
+    fn invalidated() {
+        let cache = UsageCache::default();
+
+        assert_eq!(cache.get(100, || Ok(count(1))).unwrap()["work"], 1);
+        cache.invalidate().unwrap();
+        assert_eq!(cache.get(101, || Ok(count(2))).unwrap()["work"], 2);
+    }
+}
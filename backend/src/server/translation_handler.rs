@@ -0,0 +1,77 @@
+use rocket::serde::json::Json;
+
+use crate::{
+    db::mongo::MongoConn,
+    err_forbidden,
+    errors::HubError,
+    model::{
+        account::security::{ApiKeyGuard, AuthGuard},
+        shrimp::Category,
+        translation::{Sibling, Translation},
+        validation::uuid_validation,
+    },
+};
+
+/// Одобренные переводы одобренной записи и их языки
+#[get("/<category>/<id>/translations")]
+pub fn translations<'f>(
+    _api_key: ApiKeyGuard,
+    client: MongoConn<'f>,
+    category: Category,
+    id: &str,
+) -> Result<Json<Vec<Sibling>>, HubError> {
+    let id = uuid_validation(id)?;
+    category.published_author(client.0.as_ref(), id)?;
+
+    Ok(Json(Translation::translations(
+        client.0.as_ref(),
+        &category,
+        id,
+    )?))
+}
+
+/// Связывание записей как переводов друг друга.
+/// Изменять группу может автор обеих записей или модератор.
+#[put("/<category>/<id>/translations/<other_id>")]
+pub fn link_translation<'f>(
+    _auth: AuthGuard,
+    client: MongoConn<'f>,
+    category: Category,
+    id: &str,
+    other_id: &str,
+) -> Result<Json<Vec<Sibling>>, HubError> {
+    let id = uuid_validation(id)?;
+    let other_id = uuid_validation(other_id)?;
+
+    for record_id in [id, other_id] {
+        let author = category.record_author(client.0.as_ref(), record_id)?;
+        if !_auth.0.can_manage(&author) {
+            return Err(err_forbidden!());
+        }
+    }
+
+    Ok(Json(Translation::link(
+        client.0.as_ref(),
+        category,
+        id,
+        other_id,
+    )?))
+}
+
+/// Исключение записи из ее группы переводов
+#[delete("/<category>/<id>/translations", rank = 2)]
+pub fn unlink_translation<'f>(
+    _auth: AuthGuard,
+    client: MongoConn<'f>,
+    category: Category,
+    id: &str,
+) -> Result<(), HubError> {
+    let id = uuid_validation(id)?;
+    let author = category.record_author(client.0.as_ref(), id)?;
+
+    if !_auth.0.can_manage(&author) {
+        return Err(err_forbidden!());
+    }
+
+    Translation::unlink(client.0.as_ref(), id)
+}
@@ -0,0 +1,52 @@
+use serde_json::{json, Value};
+
+use crate::{
+    db::mongo::MongoConn,
+    errors::HubError,
+    model::{
+        account::security::LevelGuard, shrimp::Category, trash::Trash, validation::uuid_validation,
+    },
+};
+
+/// Записи в корзине, начиная с удаленных последними
+#[get("/trash?<category>&<limit>")]
+pub fn trash_list<'f>(
+    _level: LevelGuard,
+    client: MongoConn<'f>,
+    category: Option<Vec<Category>>,
+    limit: Option<i64>,
+) -> Result<Value, HubError> {
+    let records = Trash::list(
+        client.0.as_ref(),
+        &category.unwrap_or(Category::all()),
+        limit.unwrap_or(50).clamp(1, 100),
+    )?;
+
+    let mut result: Vec<Value> = Vec::new();
+    for record in records {
+        result.push(Category::moderation(record)?);
+    }
+
+    Ok(json!(result))
+}
+
+#[put("/<category>/<id>/trash/restore")]
+pub fn trash_restore<'f>(
+    _level: LevelGuard,
+    client: MongoConn<'f>,
+    category: Category,
+    id: &str,
+) -> Result<(), HubError> {
+    Trash::restore(client.0.as_ref(), &category, uuid_validation(id)?)
+}
+
+/// Окончательное удаление записи из корзины, не дожидаясь истечения срока хранения
+#[delete("/<category>/<id>/trash/purge")]
+pub fn trash_purge<'f>(
+    _level: LevelGuard,
+    client: MongoConn<'f>,
+    category: Category,
+    id: &str,
+) -> Result<(), HubError> {
+    Trash::purge(client.0.as_ref(), &category, uuid_validation(id)?)
+}
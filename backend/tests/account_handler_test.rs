@@ -388,6 +388,7 @@ mod tariff_guard {
         match TestNewJoke::create_test_record(&client, Box::new(padawan)) {
             Ok((tokens, status, id)) => {
                 assert_eq!(status, Status::Ok);
+                common::approve(&client, "joke", &id);
 
                 let resp = client
                     .get(format!("{}/{}", path, id))
@@ -24,6 +24,14 @@ fn get_anecdote() {
         Ok((_, status, id)) => {
             assert_eq!(status, Status::Ok);
 
+            // Запись на модерации не видна потребителям
+            {
+                let resp = client.get(format!("{}/{}", path, id)).dispatch();
+                assert_eq!(resp.status(), Status::NotFound);
+            }
+
+            common::approve(&client, "anecdote", &id);
+
             // Получение записи
             {
                 let resp = client.get(format!("{}/{}", path, id)).dispatch();
@@ -0,0 +1,78 @@
+mod common;
+
+use rocket::http::{ContentType, Header, Status};
+
+use common::{
+    accounts::{self as account, TestMaster, TestPadawan},
+    joke::TestNewJoke,
+};
+
+#[test]
+fn author_profile() {
+    let client = common::test_client().lock().unwrap();
+
+    let (padawan, status, id) =
+        TestNewJoke::create_test_record(&client, Box::new(TestPadawan::default()))
+            .expect("test record");
+    assert_eq!(status, Status::Ok);
+
+    let resp = client
+        .put("/v1/account/profile")
+        .header(bearer!((padawan.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({"display_name": "Падаван", "bio": "Пишу шутки"}))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // С ключом Enterprice в записях есть идентификаторы, по ним проверяется запись на модерации
+    let resp = client
+        .get("/v1/authors/upadawan")
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let profile = common::response_json_value(resp);
+    let author = &profile["author"];
+    assert_eq!(author["username"], "upadawan");
+    assert_eq!(author["display_name"], "Падаван");
+    assert_eq!(author["bio"], "Пишу шутки");
+    assert!(author["joined_at"].is_string());
+
+    // Закрытые поля пользователя не попадают в профиль
+    for field in ["hash", "level", "tariff", "theme"] {
+        assert!(author.get(field).is_none(), "{} leaked", field);
+    }
+
+    // Запись на модерации не учитывается
+    let jokes = author["stats"]["records"]["joke"].as_u64().expect("count");
+    assert!(profile["records"]
+        .as_array()
+        .expect("records")
+        .iter()
+        .all(|record| record["id"].is_string() && record["id"] != id.as_str()));
+
+    let master =
+        account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+
+    let resp = client
+        .put(format!("/v1/joke/{}/status/approve", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client
+        .get("/v1/authors/upadawan?limit=1")
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    let profile = common::response_json_value(resp);
+
+    assert_eq!(profile["author"]["stats"]["records"]["joke"], jokes + 1);
+    assert_eq!(profile["records"][0]["id"], id.as_str());
+
+    let resp = client.get("/v1/authors/nobody").dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
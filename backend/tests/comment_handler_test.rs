@@ -0,0 +1,183 @@
+mod common;
+
+use rocket::http::{ContentType, Header, Status};
+
+use common::{
+    accounts::{self as account, TestMaster, TestPadawan},
+    joke::TestNewJoke,
+};
+
+#[test]
+fn comment_threads() {
+    let client = common::test_client().lock().unwrap();
+
+    let (padawan, status, id) =
+        TestNewJoke::create_test_record(&client, Box::new(TestPadawan::default()))
+            .expect("test record");
+    assert_eq!(status, Status::Ok);
+
+    let master =
+        account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+
+    // Запись на модерации комментировать и читать ее ветку нельзя
+    let resp = client
+        .post(format!("/v1/joke/{}/comments", id))
+        .header(bearer!((master.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({"text": "Смешно"}))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    let resp = client.get(format!("/v1/joke/{}/comments", id)).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    let resp = client
+        .put(format!("/v1/joke/{}/status/approve", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client
+        .post(format!("/v1/joke/{}/comments", id))
+        .header(bearer!((master.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({"text": "Смешно"}))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let root = common::response_json_value(resp)["id"]
+        .as_str()
+        .expect("comment id")
+        .to_string();
+
+    let resp = client
+        .post(format!("/v1/joke/{}/comments", id))
+        .header(bearer!((padawan.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({"text": "Спасибо", "parent_id": root}))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let reply = common::response_json_value(resp);
+    assert_eq!(reply["parent_id"], root.as_str());
+
+    let resp = client
+        .post(format!("/v1/joke/{}/comments", id))
+        .header(bearer!((master.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({"text": "Еще один комментарий"}))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Счетчик комментариев виден начиная с тарифа Basic
+    let resp = client
+        .get(format!("/v1/joke/{}", id))
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    assert_eq!(
+        common::response_json_value(resp)["_meta-data"]["comments_count"],
+        3
+    );
+
+    // Комментарии верхнего уровня постранично
+    let resp = client
+        .get(format!("/v1/joke/{}/comments?limit=1", id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let page = common::response_json_value(resp);
+    assert_eq!(page["comments"][0]["id"], root.as_str());
+    assert_eq!(page["comments"][0]["replies"], 1);
+
+    let next = page["next"].as_str().expect("next page").to_string();
+    let resp = client
+        .get(format!("/v1/joke/{}/comments?limit=1&cursor={}", id, next))
+        .dispatch();
+    let page = common::response_json_value(resp);
+    assert_eq!(page["comments"][0]["text"], "Еще один комментарий");
+    assert!(page["next"].is_null());
+
+    let resp = client
+        .get(format!("/v1/joke/{}/comments?parent={}", id, root))
+        .dispatch();
+    let page = common::response_json_value(resp);
+    assert_eq!(page["comments"][0]["text"], "Спасибо");
+
+    // Изменить комментарий может только автор
+    let resp = client
+        .put(format!("/v1/comments/{}", root))
+        .header(bearer!((padawan.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({"text": "Не смешно"}))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    let resp = client
+        .put(format!("/v1/comments/{}", root))
+        .header(bearer!((master.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({"text": "Очень смешно"}))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client
+        .post(format!("/v1/comments/{}/reaction/fire", root))
+        .header(bearer!((padawan.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Автор записи не может удалить чужой комментарий
+    let resp = client
+        .delete(format!("/v1/comments/{}", root))
+        .header(bearer!((padawan.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // Комментарий с ответами остается в ветке без текста
+    let resp = client
+        .delete(format!("/v1/comments/{}", root))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client.get(format!("/v1/joke/{}/comments", id)).dispatch();
+    let page = common::response_json_value(resp);
+    assert_eq!(page["comments"][0]["id"], root.as_str());
+    assert!(page["comments"][0]["text"].is_null());
+    assert!(page["comments"][0]["reactions"]["fire"].is_null());
+
+    let resp = client
+        .get(format!("/v1/joke/{}", id))
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    assert_eq!(
+        common::response_json_value(resp)["_meta-data"]["comments_count"],
+        2
+    );
+
+    let reply = reply["id"].as_str().expect("comment id").to_string();
+
+    // Комментарии отклоненной записи скрыты: на них нельзя реагировать и их нельзя изменить
+    let resp = client
+        .put(format!("/v1/joke/{}/status/reject", id))
+        .header(bearer!((master.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({ "reason": "Шутка уже есть в базе" }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client
+        .post(format!("/v1/comments/{}/reaction/fire", reply))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    let resp = client
+        .put(format!("/v1/comments/{}", reply))
+        .header(bearer!((padawan.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({"text": "Спасибо большое"}))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
@@ -2,9 +2,13 @@ pub mod accounts;
 pub mod anecdote;
 pub mod joke;
 pub mod punch;
+pub mod story;
 
 use once_cell::sync::OnceCell;
-use rocket::local::blocking::{Client, LocalResponse};
+use rocket::{
+    http::{Header, Status},
+    local::blocking::{Client, LocalResponse},
+};
 use serde::Deserialize;
 use serde_json::Value;
 use std::sync::Mutex;
@@ -66,3 +70,16 @@ pub fn response_json_value<'a>(response: LocalResponse<'a>) -> Value {
     let body = response.into_string().unwrap();
     serde_json::from_str(&body).expect("can't parse value")
 }
+
+/// Одобрение записи модератором: потребителям видны только одобренные записи
+#[allow(dead_code)]
+pub fn approve(client: &Client, category: &str, id: &str) {
+    let master = accounts::try_login(client, Box::new(accounts::TestMaster::default()))
+        .expect("valid tokens");
+
+    let resp = client
+        .put(format!("/v1/{}/{}/status/approve", category, id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+}
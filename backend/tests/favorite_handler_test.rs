@@ -0,0 +1,96 @@
+mod common;
+
+use rocket::http::{Header, Status};
+
+use common::{
+    accounts::{self as account, TestMaster, TestPadawan},
+    joke::TestNewJoke,
+};
+
+#[test]
+fn favorite() {
+    let client = common::test_client().lock().unwrap();
+
+    let (padawan, status, id) =
+        TestNewJoke::create_test_record(&client, Box::new(TestPadawan::default()))
+            .expect("test record");
+    assert_eq!(status, Status::Ok);
+
+    let master =
+        account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+
+    // Несуществующую запись добавить нельзя
+    let resp = client
+        .post("/v1/account/favorite/00000000-0000-0000-0000-000000000000")
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // Запись на модерации добавить нельзя
+    let resp = client
+        .post(format!("/v1/account/favorite/{}", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    let resp = client
+        .put(format!("/v1/joke/{}/status/approve", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client
+        .post(format!("/v1/account/favorite/{}", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client
+        .post(format!("/v1/account/favorite/{}", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+
+    // Одну и ту же запись могут добавить разные пользователи
+    let resp = client
+        .post(format!("/v1/account/favorite/{}", id))
+        .header(bearer!((padawan.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client
+        .get("/v1/account/favorite?limit=1")
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let favorites = common::response_json_value(resp);
+    assert_eq!(favorites["favorites"][0]["category"], "joke");
+    assert_eq!(favorites["favorites"][0]["record"]["id"], id.as_str());
+
+    // Удаление затрагивает только избранное вызывающего
+    let resp = client
+        .delete(format!("/v1/account/favorite/{}", id))
+        .header(bearer!((padawan.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client
+        .delete(format!("/v1/account/favorite/{}", id))
+        .header(bearer!((padawan.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    let resp = client
+        .get("/v1/account/favorite?limit=1")
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    let favorites = common::response_json_value(resp);
+    assert_eq!(favorites["favorites"][0]["record"]["id"], id.as_str());
+
+    let resp = client
+        .delete(format!("/v1/account/favorite/{}", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+}
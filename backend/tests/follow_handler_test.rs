@@ -0,0 +1,114 @@
+mod common;
+
+use rocket::http::{Header, Status};
+
+use common::{
+    accounts::{self as account, TestMaster, TestPadawan},
+    joke::TestNewJoke,
+};
+
+#[test]
+fn follow_and_feed() {
+    let client = common::test_client().lock().unwrap();
+
+    let master =
+        account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+
+    // Подписаться на себя нельзя
+    let resp = client
+        .put("/v1/account/following/tmaster")
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+
+    let resp = client
+        .put("/v1/account/following/nobody")
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    let resp = client
+        .put("/v1/account/following/upadawan")
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client.get("/v1/authors/upadawan/followers").dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert!(common::response_json_value(resp)["followers"]
+        .as_array()
+        .expect("followers")
+        .iter()
+        .any(|f| f["username"] == "tmaster"));
+
+    let resp = client.get("/v1/authors/tmaster/following").dispatch();
+    assert!(common::response_json_value(resp)["following"]
+        .as_array()
+        .expect("following")
+        .iter()
+        .any(|f| f["username"] == "upadawan"));
+
+    let (_, status, id) =
+        TestNewJoke::create_test_record(&client, Box::new(TestPadawan::default()))
+            .expect("test record");
+    assert_eq!(status, Status::Ok);
+
+    let (_, status, later_id) =
+        TestNewJoke::create_test_record(&client, Box::new(TestPadawan::default()))
+            .expect("test record");
+    assert_eq!(status, Status::Ok);
+
+    // Запись появляется в ленте после одобрения, лента идет по времени одобрения
+    for record_id in [&later_id, &id] {
+        let resp = client
+            .put(format!("/v1/joke/{}/status/approve", record_id))
+            .header(bearer!((master.access_token)))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+    }
+
+    let resp = client
+        .get("/v1/feed?limit=1")
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let feed = common::response_json_value(resp);
+    assert_eq!(feed["records"][0]["id"], id.as_str());
+    assert_eq!(feed["records"][0]["_meta-data"]["author"], "upadawan");
+
+    // Следующая страница продолжается записью, одобренной раньше
+    let resp = client
+        .get(format!(
+            "/v1/feed?limit=1&cursor={}",
+            feed["next"].as_str().expect("next page")
+        ))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(
+        common::response_json_value(resp)["records"][0]["id"],
+        later_id.as_str()
+    );
+
+    let resp = client
+        .delete("/v1/account/following/upadawan")
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client
+        .delete("/v1/account/following/upadawan")
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    let resp = client
+        .get("/v1/feed")
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert!(common::response_json_value(resp)["records"]
+        .as_array()
+        .expect("records")
+        .iter()
+        .all(|record| record["_meta-data"]["author"] != "upadawan"));
+}
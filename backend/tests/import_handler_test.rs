@@ -0,0 +1,193 @@
+mod common;
+
+use rand::{distributions::Alphanumeric, Rng};
+use rocket::http::{ContentType, Header, Status};
+use serde::Deserialize;
+
+use common::accounts::{self as account, TestMaster, TestPadawan};
+
+#[derive(Deserialize, Debug)]
+struct ImportFailureResp {
+    row: usize,
+    error: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct ImportResp {
+    total: usize,
+    inserted: usize,
+    duplicates: usize,
+    failed: Vec<ImportFailureResp>,
+}
+
+/// Строки JSON Lines: две корректные записи и две с ошибками.
+/// Тексты солятся, чтобы первая загрузка в тесте всегда вставляла записи.
+fn jsonl() -> String {
+    let salt = salt();
+
+    [
+        format!(r#"{{"category": "joke", "text": "Imported joke about a cat {}"}}"#, salt),
+        format!(
+            r#"{{"category": "punch", "setup": "Как называют импортированный панч {}?", "punchline": "Привозным"}}"#,
+            salt
+        ),
+        String::from(r#"{"category": "joke", "text": "short"}"#),
+        String::from(r#"{"category": "story", "title": "Not supported"}"#),
+    ]
+    .join("\n")
+}
+
+fn salt() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(16)
+        .map(char::from)
+        .collect()
+}
+
+#[test]
+fn import_by_padawan() {
+    let client = common::test_client().lock().unwrap();
+    let tokens =
+        account::try_login(&client, Box::new(TestPadawan::default())).expect("valid tokens");
+
+    let resp = client
+        .post("/v1/import")
+        .header(bearer!((tokens.access_token)))
+        .body(jsonl())
+        .dispatch();
+
+    assert_eq!(resp.status(), Status::Forbidden);
+}
+
+#[test]
+fn import_jsonl_is_idempotent() {
+    let client = common::test_client().lock().unwrap();
+    let tokens =
+        account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+
+    let data = jsonl();
+    for (inserted, duplicates) in [(2, 0), (0, 2)] {
+        let resp = client
+            .post("/v1/import?format=jsonl")
+            .header(bearer!((tokens.access_token)))
+            .body(&data)
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+
+        #[allow(unused_parens)]
+        let body = assert_body!(resp, (ImportResp));
+
+        assert_eq!(body.total, 4);
+        assert_eq!(body.failed.len(), 2);
+        assert_eq!(body.failed[0].row, 3);
+        assert_eq!(body.failed[1].row, 4);
+        assert!(!body.failed[0].error.is_empty());
+
+        // Повторная загрузка ничего не добавляет
+        assert_eq!(body.inserted, inserted);
+        assert_eq!(body.duplicates, duplicates);
+    }
+}
+
+#[test]
+fn import_csv() {
+    let client = common::test_client().lock().unwrap();
+    let tokens =
+        account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+
+    let resp = client
+        .post("/v1/import?format=csv")
+        .header(bearer!((tokens.access_token)))
+        .header(ContentType::CSV)
+        .body(format!(
+            concat!(
+                "category,text,tags\n",
+                "anecdote,\"Импортированный анекдот, в котором есть запятая {0}\",import;csv\n",
+                "anecdote,\"Импортированный анекдот, в котором есть запятая {0}\",import\n",
+            ),
+            salt()
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    #[allow(unused_parens)]
+    let body = assert_body!(resp, (ImportResp));
+
+    assert_eq!(body.total, 2);
+    assert!(body.failed.is_empty());
+    assert_eq!(body.inserted, 1);
+    assert_eq!(body.duplicates, 1);
+}
+
+#[test]
+fn import_fortune() {
+    let client = common::test_client().lock().unwrap();
+    let tokens =
+        account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+
+    let resp = client
+        .post("/v1/import?format=fortune&category=anecdote&tag=fortune_test&author=tpadawan")
+        .header(bearer!((tokens.access_token)))
+        .body(format!(
+            "An imported fortune cookie {0}\nwith two lines of wisdom\n%\nAnother fortune cookie from the pack {0}\n%\n",
+            salt()
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    #[allow(unused_parens)]
+    let body = assert_body!(resp, (ImportResp));
+
+    assert_eq!(body.total, 2);
+    assert!(body.failed.is_empty());
+    assert_eq!(body.inserted, 2);
+    assert_eq!(body.duplicates, 0);
+
+    // Автором может быть только существующий пользователь
+    let resp = client
+        .post("/v1/import?format=fortune&category=anecdote&author=nobody_imports_this")
+        .header(bearer!((tokens.access_token)))
+        .body("A fortune cookie without an author\n%\n")
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // Панчи не хранятся в файлах fortune
+    let resp = client
+        .post("/v1/import?format=fortune&category=punch")
+        .header(bearer!((tokens.access_token)))
+        .body("Setup without punchline\n%\n")
+        .dispatch();
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+}
+
+#[test]
+fn export_fortune() {
+    let client = common::test_client().lock().unwrap();
+    let tokens =
+        account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+
+    let resp = client
+        .get("/v1/export/fortune?category=joke&tag=for_test")
+        .header(bearer!((tokens.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let text = resp.into_string().expect("fortune file");
+    let cookies = text.matches("\n%\n").count();
+    assert!(cookies > 0);
+
+    let resp = client
+        .get("/v1/export/fortune?file=dat&category=joke&tag=for_test")
+        .header(bearer!((tokens.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let dat = resp.into_bytes().expect("strfile index");
+    let word = |i: usize| u32::from_be_bytes([dat[i], dat[i + 1], dat[i + 2], dat[i + 3]]);
+
+    assert_eq!(word(0), 2);
+    assert_eq!(word(4) as usize, cookies);
+    assert_eq!(dat[20], b'%');
+    assert_eq!(word(dat.len() - 4) as usize, text.len());
+}
@@ -1,9 +1,9 @@
 mod common;
 
-use rocket::http::{Header, Status};
+use rocket::http::{ContentType, Header, Status};
 
 use common::{
-    accounts::{TestMaster, TestPadawan},
+    accounts::{self as account, TestMaster, TestPadawan},
     joke::TestNewJoke,
 };
 
@@ -18,6 +18,63 @@ fn create_joke() {
     }
 }
 
+#[test]
+fn create_joke_with_declared_language() {
+    let path: &str = "/v1/joke/new";
+    let client = common::test_client().lock().unwrap();
+    let tokens =
+        account::try_login(&client, Box::new(TestPadawan::default())).expect("valid tokens");
+
+    // Кириллический текст не может быть английским
+    let resp = client
+        .post(path)
+        .header(bearer!((tokens.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({"text": TestNewJoke::default().text, "lang": "en"}))
+        .dispatch();
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+
+    // Неподдерживаемый язык
+    let resp = client
+        .post(path)
+        .header(bearer!((tokens.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({"text": TestNewJoke::default().text, "lang": "xx"}))
+        .dispatch();
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+
+    let resp = client
+        .post(path)
+        .header(bearer!((tokens.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({"text": TestNewJoke::default().text, "lang": "ru"}))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let id = common::response_json_value(resp)["id"]
+        .as_str()
+        .expect("record id")
+        .to_string();
+    common::approve(&client, "joke", &id);
+
+    let resp = client
+        .get(format!("/v1/joke/{}", id))
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    let tail = &common::response_json_value(resp)["_meta-data"];
+
+    assert_eq!(tail["language"], "ru");
+    assert_eq!(tail["language_detection"]["declared"], true);
+    assert_eq!(tail["language_detection"]["detected"], "ru");
+    assert!(
+        tail["language_detection"]["confidence"]
+            .as_f64()
+            .expect("confidence")
+            > 0.0
+    );
+}
+
 #[test]
 fn get_punch() {
     let path: &str = "/v1/joke/";
@@ -28,12 +85,35 @@ fn get_punch() {
         Ok((_, status, id)) => {
             assert_eq!(status, Status::Ok);
 
+            // Запись на модерации не видна потребителям
+            {
+                let resp = client.get(format!("{}/{}", path, id)).dispatch();
+                assert_eq!(resp.status(), Status::NotFound);
+            }
+
+            common::approve(&client, "joke", &id);
+
             // Получение записи
             {
                 let resp = client.get(format!("{}/{}", path, id)).dispatch();
                 assert_eq!(resp.status(), Status::Ok);
             }
 
+            // Язык записи хранится кодом ISO 639-1
+            {
+                let resp = client
+                    .get(format!("{}/{}", path, id))
+                    .header(apikey!(
+                        "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+                    ))
+                    .dispatch();
+                assert_eq!(resp.status(), Status::Ok);
+                assert_eq!(
+                    common::response_json_value(resp)["_meta-data"]["language"],
+                    "ru"
+                );
+            }
+
             // Получение по неправильному uuid
             {
                 let resp = client.get(format!("{}/invalid-format", path)).dispatch();
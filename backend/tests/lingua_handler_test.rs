@@ -0,0 +1,90 @@
+mod common;
+
+use rocket::http::{ContentType, Header, Status};
+use std::net::SocketAddr;
+
+#[test]
+fn detect_languages() {
+    let client = common::test_client().lock().unwrap();
+
+    let resp = client
+        .post("/v1/lingua/detect")
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .header(ContentType::JSON)
+        .body(json_string!({
+            "texts": [
+                "Как каннибал называет Пашу? Паштет.",
+                "Why did the chicken cross the road? To get to the other side."
+            ]
+        }))
+        .dispatch();
+
+    assert_eq!(resp.status(), Status::Ok);
+    assert!(resp.headers().get_one("X-RateLimit-Remaining").is_some());
+
+    let value = common::response_json_value(resp);
+    assert_eq!(value[0]["language"], "ru");
+    assert_eq!(value[0]["candidates"][0]["language"], "ru");
+    assert_eq!(value[1]["language"], "en");
+    assert!(value[1]["confidence"].as_f64().expect("confidence") > 0.0);
+
+    // Пустой пакет
+    let resp = client
+        .post("/v1/lingua/detect")
+        .header(ContentType::JSON)
+        .body(json_string!({ "texts": [] }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+}
+
+#[test]
+fn detect_languages_rate_limit() {
+    let client = common::test_client().lock().unwrap();
+    let texts: Vec<String> = (0..10).map(|i| format!("Hello world {}", i)).collect();
+    let remote: SocketAddr = "10.0.0.1:8000".parse().unwrap();
+
+    // Без Api-Key и без адреса клиента квота не определена
+    let resp = client
+        .post("/v1/lingua/detect")
+        .header(ContentType::JSON)
+        .body(json_string!({ "texts": &texts }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // Без Api-Key действует тариф Free: не больше 10 текстов за запрос
+    let too_many = [&texts[..], &["one more".to_string()]].concat();
+    let resp = client
+        .post("/v1/lingua/detect")
+        .remote(remote)
+        .header(ContentType::JSON)
+        .body(json_string!({ "texts": too_many }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+
+    // и не больше 30 текстов в минуту, даже если запросы попали в два соседних окна
+    let responses: Vec<(Status, Option<String>)> = (0..7)
+        .map(|_| {
+            let resp = client
+                .post("/v1/lingua/detect")
+                .remote(remote)
+                .header(ContentType::JSON)
+                .body(json_string!({ "texts": &texts }))
+                .dispatch();
+
+            let retry = resp.headers().get_one("Retry-After").map(String::from);
+            (resp.status(), retry)
+        })
+        .collect();
+
+    assert_eq!(responses[0].0, Status::Ok);
+
+    // Ответ 429 подсказывает, когда повторить запрос
+    let (_, retry) = responses
+        .iter()
+        .find(|(status, _)| *status == Status::TooManyRequests)
+        .expect("rate limited response");
+    let retry: i64 = retry.as_deref().expect("Retry-After").parse().unwrap();
+    assert!((0..=60).contains(&retry));
+}
@@ -0,0 +1,455 @@
+mod common;
+
+use rand::{distributions::Alphanumeric, Rng};
+use rocket::http::{ContentType, Header, Status};
+use serde::Deserialize;
+
+use common::{
+    accounts::{self as account, TestMaster, TestPadawan},
+    joke::TestNewJoke,
+    RegResp,
+};
+
+#[derive(Deserialize, Debug)]
+struct QueueResp {
+    id: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct FlagAuditResp {
+    flag: String,
+    value: bool,
+}
+
+#[test]
+fn self_flagged_record() {
+    let client = common::test_client().lock().unwrap();
+    let tokens =
+        account::try_login(&client, Box::new(TestPadawan::default())).expect("valid tokens");
+
+    let resp = client
+        .post("/v1/joke/new")
+        .header(bearer!((tokens.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({
+            "text": TestNewJoke::default().text,
+            "flags": ["nsfw", "political"]
+        }))
+        .dispatch();
+
+    assert_eq!(resp.status(), Status::Ok);
+    assert_body!(resp, RegResp);
+
+    // Неизвестный флаг
+    let resp = client
+        .post("/v1/joke/new")
+        .header(bearer!((tokens.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({
+            "text": TestNewJoke::default().text,
+            "flags": ["unknown"]
+        }))
+        .dispatch();
+
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+}
+
+#[test]
+fn flag_by_padawan() {
+    let path: &str = "/v1/joke";
+    let client = common::test_client().lock().unwrap();
+    let padawan = TestPadawan::default();
+
+    match TestNewJoke::create_test_record(&client, Box::new(padawan)) {
+        Ok((tokens, status, id)) => {
+            assert_eq!(status, Status::Ok);
+
+            let resp = client
+                .put(format!("{}/{}/flags/nsfw", path, id))
+                .header(bearer!((tokens.access_token)))
+                .dispatch();
+
+            assert_eq!(resp.status(), Status::Forbidden);
+        }
+
+        Err(err) => assert!(false, "\n\nFaild to create test record: {}\n\n", err),
+    }
+}
+
+#[test]
+fn flag_by_master() {
+    let path: &str = "/v1/joke";
+    let client = common::test_client().lock().unwrap();
+    let master = TestMaster::default();
+
+    match TestNewJoke::create_test_record(&client, Box::new(master)) {
+        Ok((tokens, status, id)) => {
+            assert_eq!(status, Status::Ok);
+
+            // Установка флага
+            {
+                let resp = client
+                    .put(format!("{}/{}/flags/religious", path, id))
+                    .header(bearer!((tokens.access_token)))
+                    .dispatch();
+
+                assert_eq!(resp.status(), Status::Ok);
+            }
+
+            // Повторная установка не попадает в журнал
+            {
+                let resp = client
+                    .put(format!("{}/{}/flags/religious", path, id))
+                    .header(bearer!((tokens.access_token)))
+                    .dispatch();
+
+                assert_eq!(resp.status(), Status::Ok);
+            }
+
+            // Снятие флага
+            {
+                let resp = client
+                    .delete(format!("{}/{}/flags/religious", path, id))
+                    .header(bearer!((tokens.access_token)))
+                    .dispatch();
+
+                assert_eq!(resp.status(), Status::Ok);
+            }
+
+            // Неизвестный флаг
+            {
+                let resp = client
+                    .put(format!("{}/{}/flags/unknown", path, id))
+                    .header(bearer!((tokens.access_token)))
+                    .dispatch();
+
+                assert_eq!(resp.status(), Status::UnprocessableEntity);
+            }
+
+            // Журнал изменений
+            {
+                let resp = client
+                    .get(format!("{}/{}/flags/audit", path, id))
+                    .header(bearer!((tokens.access_token)))
+                    .dispatch();
+
+                assert_eq!(resp.status(), Status::Ok);
+
+                #[allow(unused_parens)]
+                let body = assert_body!(resp, (Vec<FlagAuditResp>));
+
+                assert_eq!(body.len(), 2);
+                assert_eq!(body[0].flag, "religious");
+                assert!(!body[0].value);
+                assert!(body[1].value);
+            }
+        }
+
+        Err(err) => assert!(false, "\n\nFaild to create test record: {}\n\n", err),
+    }
+}
+
+#[test]
+fn queue_by_padawan() {
+    let client = common::test_client().lock().unwrap();
+    let tokens =
+        account::try_login(&client, Box::new(TestPadawan::default())).expect("valid tokens");
+
+    let resp = client
+        .get("/v1/queue")
+        .header(bearer!((tokens.access_token)))
+        .dispatch();
+
+    assert_eq!(resp.status(), Status::Forbidden);
+}
+
+#[test]
+fn review_by_master() {
+    let path: &str = "/v1/joke";
+    let client = common::test_client().lock().unwrap();
+    let padawan = TestPadawan::default();
+
+    match TestNewJoke::create_test_record(&client, Box::new(padawan)) {
+        Ok((_, status, id)) => {
+            assert_eq!(status, Status::Ok);
+
+            let tokens =
+                account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+
+            // Новая запись ожидает проверки
+            {
+                let resp = client
+                    .get(format!("/v1/queue?category=joke&author=upadawan&id={}", id))
+                    .header(bearer!((tokens.access_token)))
+                    .dispatch();
+
+                assert_eq!(resp.status(), Status::Ok);
+
+                #[allow(unused_parens)]
+                let body = assert_body!(resp, (Vec<QueueResp>));
+                assert_eq!(body.len(), 1);
+                assert_eq!(body[0].id, id);
+            }
+
+            // Слишком короткая причина отказа
+            {
+                let resp = client
+                    .put(format!("{}/{}/status/reject", path, id))
+                    .header(bearer!((tokens.access_token)))
+                    .header(ContentType::JSON)
+                    .body(json_string!({ "reason": "Нет" }))
+                    .dispatch();
+
+                assert_eq!(resp.status(), Status::UnprocessableEntity);
+            }
+
+            // Отказ
+            {
+                let resp = client
+                    .put(format!("{}/{}/status/reject", path, id))
+                    .header(bearer!((tokens.access_token)))
+                    .header(ContentType::JSON)
+                    .body(json_string!({ "reason": "Шутка уже есть в базе" }))
+                    .dispatch();
+
+                assert_eq!(resp.status(), Status::Ok);
+            }
+
+            // Одобрение после пересмотра
+            {
+                let resp = client
+                    .put(format!("{}/{}/status/approve", path, id))
+                    .header(bearer!((tokens.access_token)))
+                    .dispatch();
+
+                assert_eq!(resp.status(), Status::Ok);
+            }
+
+            // Несуществующая запись
+            {
+                let resp = client
+                    .put(format!(
+                        "{}/{}/status/approve",
+                        path, "00000000-0000-0000-0000-000000000000"
+                    ))
+                    .header(bearer!((tokens.access_token)))
+                    .dispatch();
+
+                assert_eq!(resp.status(), Status::NotFound);
+            }
+        }
+
+        Err(err) => assert!(false, "\n\nFaild to create test record: {}\n\n", err),
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct ErrorResp {
+    details: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ClusterRecordResp {
+    id: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct ClusterResp {
+    records: Vec<ClusterRecordResp>,
+    similarity: f64,
+}
+
+#[test]
+fn near_duplicate_rejected() {
+    let client = common::test_client().lock().unwrap();
+    let tokens =
+        account::try_login(&client, Box::new(TestPadawan::default())).expect("valid tokens");
+
+    let salt = TestNewJoke::default()
+        .text
+        .rsplit(' ')
+        .next()
+        .unwrap()
+        .to_string();
+    let text = format!(
+        "Штирлиц шел по лесу и увидел {}. Это была ель, понял Штирлиц.",
+        salt
+    );
+
+    let resp = client
+        .post("/v1/joke/new")
+        .header(bearer!((tokens.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({ "text": &text }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let id = common::response_json_value(resp)["id"]
+        .as_str()
+        .expect("record id")
+        .to_string();
+
+    // Лишние пробелы, регистр и знаки препинания не делают запись новой
+    let resp = client
+        .post("/v1/anecdote/new")
+        .header(bearer!((tokens.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({
+            "text": format!("штирлиц  шел по лесу и увидел {}... Это была ель - понял Штирлиц!", salt)
+        }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+
+    #[allow(unused_parens)]
+    let body = assert_body!(resp, (ErrorResp));
+    assert!(body.details.contains(&format!("joke/{}", id)));
+
+    // Отчет о группах дубликатов доступен только модераторам
+    let resp = client
+        .get("/v1/duplicates")
+        .header(bearer!((tokens.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+}
+
+#[test]
+fn duplicates_report() {
+    let client = common::test_client().lock().unwrap();
+    let tokens =
+        account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+
+    let resp = client
+        .get("/v1/duplicates?threshold=0.5")
+        .header(bearer!((tokens.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    #[allow(unused_parens)]
+    let body = assert_body!(resp, (Vec<ClusterResp>));
+    for cluster in body {
+        assert!(cluster.records.len() > 1);
+        assert!(cluster.records.iter().all(|r| !r.id.is_empty()));
+        assert!(cluster.similarity >= 0.5);
+    }
+}
+
+#[test]
+fn lexicon_flags() {
+    let client = common::test_client().lock().unwrap();
+    let tokens =
+        account::try_login(&client, Box::new(TestPadawan::default())).expect("valid tokens");
+
+    let master =
+        account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+
+    let tag = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(8)
+        .map(char::from)
+        .collect::<String>()
+        .to_lowercase();
+
+    let resp = client
+        .post("/v1/joke/new")
+        .header(bearer!((tokens.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({
+            "text": format!("Встретились в церкви депутат и {}", TestNewJoke::default().text),
+            "flags": ["nsfw"],
+            "tags": [tag]
+        }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let id = common::response_json_value(resp)["id"]
+        .as_str()
+        .expect("record id")
+        .to_string();
+
+    let resp = client
+        .get(format!("/v1/queue?category=joke&tag={}", tag))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Флаги автора сохраняются, флаги по словарям добавляются к ним
+    let queue = common::response_json_value(resp);
+    let tail = &queue[0]["_meta-data"];
+    assert_eq!(queue[0]["id"], id.as_str());
+    assert_eq!(tail["flags"]["nsfw"], true);
+    assert_eq!(tail["flags"]["religious"], true);
+    assert_eq!(tail["flags"]["political"], true);
+    assert_eq!(tail["flags"]["racist"], false);
+    assert_eq!(tail["triggers"]["religious"][0], "церк*");
+    assert_eq!(tail["triggers"]["political"][0], "депутат*");
+
+    // Потребители видят флаги, но не термины словарей
+    let resp = client
+        .put(format!("/v1/joke/{}/status/approve", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client
+        .get(format!("/v1/joke/{}", id))
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let tail = &common::response_json_value(resp)["_meta-data"];
+    assert_eq!(tail["flags"]["religious"], true);
+    assert!(tail["triggers"].is_null());
+
+    // Перечитать словари может только модератор
+    let resp = client
+        .post("/v1/lexicons/reload")
+        .header(bearer!((tokens.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    let resp = client
+        .post("/v1/lexicons/reload")
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert!(
+        common::response_json_value(resp)["religious"]
+            .as_u64()
+            .unwrap_or_default()
+            > 0
+    );
+}
+
+#[test]
+fn mislabeled_records() {
+    let client = common::test_client().lock().unwrap();
+
+    let tokens =
+        account::try_login(&client, Box::new(TestPadawan::default())).expect("valid tokens");
+    let resp = client
+        .get("/v1/mislabeled")
+        .header(bearer!((tokens.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    let tokens =
+        account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+    let resp = client
+        .get("/v1/mislabeled?confidence=1&category=joke&lang=ru")
+        .header(bearer!((tokens.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let records = common::response_json_value(resp);
+    let confidences: Vec<f64> = records
+        .as_array()
+        .expect("records")
+        .iter()
+        .map(|r| {
+            r["_meta-data"]["language_detection"]["confidence"]
+                .as_f64()
+                .expect("confidence")
+        })
+        .collect();
+    assert!(confidences.windows(2).all(|w| w[0] <= w[1]));
+}
@@ -0,0 +1,228 @@
+mod common;
+
+use rand::{distributions::Alphanumeric, Rng};
+use rocket::http::{ContentType, Header, Status};
+use serde::Deserialize;
+
+use common::{
+    accounts::{self as account, TestMaster, TestPadawan},
+    joke::TestNewJoke,
+    punch::TestNewPunch,
+};
+
+#[derive(Deserialize, Debug)]
+struct RevisionResp {
+    id: String,
+}
+
+#[test]
+fn update_joke_by_author() {
+    let path: &str = "/v1/joke";
+    let client = common::test_client().lock().unwrap();
+    let padawan = TestPadawan::default();
+
+    match TestNewJoke::create_test_record(&client, Box::new(padawan)) {
+        Ok((tokens, status, id)) => {
+            assert_eq!(status, Status::Ok);
+
+            // Редактирование записи автором
+            {
+                let resp = client
+                    .put(format!("{}/{}", path, id))
+                    .header(bearer!((tokens.access_token)))
+                    .header(ContentType::JSON)
+                    .body(json_string!({
+                        "text": format!("{} (исправлено)", TestNewJoke::default().text),
+                    }))
+                    .dispatch();
+
+                assert_eq!(resp.status(), Status::Ok);
+            }
+
+            // Невалидное тело записи
+            {
+                let resp = client
+                    .put(format!("{}/{}", path, id))
+                    .header(bearer!((tokens.access_token)))
+                    .header(ContentType::JSON)
+                    .body(json_string!({ "text": "Как?" }))
+                    .dispatch();
+
+                assert_eq!(resp.status(), Status::UnprocessableEntity);
+            }
+
+            // Прошлая версия попала в историю
+            let revisions = {
+                let resp = client
+                    .get(format!("{}/{}/revisions", path, id))
+                    .header(bearer!((tokens.access_token)))
+                    .dispatch();
+
+                assert_eq!(resp.status(), Status::Ok);
+
+                #[allow(unused_parens)]
+                let body = assert_body!(resp, (Vec<RevisionResp>));
+                body
+            };
+
+            assert_eq!(revisions.len(), 1);
+
+            // Откат к прошлой версии
+            {
+                let resp = client
+                    .put(format!("{}/{}/revisions/{}", path, id, revisions[0].id))
+                    .header(bearer!((tokens.access_token)))
+                    .dispatch();
+
+                assert_eq!(resp.status(), Status::Ok);
+            }
+
+            // Откат тоже сохраняет версию
+            {
+                let resp = client
+                    .get(format!("{}/{}/revisions", path, id))
+                    .header(bearer!((tokens.access_token)))
+                    .dispatch();
+
+                assert_eq!(resp.status(), Status::Ok);
+
+                #[allow(unused_parens)]
+                let body = assert_body!(resp, (Vec<RevisionResp>));
+                assert_eq!(body.len(), 2);
+            }
+        }
+
+        Err(err) => assert!(false, "\n\nFaild to create test record: {}\n\n", err),
+    }
+}
+
+#[test]
+fn update_punch_by_stranger() {
+    let path: &str = "/v1/punch";
+    let client = common::test_client().lock().unwrap();
+    let author = TestPadawan::default();
+    let stranger = TestPadawan::new("ustranger", "password2022");
+
+    match TestNewPunch::create_test_record(&client, Box::new(author)) {
+        Ok((_, status, id)) => {
+            assert_eq!(status, Status::Ok);
+
+            let tokens = account::try_login(&client, Box::new(stranger)).expect("valid tokens");
+            let punch = TestNewPunch::default();
+
+            let resp = client
+                .put(format!("{}/{}", path, id))
+                .header(bearer!((tokens.access_token)))
+                .header(ContentType::JSON)
+                .body(json_string!({
+                    "setup": punch.setup,
+                    "punchline": punch.punchline
+                }))
+                .dispatch();
+
+            assert_eq!(resp.status(), Status::Forbidden);
+
+            let resp = client
+                .get(format!("{}/{}/revisions", path, id))
+                .header(bearer!((tokens.access_token)))
+                .dispatch();
+
+            assert_eq!(resp.status(), Status::Forbidden);
+        }
+
+        Err(err) => assert!(false, "\n\nFaild to create test record: {}\n\n", err),
+    }
+}
+
+#[test]
+fn update_punch_by_master() {
+    let path: &str = "/v1/punch";
+    let client = common::test_client().lock().unwrap();
+    let author = TestPadawan::default();
+    let master = TestMaster::default();
+
+    match TestNewPunch::create_test_record(&client, Box::new(author)) {
+        Ok((_, status, id)) => {
+            assert_eq!(status, Status::Ok);
+
+            let tokens = account::try_login(&client, Box::new(master)).expect("valid tokens");
+            let punch = TestNewPunch::default();
+
+            let resp = client
+                .put(format!("{}/{}", path, id))
+                .header(bearer!((tokens.access_token)))
+                .header(ContentType::JSON)
+                .body(json_string!({
+                    "setup": punch.setup,
+                    "punchline": punch.punchline
+                }))
+                .dispatch();
+
+            assert_eq!(resp.status(), Status::Ok);
+        }
+
+        Err(err) => assert!(false, "\n\nFaild to create test record: {}\n\n", err),
+    }
+}
+
+#[test]
+fn update_approved_joke_returns_to_moderation() {
+    let client = common::test_client().lock().unwrap();
+
+    // Уникальный тег, чтобы случайная запись выбиралась только из тестовой
+    let tag: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(8)
+        .map(char::from)
+        .collect::<String>()
+        .to_lowercase();
+    let joke = TestNewJoke::default();
+
+    let author =
+        account::try_login(&client, Box::new(TestPadawan::default())).expect("valid tokens");
+    let master =
+        account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+
+    let resp = client
+        .post("/v1/joke/new")
+        .header(bearer!((author.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({"text": joke.text, "tags": [tag]}))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let id = common::response_json_value(resp)["id"]
+        .as_str()
+        .expect("record id")
+        .to_string();
+
+    let resp = client
+        .put(format!("/v1/joke/{}/status/approve", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let random = format!("/v1/random?category=joke&tag={}", tag);
+    let resp = client
+        .get(random.as_str())
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(common::response_json_value(resp)["id"], id.as_str());
+
+    // Измененная запись снова ждет модератора и не показывается
+    let resp = client
+        .put(format!("/v1/joke/{}", id))
+        .header(bearer!((author.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({
+            "text": format!("{} (исправлено)", joke.text),
+            "tags": [tag]
+        }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client.get(random.as_str()).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use crate::common::response_json_value;
+use rocket::http::Status;
+use test_case::test_case;
+
+#[test_case("q=%D0%A8%D1%82%D0%B8%D1%80%D0%BB%D0%B8%D1%86%D0%B0&lang=russian", Status::Ok, 1 ; "russian_stemmed" )]
+#[test_case("q=atom", Status::Ok, 1 ; "english_stemmed" )]
+#[test_case("q=atoms&lang=english&tag=search_test", Status::Ok, 1 ; "with_tag" )]
+#[test_case("q=atoms&category=joke", Status::Ok, 0 ; "other_category" )]
+#[test_case("q=%D0%A8%D1%82%D0%B8%D1%80%D0%BB%D0%B8%D1%86&tag=for_test", Status::Ok, 0 ; "non_matching_tag" )]
+#[test_case("q=%D0%A8%D1%82%D0%B8%D1%80%D0%BB%D0%B8%D1%86&author=noex", Status::Ok, 0 ; "non_existent_author" )]
+#[test_case("q=a", Status::UnprocessableEntity, 0 ; "short_query" )]
+#[test_case("", Status::UnprocessableEntity, 0 ; "empty_query" )]
+fn search(filter: &str, status: Status, count: usize) {
+    let client = common::test_client().lock().unwrap();
+
+    let resp = client.get(format!("/v1/search?{}", filter)).dispatch();
+    assert_eq!(resp.status(), status);
+
+    if status == Status::Ok {
+        let value = response_json_value(resp);
+        assert_eq!(value["records"].as_array().map(|r| r.len()), Some(count));
+    }
+}
+
+#[test]
+fn search_highlights() {
+    let client = common::test_client().lock().unwrap();
+
+    let resp = client
+        .get("/v1/search?q=%D0%A8%D1%82%D0%B8%D1%80%D0%BB%D0%B8%D1%86%D0%B0&category=anecdote")
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let value = response_json_value(resp);
+    let highlights = value["records"][0]["highlights"][0]
+        .as_str()
+        .expect("highlighted fragment");
+
+    assert!(highlights.contains("<em>Штирлиц</em>"));
+    assert!(value["records"][0]["score"].as_f64().is_some());
+}
@@ -1,8 +1,16 @@
 mod common;
 
-use crate::common::response_json_value;
+use crate::common::{
+    accounts::{self as account, TestMaster, TestPadawan},
+    joke::TestNewJoke,
+    response_json_value,
+};
 use jokehub::model::shrimp::{ReactionKind, Shrimp};
-use rocket::http::{Header, Status};
+use rand::{distributions::Alphanumeric, Rng};
+use rocket::{
+    http::{ContentType, Header, Status},
+    local::blocking::Client,
+};
 use test_case::test_case;
 
 #[test_case("", Status::Ok ; "empty_filter" )]
@@ -12,8 +20,10 @@ use test_case::test_case;
 #[test_case("category=joke&tag=for_test&author=noex", Status::NotFound ; "existent_tag__not_existent_author" )]
 #[test_case("category=joke&tag=for_test&author=shavedkiwi&lang=russian", Status::Ok ; "existent_tag__existent_author__valid_lang" )]
 #[test_case("category=joke&tag=for_test&author=shavedkiwi&lang=english", Status::Ok ; "existent_tag__existent_author__valid_lang_2" )]
+#[test_case("category=joke&tag=for_test&author=shavedkiwi&lang=en", Status::Ok ; "existent_tag__existent_author__iso_lang" )]
 #[test_case("category=joke&author=shavedkiwi&lang=english", Status::Ok ; "existent_tag__existent_author__valid_lang_3" )]
 #[test_case("category=joke&author=shavedkiwi&lang=fr", Status::NotFound ; "existent_tag__existent_author__invalid_lang" )]
+#[test_case("category=joke&author=shavedkiwi&lang=xx", Status::UnprocessableEntity ; "existent_tag__existent_author__unknown_lang" )]
 #[test_case("category=joke&flag=sexist", Status::NotFound ; "non_existent_flag" )]
 #[test_case("category=joke&flag=nsfw", Status::Ok ; "existent_flag" )]
 #[test_case("category=joke&flag=religious&tag=for_test", Status::Ok ; "existent_flag_and_existent_tag" )]
@@ -23,6 +33,12 @@ use test_case::test_case;
 #[test_case("category=joke&flag=religious", Status::Ok ; "valid" )]
 #[test_case("category=joke&flag=religious&flag=racist&lang=russian", Status::Ok ; "valid_1" )]
 #[test_case("category=joke&flag=religious&flag=racist&lang=russian&tag=for_test", Status::Ok ; "valid_2" )]
+#[test_case("category=joke&flag=religious&flag=racist&lang=ru&tag=for_test", Status::Ok ; "valid_iso" )]
+#[test_case("category=story&tag=for_test", Status::Ok ; "story_existent_tag" )]
+#[test_case("category=story&flag=nsfw", Status::NotFound ; "story_non_existent_flag" )]
+#[test_case("category=joke&tag=pending_only", Status::NotFound ; "pending_record" )]
+#[test_case("category=joke&tag=test_alias", Status::Ok ; "tag_alias" )]
+#[test_case("category=joke&tag=For-Test", Status::Ok ; "tag_not_normalized" )]
 fn get_random(filter: &str, status: Status) {
     let path: &str = "/v1/random";
     let client = common::test_client().lock().unwrap();
@@ -37,6 +53,81 @@ fn get_random(filter: &str, status: Status) {
     assert_eq!(s, status);
 }
 
+#[test_case("joke", "tag=for_test", Status::Ok, 2 ; "existent_tag" )]
+#[test_case("joke", "tag=for_test&sort=counter&order=asc", Status::Ok, 2 ; "sort_by_counter" )]
+#[test_case("joke", "tag=for_test&sort=reactions", Status::Ok, 2 ; "sort_by_reactions" )]
+#[test_case("joke", "tag=for_test&flag=religious", Status::Ok, 1 ; "existent_tag_and_flag" )]
+#[test_case("joke", "tag=for_test&page=2", Status::Ok, 0 ; "page_out_of_range" )]
+#[test_case("joke", "tag=pending_only", Status::Ok, 0 ; "pending_record" )]
+#[test_case("story", "tag=for_test", Status::Ok, 1 ; "story_existent_tag" )]
+#[test_case("joke", "cursor=invalid", Status::UnprocessableEntity, 0 ; "invalid_cursor" )]
+#[test_case("unknown", "", Status::NotFound, 0 ; "invalid_category" )]
+fn get_list(category: &str, filter: &str, status: Status, count: usize) {
+    let client = common::test_client().lock().unwrap();
+
+    let resp = client
+        .get(format!("/v1/{}?{}", category, filter))
+        .dispatch();
+
+    assert_eq!(resp.status(), status);
+
+    if status == Status::Ok {
+        let value = response_json_value(resp);
+        assert_eq!(value["records"].as_array().map(|r| r.len()), Some(count));
+    }
+}
+
+#[test]
+fn get_list_by_cursor() {
+    let client = common::test_client().lock().unwrap();
+
+    let resp = client.get("/v1/joke?tag=for_test&limit=1").dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let value = response_json_value(resp);
+    assert_eq!(value["records"].as_array().map(|r| r.len()), Some(1));
+
+    let cursor = value["next"].as_str().expect("next page cursor");
+
+    let resp = client
+        .get(format!("/v1/joke?tag=for_test&limit=1&cursor={}", cursor))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let value = response_json_value(resp);
+    assert_eq!(value["records"].as_array().map(|r| r.len()), Some(1));
+    assert!(value["next"].is_null());
+}
+
+#[test_case("top", "" ; "top_all" )]
+#[test_case("top", "period=week&category=joke" ; "top_week_jokes" )]
+#[test_case("top", "tag=test_alias&lang=russian" ; "top_with_filters" )]
+#[test_case("trending", "" ; "trending_week" )]
+#[test_case("trending", "period=all&category=anecdote&category=story" ; "trending_all" )]
+fn get_ranked(ranking: &str, filter: &str) {
+    let client = common::test_client().lock().unwrap();
+
+    let resp = client
+        .get(format!("/v1/{}?{}", ranking, filter))
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+
+    assert_eq!(resp.status(), Status::Ok);
+
+    let value = response_json_value(resp);
+    let scores: Vec<f64> = value["records"]
+        .as_array()
+        .expect("records")
+        .iter()
+        .map(|r| r["score"].as_f64().expect("score"))
+        .collect();
+
+    assert!(!scores.is_empty());
+    assert!(scores.windows(2).all(|w| w[0] >= w[1]));
+}
+
 /// Тест предполагает использование токена с тарифом не ниже STANDART
 #[test_case(ReactionKind::Laughing ; "reaction_laughing" )]
 #[test_case(ReactionKind::Enraged ; "reaction_enraged" )]
@@ -77,3 +168,263 @@ fn shrimp_reaction(reaction: ReactionKind) {
         assert_eq!(body.tail.reactions[&reaction], 1)
     }
 }
+
+#[test]
+fn shrimp_reaction_per_identity() {
+    let client = common::test_client().lock().unwrap();
+
+    let id = match TestNewJoke::create_test_record(&client, Box::new(TestPadawan::default())) {
+        Ok((_, status, id)) => {
+            assert_eq!(status, Status::Ok);
+            id
+        }
+
+        Err(err) => panic!("\n\nFaild to create test record: {}\n\n", err),
+    };
+    common::approve(&client, "joke", &id);
+
+    let reactions = |client: &Client| {
+        let resp = client
+            .get(format!("/v1/joke/{}", id))
+            .header(apikey!(
+                "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+            ))
+            .dispatch();
+
+        response_json_value(resp)["_meta-data"]["reactions"].clone()
+    };
+
+    // Без Api-Key и токена реакцию поставить нельзя
+    let resp = client
+        .post(format!("/v1/joke/reaction/{}/fire", id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // Повторная реакция не увеличивает счетчик
+    for _ in 0..2 {
+        let resp = client
+            .post(format!("/v1/joke/reaction/{}/laughing", id))
+            .header(apikey!(
+                "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+            ))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+    }
+    assert_eq!(reactions(&client)["laughing"], 1);
+
+    // Смена реакции
+    let resp = client
+        .post(format!("/v1/joke/reaction/{}/fire", id))
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let value = reactions(&client);
+    assert_eq!(value["laughing"], 0);
+    assert_eq!(value["fire"], 1);
+
+    let resp = client
+        .get(format!("/v1/joke/reaction/{}", id))
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(response_json_value(resp)["kind"], "fire");
+
+    // Отмена реакции
+    for status in [Status::Ok, Status::NotFound] {
+        let resp = client
+            .delete(format!("/v1/joke/reaction/{}", id))
+            .header(apikey!(
+                "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+            ))
+            .dispatch();
+        assert_eq!(resp.status(), status);
+    }
+    assert_eq!(reactions(&client)["fire"], 0);
+
+    let resp = client
+        .get(format!("/v1/joke/reaction/{}", id))
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[test]
+fn random_unique() {
+    let path = "/v1/random?category=joke&tag=for_test&unique=true";
+    let client = common::test_client().lock().unwrap();
+
+    // Без Api-Key история не ведется
+    let resp = client.get(path).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    let resp = client
+        .delete("/v1/random/history")
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // В выборке ровно две записи, и они не повторяются
+    let mut ids: Vec<String> = Vec::new();
+    for _ in 0..2 {
+        let resp = client
+            .get(path)
+            .header(apikey!(
+                "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+            ))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+
+        let id = response_json_value(resp)["_id"]
+            .as_str()
+            .expect("record id")
+            .to_string();
+        assert!(!ids.contains(&id));
+        ids.push(id);
+    }
+
+    let resp = client
+        .get(path)
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let value = response_json_value(resp);
+    assert_eq!(value["exhausted"], true);
+    assert_eq!(value["served"], 2);
+
+    // После сброса истории записи снова доступны
+    let resp = client
+        .delete("/v1/random/history")
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(response_json_value(resp)["deleted"], 2);
+
+    let resp = client
+        .get(path)
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert!(response_json_value(resp)["_id"].is_string());
+}
+
+#[test_case("", Status::Ok ; "empty_filter")]
+#[test_case("?category=joke&tag=for_test", Status::Ok ; "existent_tag")]
+#[test_case("?category=joke&tag=base", Status::NotFound ; "non_existent_tag")]
+#[test_case("?tz=%2B03:00", Status::Ok ; "positive_offset")]
+#[test_case("?tz=-0530&period=7", Status::Ok ; "negative_offset")]
+#[test_case("?tz=Europe/Moscow", Status::UnprocessableEntity ; "named_zone")]
+fn get_daily(query: &str, status: Status) {
+    let client = common::test_client().lock().unwrap();
+    let resp = client.get(format!("/v1/daily{}", query)).dispatch();
+
+    assert_eq!(resp.status(), status);
+}
+
+#[test]
+fn daily_is_stable() {
+    let path = "/v1/daily?category=joke&tag=for_test";
+    let client = common::test_client().lock().unwrap();
+
+    let mut ids: Vec<String> = Vec::new();
+    for _ in 0..2 {
+        let resp = client
+            .get(path)
+            .header(apikey!(
+                "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+            ))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+
+        let cache = resp.headers().get_one("Cache-Control").unwrap_or_default();
+        assert!(cache.starts_with("public, max-age="));
+
+        let value = response_json_value(resp);
+        assert!(value["date"].is_string());
+        ids.push(value["_id"].as_str().expect("record id").to_string());
+    }
+
+    // В течение дня все получают одну и ту же запись
+    assert_eq!(ids[0], ids[1]);
+}
+
+#[test]
+fn daily_skips_rejected() {
+    let client = common::test_client().lock().unwrap();
+    let master =
+        account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+
+    let salt: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(16)
+        .map(char::from)
+        .collect::<String>()
+        .to_lowercase();
+    let tag = format!("daily_{}", salt);
+
+    // Две одобренные записи с собственным тегом, чтобы запись дня выбиралась только из них
+    let mut ids: Vec<String> = Vec::new();
+    for n in 0..2 {
+        let resp = client
+            .post("/v1/joke/new")
+            .header(bearer!((master.access_token)))
+            .header(ContentType::JSON)
+            .body(json_string!({
+                "text": format!("Шутка дня номер {} {}", n, salt),
+                "tags": [&tag],
+                "language": "ru"
+            }))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+
+        let id = response_json_value(resp)["id"]
+            .as_str()
+            .expect("record id")
+            .to_string();
+        common::approve(&client, "joke", &id);
+        ids.push(id);
+    }
+
+    let path = format!("/v1/daily?category=joke&tag={}", tag);
+
+    let resp = client.get(&path).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let daily = response_json_value(resp)["_id"]
+        .as_str()
+        .expect("record id")
+        .to_string();
+
+    let resp = client
+        .put(format!("/v1/joke/{}/status/reject", daily))
+        .header(bearer!((master.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({ "reason": "Запись больше не подходит" }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Отклоненная запись перестает быть записью дня
+    let resp = client.get(&path).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let other = response_json_value(resp)["_id"]
+        .as_str()
+        .expect("record id")
+        .to_string();
+    assert_ne!(other, daily);
+    assert!(ids.contains(&other));
+}
@@ -0,0 +1,180 @@
+mod common;
+
+use flate2::read::GzDecoder;
+use rocket::{
+    http::{ContentType, Header, Status},
+    local::blocking::Client,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+
+use common::{
+    accounts::{self as account, TestMaster, TestPadawan},
+    joke::TestNewJoke,
+};
+
+#[derive(Deserialize, Debug)]
+struct ManifestEntryResp {
+    category: String,
+    count: usize,
+    removed: usize,
+    sha256: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct ManifestResp {
+    until: i64,
+    total: usize,
+    removed: usize,
+    sha256: String,
+    categories: Vec<ManifestEntryResp>,
+}
+
+/// Строки снимка без последней строки манифеста и сам манифест
+fn take_snapshot(client: &Client, query: &str) -> (String, ManifestResp) {
+    let resp = client
+        .get(format!("/v1/snapshot{}", query))
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(
+        resp.content_type(),
+        Some(ContentType::new("application", "gzip"))
+    );
+
+    let mut data = String::new();
+    GzDecoder::new(&resp.into_bytes().expect("snapshot")[..])
+        .read_to_string(&mut data)
+        .expect("gzip data");
+
+    let (lines, last) = data
+        .trim_end()
+        .rsplit_once('\n')
+        .unwrap_or(("", data.trim_end()));
+    let manifest: serde_json::Value = serde_json::from_str(last).expect("manifest line");
+    let manifest: ManifestResp =
+        serde_json::from_value(manifest["_manifest"].clone()).expect("manifest");
+
+    let lines = if lines.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", lines)
+    };
+
+    (lines, manifest)
+}
+
+#[test]
+fn snapshot_without_enterprice() {
+    let client = common::test_client().lock().unwrap();
+
+    let resp = client.get("/v1/snapshot").dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+}
+
+#[test]
+fn snapshot_matches_manifest() {
+    let client = common::test_client().lock().unwrap();
+
+    let (data, manifest) = take_snapshot(&client, "");
+    assert!(manifest.total > 0);
+    assert_eq!(manifest.removed, 0);
+    assert_eq!(
+        manifest.categories.iter().map(|c| c.count).sum::<usize>(),
+        manifest.total
+    );
+    assert!(manifest.categories.iter().any(|c| c.category == "joke"));
+    assert!(manifest.categories.iter().all(|c| c.sha256.len() == 64));
+
+    assert_eq!(data.lines().count(), manifest.total);
+    assert_eq!(
+        format!("{:x}", Sha256::digest(data.as_bytes())),
+        manifest.sha256
+    );
+
+    let record: serde_json::Value = serde_json::from_str(data.lines().next().unwrap()).unwrap();
+    assert!(record["_header"].is_object());
+    assert!(record["_meta-data"].is_object());
+
+    // Тот же `until` дает тот же снимок
+    let (_, again) = take_snapshot(&client, &format!("?until={}", manifest.until));
+    assert_eq!(again.sha256, manifest.sha256);
+}
+
+#[test]
+fn snapshot_incremental() {
+    let client = common::test_client().lock().unwrap();
+
+    // Записей, измененных после 2100 года, нет
+    let (data, manifest) = take_snapshot(&client, "?since=4102444800000");
+    assert_eq!(manifest.total, 0);
+    assert_eq!(manifest.removed, 0);
+    assert!(data.is_empty());
+}
+
+#[test]
+fn snapshot_incremental_changes() {
+    let client = common::test_client().lock().unwrap();
+
+    let master =
+        account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+    let (_, status, id) =
+        TestNewJoke::create_test_record(&client, Box::new(TestPadawan::default()))
+            .expect("test record");
+    assert_eq!(status, Status::Ok);
+
+    let (_, before) = take_snapshot(&client, "");
+
+    // Одобренная после предыдущего снимка запись попадает в следующий
+    let resp = client
+        .put(format!("/v1/joke/{}/status/approve", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let (data, _) = take_snapshot(&client, &format!("?since={}", before.until));
+    assert!(data
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .any(|record| record["id"] == id.as_str()));
+
+    let (_, approved) = take_snapshot(&client, "");
+
+    // Реакция меняет счетчики записи, и запись снова попадает в снимок
+    let resp = client
+        .post(format!("/v1/joke/reaction/{}/fire", id))
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let (data, _) = take_snapshot(&client, &format!("?since={}", approved.until));
+    assert!(data
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .any(|record| record["id"] == id.as_str()));
+
+    let (_, approved) = take_snapshot(&client, "");
+
+    // Удаленная запись приходит отметкой
+    let resp = client
+        .delete(format!("/v1/joke/{}", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let (data, manifest) = take_snapshot(&client, &format!("?since={}", approved.until));
+    assert!(manifest.removed > 0);
+    assert_eq!(
+        manifest.categories.iter().map(|c| c.removed).sum::<usize>(),
+        manifest.removed
+    );
+    assert!(data
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .any(|line| line["_tombstone"]["id"] == id.as_str()));
+}
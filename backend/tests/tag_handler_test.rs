@@ -0,0 +1,145 @@
+mod common;
+
+use rand::{distributions::Alphanumeric, Rng};
+use rocket::http::{ContentType, Header, Status};
+use serde::Deserialize;
+
+use common::{
+    accounts::{self as account, TestMaster, TestPadawan},
+    joke::TestNewJoke,
+};
+
+#[derive(Deserialize, Debug)]
+struct TagResp {
+    name: String,
+    aliases: Vec<String>,
+    count: i64,
+}
+
+#[test]
+fn tags_list() {
+    let client = common::test_client().lock().unwrap();
+
+    let resp = client.get("/v1/tags?q=test_al").dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    #[allow(unused_parens)]
+    let body = assert_body!(resp, (Vec<TagResp>));
+
+    assert_eq!(body.len(), 1);
+    assert_eq!(body[0].name, "for_test");
+    assert!(body[0].aliases.contains(&"test_alias".to_string()));
+    assert!(body[0].count > 0);
+}
+
+#[test]
+fn tag_merge_by_padawan() {
+    let client = common::test_client().lock().unwrap();
+    let tokens =
+        account::try_login(&client, Box::new(TestPadawan::default())).expect("valid tokens");
+
+    let resp = client
+        .post("/v1/tags/merge")
+        .header(bearer!((tokens.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({
+            "source": "general",
+            "target": "for_test"
+        }))
+        .dispatch();
+
+    assert_eq!(resp.status(), Status::Forbidden);
+}
+
+#[test]
+fn tag_merge_by_master() {
+    let client = common::test_client().lock().unwrap();
+    let tokens =
+        account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+
+    // Новый тег регистрируется при создании записи
+    let resp = client
+        .post("/v1/joke/new")
+        .header(bearer!((tokens.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({
+            "text": "Как называют программиста, который боится слияний? Мерджефоб.",
+            "tags": ["Merge Source"]
+        }))
+        .dispatch();
+
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client
+        .post("/v1/tags/merge")
+        .header(bearer!((tokens.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({
+            "source": "merge source",
+            "target": "Слияние"
+        }))
+        .dispatch();
+
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Старое имя стало синонимом
+    let resp = client.get("/v1/tags?q=merge_source").dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    #[allow(unused_parens)]
+    let body = assert_body!(resp, (Vec<TagResp>));
+
+    assert_eq!(body.len(), 1);
+    assert_eq!(body[0].name, "слияние");
+    assert!(body[0].aliases.contains(&"merge_source".to_string()));
+
+    // Слияние тега с самим собой
+    let resp = client
+        .post("/v1/tags/merge")
+        .header(bearer!((tokens.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({
+            "source": "merge_source",
+            "target": "слияние"
+        }))
+        .dispatch();
+
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+}
+
+#[test]
+fn invalid_tags_not_registered() {
+    let client = common::test_client().lock().unwrap();
+    let tokens =
+        account::try_login(&client, Box::new(TestPadawan::default())).expect("valid tokens");
+
+    let tag = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(8)
+        .map(char::from)
+        .collect::<String>()
+        .to_lowercase();
+
+    let many: Vec<String> = (0..11).map(|i| format!("{}{}", tag, i)).collect();
+    for tags in [vec![tag.clone(), String::from("<script>")], many] {
+        let resp = client
+            .post("/v1/joke/new")
+            .header(bearer!((tokens.access_token)))
+            .header(ContentType::JSON)
+            .body(json_string!({
+                "text": TestNewJoke::default().text,
+                "tags": tags
+            }))
+            .dispatch();
+
+        assert_eq!(resp.status(), Status::UnprocessableEntity);
+    }
+
+    // Теги отклоненной записи не попадают в реестр
+    let resp = client.get(format!("/v1/tags?q={}", tag)).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    #[allow(unused_parens)]
+    let body = assert_body!(resp, (Vec<TagResp>));
+    assert!(body.is_empty());
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use rocket::{
+    http::{ContentType, Header, Status},
+    local::blocking::Client,
+};
+use std::sync::MutexGuard;
+
+use common::{
+    accounts::{self as account, TestPadawan},
+    joke::TestNewJoke,
+};
+use jokehub::model::account::security::Tokens;
+
+fn create_joke(client: &MutexGuard<Client>, tokens: &Tokens, text: &str, lang: &str) -> String {
+    let resp = client
+        .post("/v1/joke/new")
+        .header(bearer!((tokens.access_token)))
+        .header(ContentType::JSON)
+        .body(json_string!({"text": text, "lang": lang}))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    common::response_json_value(resp)["id"]
+        .as_str()
+        .expect("record id")
+        .to_string()
+}
+
+#[test]
+fn link_translations() {
+    let client = common::test_client().lock().unwrap();
+    let tokens =
+        account::try_login(&client, Box::new(TestPadawan::default())).expect("valid tokens");
+
+    let salt = TestNewJoke::default().text;
+    let ru = create_joke(
+        &client,
+        &tokens,
+        &format!("Почему программисты путают Хэллоуин с Рождеством? {}", salt),
+        "ru",
+    );
+    let en = create_joke(
+        &client,
+        &tokens,
+        &format!(
+            "Why do programmers always mix up Halloween and Christmas? Because Oct 31 equals Dec 25. {}",
+            salt
+        ),
+        "en",
+    );
+    let other_ru = create_joke(&client, &tokens, &TestNewJoke::default().text, "ru");
+
+    common::approve(&client, "joke", &ru);
+
+    // Перевода еще нет
+    let resp = client.get(format!("/v1/joke/{}?lang=en", ru)).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    let resp = client
+        .put(format!("/v1/joke/{}/translations/{}", ru, en))
+        .header(bearer!((tokens.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(
+        common::response_json_value(resp).as_array().unwrap().len(),
+        2
+    );
+
+    // Перевод на модерации никому не виден
+    let resp = client
+        .get(format!("/v1/joke/{}/translations", ru))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert!(common::response_json_value(resp)
+        .as_array()
+        .unwrap()
+        .is_empty());
+
+    let resp = client
+        .get(format!("/v1/joke/{}/translations", en))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    common::approve(&client, "joke", &en);
+
+    // Второй записи на русском в группе быть не может
+    let resp = client
+        .put(format!("/v1/joke/{}/translations/{}", en, other_ru))
+        .header(bearer!((tokens.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+
+    let resp = client
+        .get(format!("/v1/joke/{}?lang=en", ru))
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(common::response_json_value(resp)["id"], en.as_str());
+
+    // Запись на запрошенном языке отдается сама
+    let resp = client
+        .get(format!("/v1/joke/{}?lang=ru", ru))
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    assert_eq!(common::response_json_value(resp)["id"], ru.as_str());
+
+    let resp = client.get(format!("/v1/joke/{}?lang=xx", ru)).dispatch();
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+
+    let resp = client
+        .get(format!("/v1/joke/{}/translations", en))
+        .dispatch();
+    let translations = common::response_json_value(resp);
+    assert_eq!(translations[0]["id"], ru.as_str());
+    assert_eq!(translations[0]["language"], "ru");
+
+    let resp = client
+        .delete(format!("/v1/joke/{}/translations", en))
+        .header(bearer!((tokens.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Группа из одной записи удаляется
+    let resp = client
+        .delete(format!("/v1/joke/{}/translations", ru))
+        .header(bearer!((tokens.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    let resp = client.get(format!("/v1/joke/{}?lang=en", ru)).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[test]
+fn random_with_translations() {
+    let client = common::test_client().lock().unwrap();
+
+    let resp = client
+        .get("/v1/random?category=story&translations=true")
+        .header(apikey!(
+            "5Jh0Y7u6zJfK1PDdbd1GiJ9ahvoHoJz55FfmQQr8oSz7dcoi3o"
+        ))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    assert!(common::response_json_value(resp)["translations"].is_array());
+}
@@ -0,0 +1,165 @@
+mod common;
+
+use rocket::http::{ContentType, Header, Status};
+
+use common::{
+    accounts::{self as account, TestMaster, TestPadawan, TestSith},
+    joke::TestNewJoke,
+};
+
+#[test]
+fn trash_and_restore() {
+    let client = common::test_client().lock().unwrap();
+
+    let (tokens, status, id) =
+        TestNewJoke::create_test_record(&client, Box::new(TestPadawan::default()))
+            .expect("test record");
+    assert_eq!(status, Status::Ok);
+
+    let master =
+        account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+    common::approve(&client, "joke", &id);
+
+    let resp = client
+        .delete(format!("/v1/joke/{}", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Запись в корзине не видна и не удаляется повторно
+    let resp = client.get(format!("/v1/joke/{}", id)).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    let resp = client
+        .delete(format!("/v1/joke/{}", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // Корзину видят только модераторы
+    let resp = client
+        .get("/v1/trash?category=joke")
+        .header(bearer!((tokens.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    let resp = client
+        .get("/v1/trash?category=joke&limit=100")
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let trash = common::response_json_value(resp);
+    let record = trash
+        .as_array()
+        .expect("trashed records")
+        .iter()
+        .find(|record| record["id"] == id.as_str())
+        .expect("trashed record");
+    assert_eq!(record["_meta-data"]["trash"]["deleted_by"], "tmaster");
+
+    let resp = client
+        .put(format!("/v1/joke/{}/trash/restore", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client.get(format!("/v1/joke/{}", id)).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Восстановить можно только запись из корзины
+    let resp = client
+        .put(format!("/v1/joke/{}/trash/restore", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[test]
+fn trash_purge() {
+    let client = common::test_client().lock().unwrap();
+
+    let (_, status, id) =
+        TestNewJoke::create_test_record(&client, Box::new(TestPadawan::default()))
+            .expect("test record");
+    assert_eq!(status, Status::Ok);
+
+    let master =
+        account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+    let sith = account::try_login(&client, Box::new(TestSith::default())).expect("valid tokens");
+
+    // Из корзины удаляется только запись, которая в нее попала
+    let resp = client
+        .delete(format!("/v1/joke/{}/trash/purge", id))
+        .header(bearer!((sith.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    let resp = client
+        .delete(format!("/v1/joke/{}", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Окончательное удаление доступно только Sith
+    let resp = client
+        .delete(format!("/v1/joke/{}/trash/purge", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    let resp = client
+        .delete(format!("/v1/joke/{}/trash/purge", id))
+        .header(bearer!((sith.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client
+        .put(format!("/v1/joke/{}/trash/restore", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[test]
+fn trash_frees_text() {
+    let client = common::test_client().lock().unwrap();
+
+    let padawan =
+        account::try_login(&client, Box::new(TestPadawan::default())).expect("valid tokens");
+    let master =
+        account::try_login(&client, Box::new(TestMaster::default())).expect("valid tokens");
+    let joke = TestNewJoke::default();
+
+    let create = || {
+        client
+            .post("/v1/joke/new")
+            .header(bearer!((padawan.access_token)))
+            .header(ContentType::JSON)
+            .body(json_string!({"text": joke.text, "language": "ru"}))
+            .dispatch()
+    };
+
+    let resp = create();
+    assert_eq!(resp.status(), Status::Ok);
+    let id = common::response_json_value(resp)["id"]
+        .as_str()
+        .expect("record id")
+        .to_string();
+
+    let resp = client
+        .delete(format!("/v1/joke/{}", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Запись в корзине не мешает добавить такую же заново
+    assert_eq!(create().status(), Status::Ok);
+
+    // Но вернуть ее из корзины, пока есть такая же, нельзя
+    let resp = client
+        .put(format!("/v1/joke/{}/trash/restore", id))
+        .header(bearer!((master.access_token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+}
@@ -46,7 +46,8 @@ mongo --username $MONGO_USER --password $MONGO_USER_PASSWORD --authenticationDat
     db.createCollection("anecdote");
     db.anecdote.createIndex(
         {
-            "text": 1
+            "text": 1,
+            "_meta-data.trash.deleted_at": 1
         }, 
         {
             "unique": true, 
@@ -61,7 +62,8 @@ mongo --username $MONGO_USER --password $MONGO_USER_PASSWORD --authenticationDat
     db.createCollection("joke");
     db.joke.createIndex(
         {
-            "text": 1
+            "text": 1,
+            "_meta-data.trash.deleted_at": 1
         }, 
         {
             "unique": true, 
@@ -78,6 +80,7 @@ mongo --username $MONGO_USER --password $MONGO_USER_PASSWORD --authenticationDat
         {
             "setup": 1,
             "punchline": 1,
+            "_meta-data.trash.deleted_at": 1
         }, 
         {
             "unique": true, 
@@ -93,6 +96,147 @@ mongo --username $MONGO_USER --password $MONGO_USER_PASSWORD --authenticationDat
         }
     );
 
+    db.createCollection("story");
+    db.story.createIndex(
+        {
+            "title": 1,
+            "_meta-data.trash.deleted_at": 1
+        }, 
+        {
+            "unique": true, 
+            "partialFilterExpression": {
+                "title": {
+                    \$type: "string"
+                }
+            }
+        }
+    );
+
+    db.createCollection("revisions");
+    db.revisions.createIndex(
+        {
+            "record_id": 1,
+            "created_at": -1
+        }
+    );
+
+    db.createCollection("flag_audit");
+    db.flag_audit.createIndex(
+        {
+            "record_id": 1,
+            "created_at": -1
+        }
+    );
+
+    db.createCollection("reactions");
+    db.reactions.createIndex(
+        {
+            "record_id": 1,
+            "identity": 1
+        },
+        {
+            "unique": true
+        }
+    );
+
+    db.createCollection("served");
+    db.served.createIndex(
+        {
+            "identity": 1,
+            "served_at": -1
+        }
+    );
+    db.served.createIndex(
+        {
+            "served_at" : 1
+        }, 
+        {
+            "expireAfterSeconds" : 60 * 60 * 24 * 30
+        }
+    );
+
+    db.createCollection("daily");
+    db.daily.createIndex(
+        {
+            "key": 1,
+            "date": -1
+        }
+    );
+
+    db.createCollection("fingerprints");
+    db.fingerprints.createIndex(
+        {
+            "bands": 1
+        }
+    );
+
+    db.createCollection("translations");
+    db.translations.createIndex(
+        {
+            "records": 1
+        },
+        {
+            "unique": true
+        }
+    );
+
+    db.createCollection("comments");
+    db.comments.createIndex(
+        {
+            "record_id": 1,
+            "parent_id": 1,
+            "created_at": 1
+        }
+    );
+
+    db.createCollection("comment_reactions");
+    db.comment_reactions.createIndex(
+        {
+            "comment_id": 1,
+            "identity": 1
+        },
+        {
+            "unique": true
+        }
+    );
+    db.comment_reactions.createIndex(
+        {
+            "record_id": 1
+        }
+    );
+
+    db.createCollection("follows");
+    db.follows.createIndex(
+        {
+            "follower": 1,
+            "author": 1
+        },
+        {
+            "unique": true
+        }
+    );
+    db.follows.createIndex(
+        {
+            "author": 1,
+            "created_at": -1
+        }
+    );
+
+    db.createCollection("tombstones");
+    db.tombstones.createIndex(
+        {
+            "category": 1,
+            "removed_at": 1
+        }
+    );
+
+    db.createCollection("tags");
+    db.tags.createIndex(
+        {
+            "aliases": 1
+        }
+    );
+
     db.createCollection("sessions");
     db.sessions.createIndex(
         {
@@ -136,15 +280,16 @@ mongo --username $MONGO_USER --password $MONGO_USER_PASSWORD --authenticationDat
     db.createCollection("favorite");
     db.favorite.createIndex(
         {
-            "content_id": 1,
-        }, 
+            "master": 1,
+            "content_id": 1
+        },
         {
-            "unique": true, 
-            "partialFilterExpression": {
-                "content_id": {
-                    \$type: "string"
-                },
-            }
+            "unique": true
+        }
+    );
+    db.favorite.createIndex(
+        {
+            "content_id": 1
         }
     );
 EOF
\ No newline at end of file
@@ -112,7 +112,8 @@ mongo --username $MONGO_USER --password $MONGO_USER_PASSWORD --authenticationDat
     db.createCollection("anecdote");
     db.anecdote.createIndex(
         {
-            "text": 1
+            "text": 1,
+            "_meta-data.trash.deleted_at": 1
         }, 
         {
             "unique": true, 
@@ -123,11 +124,56 @@ mongo --username $MONGO_USER --password $MONGO_USER_PASSWORD --authenticationDat
             }
         }
     );
+    db.anecdote.insertOne(
+        {
+            "_id": "31b923b0-4241-4c32-ac06-f560468fac20",
+            "_header": { "counter": NumberLong(0), "timestamp": NumberLong(new Date().getTime()) },
+            "category": "anecdote",
+            "_language": "russian",
+            "text": "Штирлиц долго смотрел в одну точку. Потом в другую. «Двоеточие!» — догадался Штирлиц.",
+            "_meta-data": {
+                "flags": {
+                    "nsfw": false,
+                    "religious": false,
+                    "political": false,
+                    "racist": false,
+                    "sexist": false
+                },
+                "author": "shavedkiwi",
+                "tags": ["search_test"],
+                "reactions": {},
+                "language": "ru"
+            }
+        }
+    );
+    db.anecdote.insertOne(
+        {
+            "_id": "31b923b0-4241-4c32-ac06-f560468fac21",
+            "_header": { "counter": NumberLong(0), "timestamp": NumberLong(new Date().getTime()) },
+            "category": "anecdote",
+            "_language": "english",
+            "text": "Why don't scientists trust atoms? Because they make up everything.",
+            "_meta-data": {
+                "flags": {
+                    "nsfw": false,
+                    "religious": false,
+                    "political": false,
+                    "racist": false,
+                    "sexist": false
+                },
+                "author": "shavedkiwi",
+                "tags": ["search_test"],
+                "reactions": {},
+                "language": "en"
+            }
+        }
+    );
 
     db.createCollection("joke");
     db.joke.createIndex(
         {
-            "text": 1
+            "text": 1,
+            "_meta-data.trash.deleted_at": 1
         }, 
         {
             "unique": true, 
@@ -143,6 +189,7 @@ mongo --username $MONGO_USER --password $MONGO_USER_PASSWORD --authenticationDat
             "_id": "11b923b0-4241-4c32-ac06-f560468fac20",
             "_header": { "counter": NumberLong(0), "timestamp": NumberLong(new Date().getTime()) },
             "category": "joke",
+            "_language": "russian",
             "text": "test_joke_record_for_random__1",
             "_meta-data": {
                 "flags": {
@@ -155,7 +202,7 @@ mongo --username $MONGO_USER --password $MONGO_USER_PASSWORD --authenticationDat
                 "author": "shavedkiwi",
                 "tags": ["general"],
                 "reactions": {},
-                "language": "russian"
+                "language": "ru"
             }
         }
     );
@@ -164,6 +211,7 @@ mongo --username $MONGO_USER --password $MONGO_USER_PASSWORD --authenticationDat
             "_id": "11b923b0-4241-4c32-ac06-f560468fac21",
             "_header": { "counter": NumberLong(0), "timestamp": NumberLong(new Date().getTime()) },
             "category": "joke",
+            "_language": "russian",
             "text": "test_joke_record_for_random__2",
             "_meta-data": {
                 "flags": {
@@ -176,7 +224,7 @@ mongo --username $MONGO_USER --password $MONGO_USER_PASSWORD --authenticationDat
                 "author": "shavedkiwi",
                 "tags": ["general"],
                 "reactions": {},
-                "language": "russian"
+                "language": "ru"
             }
         }
     );
@@ -185,6 +233,7 @@ mongo --username $MONGO_USER --password $MONGO_USER_PASSWORD --authenticationDat
             "_id": "11b923b0-4241-4c32-ac06-f560468fac22",
             "_header": { "counter": NumberLong(0), "timestamp": NumberLong(new Date().getTime()) },
             "category": "joke",
+            "_language": "russian",
             "text": "test_joke_record_for_random__3",
             "_meta-data": {
                 "flags": {
@@ -197,7 +246,7 @@ mongo --username $MONGO_USER --password $MONGO_USER_PASSWORD --authenticationDat
                 "author": "shavedkiwi",
                 "tags": ["for_test"],
                 "reactions": {},
-                "language": "russian"
+                "language": "ru"
             }
         }
     );
@@ -206,6 +255,7 @@ mongo --username $MONGO_USER --password $MONGO_USER_PASSWORD --authenticationDat
             "_id": "11b923b0-4241-4c32-ac06-f560468fac23",
             "_header": { "counter": NumberLong(0), "timestamp": NumberLong(new Date().getTime()) },
             "category": "joke",
+            "_language": "english",
             "text": "test_joke_record_for_random__4",
             "_meta-data": {
                 "flags": {
@@ -218,7 +268,30 @@ mongo --username $MONGO_USER --password $MONGO_USER_PASSWORD --authenticationDat
                 "author": "shavedkiwi",
                 "tags": ["for_test"],
                 "reactions": {},
-                "language": "english"
+                "language": "en"
+            }
+        }
+    );
+    db.joke.insertOne(
+        {
+            "_id": "11b923b0-4241-4c32-ac06-f560468fac24",
+            "_header": { "counter": NumberLong(0), "timestamp": NumberLong(new Date().getTime()) },
+            "category": "joke",
+            "_language": "russian",
+            "text": "test_joke_record_for_random__5",
+            "_meta-data": {
+                "flags": {
+                    "nsfw": false,
+                    "religious": false,
+                    "political": false,
+                    "racist": false,
+                    "sexist": false
+                },
+                "author": "shavedkiwi",
+                "tags": ["pending_only"],
+                "reactions": {},
+                "language": "ru",
+                "status": { "kind": "pending" }
             }
         }
     );
@@ -229,6 +302,7 @@ mongo --username $MONGO_USER --password $MONGO_USER_PASSWORD --authenticationDat
         {
             "setup": 1,
             "punchline": 1,
+            "_meta-data.trash.deleted_at": 1
         }, 
         {
             "unique": true, 
@@ -243,4 +317,190 @@ mongo --username $MONGO_USER --password $MONGO_USER_PASSWORD --authenticationDat
             }
         }
     );
+
+    db.createCollection("story");
+    db.story.createIndex(
+        {
+            "title": 1,
+            "_meta-data.trash.deleted_at": 1
+        }, 
+        {
+            "unique": true, 
+            "partialFilterExpression": {
+                "title": {
+                    \$type: "string"
+                }
+            }
+        }
+    );
+
+    db.createCollection("revisions");
+    db.revisions.createIndex(
+        {
+            "record_id": 1,
+            "created_at": -1
+        }
+    );
+
+    db.createCollection("flag_audit");
+    db.flag_audit.createIndex(
+        {
+            "record_id": 1,
+            "created_at": -1
+        }
+    );
+
+    db.createCollection("reactions");
+    db.reactions.createIndex(
+        {
+            "record_id": 1,
+            "identity": 1
+        },
+        {
+            "unique": true
+        }
+    );
+
+    db.createCollection("served");
+    db.served.createIndex(
+        {
+            "identity": 1,
+            "served_at": -1
+        }
+    );
+    db.served.createIndex(
+        {
+            "served_at" : 1
+        }, 
+        {
+            "expireAfterSeconds" : 60 * 60 * 24 * 30
+        }
+    );
+
+    db.createCollection("daily");
+    db.daily.createIndex(
+        {
+            "key": 1,
+            "date": -1
+        }
+    );
+
+    db.createCollection("fingerprints");
+    db.fingerprints.createIndex(
+        {
+            "bands": 1
+        }
+    );
+
+    db.createCollection("translations");
+    db.translations.createIndex(
+        {
+            "records": 1
+        },
+        {
+            "unique": true
+        }
+    );
+
+    db.createCollection("comments");
+    db.comments.createIndex(
+        {
+            "record_id": 1,
+            "parent_id": 1,
+            "created_at": 1
+        }
+    );
+
+    db.createCollection("comment_reactions");
+    db.comment_reactions.createIndex(
+        {
+            "comment_id": 1,
+            "identity": 1
+        },
+        {
+            "unique": true
+        }
+    );
+    db.comment_reactions.createIndex(
+        {
+            "record_id": 1
+        }
+    );
+
+    db.createCollection("favorite");
+    db.favorite.createIndex(
+        {
+            "master": 1,
+            "content_id": 1
+        },
+        {
+            "unique": true
+        }
+    );
+    db.favorite.createIndex(
+        {
+            "content_id": 1
+        }
+    );
+
+    db.createCollection("follows");
+    db.follows.createIndex(
+        {
+            "follower": 1,
+            "author": 1
+        },
+        {
+            "unique": true
+        }
+    );
+    db.follows.createIndex(
+        {
+            "author": 1,
+            "created_at": -1
+        }
+    );
+
+    db.createCollection("tombstones");
+    db.tombstones.createIndex(
+        {
+            "category": 1,
+            "removed_at": 1
+        }
+    );
+
+    db.createCollection("tags");
+    db.tags.createIndex(
+        {
+            "aliases": 1
+        }
+    );
+    db.tags.insertMany(
+        [
+            { "_id": "general", "aliases": [] },
+            { "_id": "for_test", "aliases": ["test_alias"] }
+        ]
+    );
+    db.story.insertOne(
+        {
+            "_id": "21b923b0-4241-4c32-ac06-f560468fac20",
+            "_header": { "counter": NumberLong(0), "timestamp": NumberLong(new Date().getTime()) },
+            "category": "story",
+            "_language": "russian",
+            "title": "test_story_record_for_random__1",
+            "text": ["test_story_record_for_random__1 paragraph"],
+            "_meta-data": {
+                "flags": {
+                    "nsfw": false,
+                    "religious": false,
+                    "political": false,
+                    "racist": false,
+                    "sexist": false
+                },
+                "author": "shavedkiwi",
+                "tags": ["for_test"],
+                "reactions": {},
+                "language": "ru"
+            }
+        }
+    );
 EOF
\ No newline at end of file